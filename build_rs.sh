@@ -2,6 +2,7 @@
 
 KERNEL_SOURCE_PATH="./os"
 USER_LIB_SOURCE_PATH="./user"
+EFS_FUSE_SOURCE_PATH="./easy-fs-fuse"
 
 # Check running param
 os_build_flag=true
@@ -80,6 +81,22 @@ if [ $user_build_flag == true ]; then
   fi
 
   cd ../ || exit 100
+
+  # Pack ULP into the file system image
+  cd $EFS_FUSE_SOURCE_PATH || exit 100
+
+  echo "[INFO] Packing file system image"
+
+  mkdir -p ../os/target
+  cargo run --release -- -s ../user/src/bin/ -t ../user/target/riscv64gc-unknown-none-elf/release/ -o ../os/target/fs.img
+
+  result=$?
+  if [ $result != 0 ]; then
+    echo "[FATAL] Failed to pack file system image. Return with code $result"
+    exit 4
+  fi
+
+  cd ../ || exit 100
 fi
 
 # Build os kernel
@@ -109,7 +126,7 @@ if [ $os_build_flag == true ]; then
     ;;
   esac
 
-  cargo build --release
+  cargo build --release --features board_qemu
   unset LOG
 
   result=$?
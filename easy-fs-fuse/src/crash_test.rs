@@ -0,0 +1,223 @@
+//! easy-fs-fuse/src/crash_test.rs <br>
+//! 崩溃注入测试：在工作负载的第n次块写入处模拟掉电，此后的写入都不会落盘；
+//! 重新挂载（重放日志）后用fsck检查一致性，并检查已完成的操作没有丢失、文件内容没有被破坏 <br>
+//! 对工作负载中每一次块写入处的掉电各测试一次
+
+use std::sync::{Arc, Mutex};
+
+use easy_fs::{
+    block_cache_release_device, block_cache_sync_device, fsck, BlockDevice, EasyFileSystem, Inode,
+    BLOCK_SZ,
+};
+
+/// 测试镜像的块数（4MiB）
+const TOTAL_BLOCKS: u32 = 8192;
+
+type Block = [u8; BLOCK_SZ];
+
+/// 内存中的块设备，可在第crash_at次写入处模拟掉电
+struct CrashDevice(Mutex<CrashDeviceInner>);
+
+struct CrashDeviceInner {
+    /// 设备的当前内容，掉电后的写入仍会写入其中，使工作负载能继续运行至结束
+    blocks: Vec<Block>,
+    /// 掉电时已落盘的内容
+    persisted: Option<Vec<Block>>,
+    /// 已发生的写入次数
+    writes: usize,
+    crash_at: Option<usize>,
+}
+
+impl CrashDevice {
+    fn new(blocks: Vec<Block>, crash_at: Option<usize>) -> Arc<Self> {
+        Arc::new(Self(Mutex::new(CrashDeviceInner {
+            blocks,
+            persisted: None,
+            writes: 0,
+            crash_at,
+        })))
+    }
+
+    /// 已落盘的内容，没有掉电时即为当前内容
+    fn persisted(&self) -> Vec<Block> {
+        let inner = self.0.lock().unwrap();
+        inner
+            .persisted
+            .clone()
+            .unwrap_or_else(|| inner.blocks.clone())
+    }
+
+    fn writes(&self) -> usize {
+        self.0.lock().unwrap().writes
+    }
+}
+
+impl BlockDevice for CrashDevice {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        buf.copy_from_slice(&self.0.lock().unwrap().blocks[block_id]);
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let mut inner = self.0.lock().unwrap();
+        if inner.crash_at == Some(inner.writes) {
+            inner.persisted = Some(inner.blocks.clone());
+        }
+        inner.blocks[block_id].copy_from_slice(buf);
+        inner.writes += 1;
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.0.lock().unwrap().blocks.len()
+    }
+}
+
+/// 测试文件的内容
+fn pattern(seed: u8, len: usize) -> Vec<u8> {
+    (0..len).map(|i| (i % 251) as u8 ^ seed).collect()
+}
+
+/// 工作负载中出现的文件，步骤从1开始编号
+struct ExpectedFile {
+    path: &'static str,
+    /// 创建该文件的步骤
+    created: usize,
+    /// 删除该文件的步骤
+    removed: Option<usize>,
+    /// 文件的完整内容，目录为None
+    content: Option<Vec<u8>>,
+}
+
+fn expected_files() -> Vec<ExpectedFile> {
+    let file = |path, created, removed, content| ExpectedFile {
+        path,
+        created,
+        removed,
+        content,
+    };
+    let mut c = vec![0u8; 2 * BLOCK_SZ];
+    c.extend(pattern(3, 10));
+    vec![
+        file("dir", 1, None, None),
+        file("dir/a", 2, Some(6), Some(pattern(1, 3 * BLOCK_SZ + 100))),
+        file("big", 3, Some(7), Some(pattern(2, 120 * BLOCK_SZ))),
+        file("dir/b", 4, None, Some(pattern(1, 3 * BLOCK_SZ + 100))),
+        file("dir/sub", 5, None, None),
+        file("dir/sub/c", 5, None, Some(c)),
+    ]
+}
+
+/// 运行工作负载，返回每一步完成时设备的写入次数
+fn workload(root: &Inode, device: &Arc<CrashDevice>) -> Vec<usize> {
+    let block_device: Arc<dyn BlockDevice> = device.clone();
+    let mut marks = Vec::new();
+    let dir = root.create_dir("dir").unwrap();
+    marks.push(device.writes());
+    let a = dir.create("a").unwrap();
+    a.write_at(0, &pattern(1, 3 * BLOCK_SZ + 100));
+    marks.push(device.writes());
+    // 跨越直接索引与一级间接索引的大文件，分多个事务写入，并会触发日志的检查点
+    let big = root.create("big").unwrap();
+    big.write_at(0, &pattern(2, 120 * BLOCK_SZ));
+    marks.push(device.writes());
+    assert!(dir.link("b", &a));
+    marks.push(device.writes());
+    let sub = dir.create_dir("sub").unwrap();
+    sub.create("c")
+        .unwrap()
+        .write_at(2 * BLOCK_SZ, &pattern(3, 10));
+    marks.push(device.writes());
+    assert!(dir.unlink("a"));
+    marks.push(device.writes());
+    assert!(root.unlink("big"));
+    marks.push(device.writes());
+    block_cache_sync_device(&block_device);
+    marks.push(device.writes());
+    marks
+}
+
+/// 按路径查找文件
+fn find_path(root: &Inode, path: &str) -> Option<Arc<Inode>> {
+    let mut names = path.split('/');
+    let mut inode = root.find(names.next().unwrap())?;
+    for name in names {
+        inode = inode.find(name)?;
+    }
+    Some(inode)
+}
+
+/// 检查恢复后的文件内容，done为掉电前已完成的步骤数，发现的问题追加至errors
+fn verify(root: &Inode, done: usize, errors: &mut Vec<String>) {
+    for ExpectedFile {
+        path,
+        created,
+        removed,
+        content,
+    } in expected_files()
+    {
+        let inode = find_path(root, path);
+        // 第done + 1步可能只完成了一部分
+        let must_exist = done >= created && removed.is_none_or(|removed| done + 1 < removed);
+        let must_not_exist = done + 1 < created || removed.is_some_and(|removed| done >= removed);
+        match (inode, content) {
+            (None, _) if must_exist => errors.push(format!("{} is lost", path)),
+            (Some(_), _) if must_not_exist => errors.push(format!("{} should not exist", path)),
+            (Some(inode), Some(content)) => {
+                let data = inode.read_all();
+                if !content.starts_with(&data) {
+                    errors.push(format!("{}: content is corrupted", path));
+                } else if done >= created && data.len() != content.len() {
+                    errors.push(format!("{}: content is incomplete", path));
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// 运行崩溃注入测试，全部通过时返回true
+pub fn run() -> bool {
+    // 创建空白镜像
+    let device = CrashDevice::new(vec![[0u8; BLOCK_SZ]; TOTAL_BLOCKS as usize], None);
+    let block_device: Arc<dyn BlockDevice> = device.clone();
+    EasyFileSystem::create(Arc::clone(&block_device), TOTAL_BLOCKS, 1);
+    block_cache_release_device(&block_device);
+    let image = device.persisted();
+    // 完整运行一次工作负载，得到其写入总次数
+    let device = CrashDevice::new(image.clone(), None);
+    let block_device: Arc<dyn BlockDevice> = device.clone();
+    let efs = EasyFileSystem::open(Arc::clone(&block_device));
+    let total_writes = *workload(&EasyFileSystem::root_inode(&efs), &device)
+        .last()
+        .unwrap();
+    block_cache_release_device(&block_device);
+    println!("crash test: {} block writes in workload", total_writes);
+    let mut passed = true;
+    for crash_at in 0..=total_writes {
+        let device = CrashDevice::new(image.clone(), Some(crash_at));
+        let block_device: Arc<dyn BlockDevice> = device.clone();
+        let efs = EasyFileSystem::open(Arc::clone(&block_device));
+        let marks = workload(&EasyFileSystem::root_inode(&efs), &device);
+        block_cache_release_device(&block_device);
+        let done = marks.iter().filter(|mark| **mark <= crash_at).count();
+        // 重新挂载掉电后的镜像
+        let recovered: Arc<dyn BlockDevice> = CrashDevice::new(device.persisted(), None);
+        let efs = EasyFileSystem::open(Arc::clone(&recovered));
+        let mut errors = fsck(&efs);
+        verify(&EasyFileSystem::root_inode(&efs), done, &mut errors);
+        block_cache_release_device(&recovered);
+        if !errors.is_empty() {
+            println!(
+                "crash test: crash at write {} ({} steps done):",
+                crash_at, done
+            );
+            for error in errors {
+                println!("  {}", error);
+            }
+            passed = false;
+        }
+    }
+    if passed {
+        println!("crash test: all {} crash points passed", total_writes + 1);
+    }
+    passed
+}
@@ -0,0 +1,155 @@
+//! easy-fs-fuse/src/main.rs <br>
+//! 宿主机上的打包工具：创建easy-fs磁盘镜像，并将用户程序的ELF写入根目录 <br>
+//! 另可检查已有镜像的一致性（`--fsck`），或运行崩溃注入测试（`--crash-test`）
+
+mod crash_test;
+
+use std::fs::{read_dir, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::sync::{Arc, Mutex};
+
+use clap::{App, Arg, ArgMatches};
+use easy_fs::{block_cache_sync_all, fsck, BlockDevice, EasyFileSystem, BLOCK_SZ};
+
+/// 以宿主机上的文件作为块设备
+struct BlockFile(Mutex<File>);
+
+impl BlockDevice for BlockFile {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        let mut file = self.0.lock().unwrap();
+        file.seek(SeekFrom::Start((block_id * BLOCK_SZ) as u64))
+            .expect("Error when seeking!");
+        assert_eq!(file.read(buf).unwrap(), BLOCK_SZ, "Not a complete block!");
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let mut file = self.0.lock().unwrap();
+        file.seek(SeekFrom::Start((block_id * BLOCK_SZ) as u64))
+            .expect("Error when seeking!");
+        assert_eq!(file.write(buf).unwrap(), BLOCK_SZ, "Not a complete block!");
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.0.lock().unwrap().metadata().unwrap().len() as usize / BLOCK_SZ
+    }
+}
+
+fn main() {
+    let matches = App::new("EasyFileSystem packer")
+        .arg(
+            Arg::with_name("source")
+                .short("s")
+                .long("source")
+                .takes_value(true)
+                .help("Executable source dir (with backslash)"),
+        )
+        .arg(
+            Arg::with_name("target")
+                .short("t")
+                .long("target")
+                .takes_value(true)
+                .help("Executable target dir (with backslash)"),
+        )
+        .arg(
+            Arg::with_name("image")
+                .short("o")
+                .long("image")
+                .takes_value(true)
+                .help("Output image path, default: <target>/fs.img"),
+        )
+        .arg(
+            Arg::with_name("size")
+                .long("size")
+                .takes_value(true)
+                .help("Image size in MiB, default: 16"),
+        )
+        .arg(
+            Arg::with_name("fsck")
+                .long("fsck")
+                .takes_value(true)
+                .value_name("IMAGE")
+                .help("Replay the journal of an existing image and check its consistency"),
+        )
+        .arg(
+            Arg::with_name("crash-test")
+                .long("crash-test")
+                .help("Run crash injection tests on an in-memory image"),
+        )
+        .get_matches();
+    let passed = if let Some(image_path) = matches.value_of("fsck") {
+        easy_fs_check(image_path).expect("Error when checking easy-fs!")
+    } else if matches.is_present("crash-test") {
+        crash_test::run()
+    } else {
+        easy_fs_pack(&matches).expect("Error when packing easy-fs!");
+        true
+    };
+    if !passed {
+        std::process::exit(1);
+    }
+}
+
+/// 检查镜像的一致性，打开镜像时会先重放其日志，没有发现问题时返回true
+fn easy_fs_check(image_path: &str) -> std::io::Result<bool> {
+    let block_file = Arc::new(BlockFile(Mutex::new(
+        OpenOptions::new().read(true).write(true).open(image_path)?,
+    )));
+    let efs = EasyFileSystem::open(block_file);
+    let errors = fsck(&efs);
+    block_cache_sync_all();
+    for error in errors.iter() {
+        println!("{}", error);
+    }
+    println!("{}: {} errors", image_path, errors.len());
+    Ok(errors.is_empty())
+}
+
+fn easy_fs_pack(matches: &ArgMatches) -> std::io::Result<()> {
+    let src_path = matches.value_of("source").unwrap();
+    let target_path = matches.value_of("target").unwrap();
+    let image_path = matches
+        .value_of("image")
+        .map(String::from)
+        .unwrap_or_else(|| format!("{}{}", target_path, "fs.img"));
+    let size_mb: u32 = matches
+        .value_of("size")
+        .map_or(16, |size| size.parse().expect("Invalid image size!"));
+    println!("src_path = {}\ntarget_path = {}", src_path, target_path);
+    let total_blocks = size_mb * 1024 * 1024 / BLOCK_SZ as u32;
+    let block_file = Arc::new(BlockFile(Mutex::new({
+        let f = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&image_path)?;
+        f.set_len(total_blocks as u64 * BLOCK_SZ as u64).unwrap();
+        f
+    })));
+    // 索引节点位图占1块，最多4096个文件
+    let efs = EasyFileSystem::create(block_file, total_blocks, 1);
+    let root_inode = Arc::new(EasyFileSystem::root_inode(&efs));
+    let mut apps: Vec<_> = read_dir(src_path)?
+        .map(|dir_entry| {
+            let mut name_with_ext = dir_entry.unwrap().file_name().into_string().unwrap();
+            name_with_ext.drain(name_with_ext.find('.').unwrap()..name_with_ext.len());
+            name_with_ext
+        })
+        .collect();
+    apps.sort();
+    for app in apps {
+        // 从宿主机读取ELF并写入镜像
+        let mut host_file = File::open(format!("{}{}", target_path, app))?;
+        let mut all_data: Vec<u8> = Vec::new();
+        host_file.read_to_end(&mut all_data)?;
+        let inode = root_inode.create(app.as_str()).unwrap();
+        inode.write_at(0, all_data.as_slice());
+        println!("{}: {} bytes", app, all_data.len());
+    }
+    // 块缓存采用写回策略，退出前须将其全部写回镜像
+    block_cache_sync_all();
+    let errors = fsck(&efs);
+    assert!(errors.is_empty(), "Inconsistent image: {:?}", errors);
+    println!("Image written to {}", image_path);
+    Ok(())
+}
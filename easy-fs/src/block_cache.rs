@@ -0,0 +1,211 @@
+//! easy-fs/src/block_cache.rs <br>
+//! 块缓存：除日志区外，文件系统对块设备的访问都经过内存中的块缓存 <br>
+//! 缓存以(设备, 块号)为键，容量有限，满时按LRU策略替换；被修改的块只在被替换或显式同步时写回块设备 <br>
+//! 在事务中被修改的块在事务提交（写入日志）前不会被替换或写回
+
+use alloc::collections::VecDeque;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+use spin::Mutex;
+
+use super::{BlockDevice, BLOCK_SZ};
+
+/// 内存中的一个块缓存
+pub struct BlockCache {
+    cache: [u8; BLOCK_SZ],
+    block_id: usize,
+    block_device: Arc<dyn BlockDevice>,
+    /// 缓存内容是否被修改过
+    modified: bool,
+    /// 是否含有当前事务中尚未提交的修改
+    uncommitted: bool,
+}
+
+impl BlockCache {
+    /// 从块设备读入编号为block_id的块
+    pub fn new(block_id: usize, block_device: Arc<dyn BlockDevice>) -> Self {
+        let mut cache = [0u8; BLOCK_SZ];
+        block_device.read_block(block_id, &mut cache);
+        Self {
+            cache,
+            block_id,
+            block_device,
+            modified: false,
+            uncommitted: false,
+        }
+    }
+
+    fn addr_of_offset(&self, offset: usize) -> usize {
+        &self.cache[offset] as *const _ as usize
+    }
+
+    /// 获取块内偏移offset处类型为T的对象的引用
+    pub fn get_ref<T>(&self, offset: usize) -> &T
+    where
+        T: Sized,
+    {
+        let type_size = core::mem::size_of::<T>();
+        assert!(offset + type_size <= BLOCK_SZ);
+        let addr = self.addr_of_offset(offset);
+        unsafe { &*(addr as *const T) }
+    }
+
+    /// 获取块内偏移offset处类型为T的对象的可变引用，并将缓存标记为已修改且未提交
+    pub fn get_mut<T>(&mut self, offset: usize) -> &mut T
+    where
+        T: Sized,
+    {
+        let type_size = core::mem::size_of::<T>();
+        assert!(offset + type_size <= BLOCK_SZ);
+        self.modified = true;
+        self.uncommitted = true;
+        let addr = self.addr_of_offset(offset);
+        unsafe { &mut *(addr as *mut T) }
+    }
+
+    /// 以只读方式访问块内偏移offset处的对象
+    pub fn read<T, V>(&self, offset: usize, f: impl FnOnce(&T) -> V) -> V {
+        f(self.get_ref(offset))
+    }
+
+    /// 以可写方式访问块内偏移offset处的对象
+    pub fn modify<T, V>(&mut self, offset: usize, f: impl FnOnce(&mut T) -> V) -> V {
+        f(self.get_mut(offset))
+    }
+
+    /// 若缓存被修改过且已提交则写回块设备，未提交的修改须等待事务提交
+    pub fn sync(&mut self) {
+        if self.modified && !self.uncommitted {
+            self.modified = false;
+            self.block_device.write_block(self.block_id, &self.cache);
+        }
+    }
+}
+
+impl Drop for BlockCache {
+    fn drop(&mut self) {
+        self.sync()
+    }
+}
+
+/// 最多同时缓存的块数
+const BLOCK_CACHE_SIZE: usize = 64;
+
+/// 块缓存的键：(设备标识, 块号)，设备标识取块设备对象的地址
+type CacheKey = (usize, usize);
+
+fn device_id(block_device: &Arc<dyn BlockDevice>) -> usize {
+    Arc::as_ptr(block_device) as *const () as usize
+}
+
+/// 块缓存管理器
+pub struct BlockCacheManager {
+    /// 按最近使用时间排列，队首为最久未使用的块
+    queue: VecDeque<(CacheKey, Arc<Mutex<BlockCache>>)>,
+}
+
+impl BlockCacheManager {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+
+    pub fn get_block_cache(
+        &mut self,
+        block_id: usize,
+        block_device: Arc<dyn BlockDevice>,
+    ) -> Arc<Mutex<BlockCache>> {
+        let key = (device_id(&block_device), block_id);
+        if let Some(idx) = self.queue.iter().position(|pair| pair.0 == key) {
+            // 命中，移至队尾成为最近使用的块
+            let pair = self.queue.remove(idx).unwrap();
+            let block_cache = Arc::clone(&pair.1);
+            self.queue.push_back(pair);
+            return block_cache;
+        }
+        if self.queue.len() == BLOCK_CACHE_SIZE {
+            // 替换最久未使用、当前没有被其他地方引用且没有未提交修改的块缓存，析构时写回被修改的内容
+            if let Some(idx) = self
+                .queue
+                .iter()
+                .position(|pair| Arc::strong_count(&pair.1) == 1 && !pair.1.lock().uncommitted)
+            {
+                self.queue.remove(idx);
+            } else {
+                panic!("Run out of BlockCache!");
+            }
+        }
+        let block_cache = Arc::new(Mutex::new(BlockCache::new(block_id, block_device)));
+        self.queue.push_back((key, Arc::clone(&block_cache)));
+        block_cache
+    }
+}
+
+lazy_static! {
+    /// 全局块缓存管理器
+    pub static ref BLOCK_CACHE_MANAGER: Mutex<BlockCacheManager> =
+        Mutex::new(BlockCacheManager::new());
+}
+
+/// 获取编号为block_id的块的缓存
+pub fn get_block_cache(
+    block_id: usize,
+    block_device: Arc<dyn BlockDevice>,
+) -> Arc<Mutex<BlockCache>> {
+    BLOCK_CACHE_MANAGER
+        .lock()
+        .get_block_cache(block_id, block_device)
+}
+
+/// 将所有被修改过的块缓存写回块设备
+pub fn block_cache_sync_all() {
+    let manager = BLOCK_CACHE_MANAGER.lock();
+    for (_, cache) in manager.queue.iter() {
+        cache.lock().sync();
+    }
+}
+
+/// 将某个块设备上所有被修改过的块缓存写回该设备
+pub fn block_cache_sync_device(block_device: &Arc<dyn BlockDevice>) {
+    let id = device_id(block_device);
+    let manager = BLOCK_CACHE_MANAGER.lock();
+    for (_, cache) in manager.queue.iter().filter(|pair| pair.0 .0 == id) {
+        cache.lock().sync();
+    }
+}
+
+/// 获取某个块设备上所有含有未提交修改的块：(块号, 块内容)
+pub fn block_cache_uncommitted(
+    block_device: &Arc<dyn BlockDevice>,
+) -> Vec<(usize, [u8; BLOCK_SZ])> {
+    let id = device_id(block_device);
+    let manager = BLOCK_CACHE_MANAGER.lock();
+    manager
+        .queue
+        .iter()
+        .filter(|pair| pair.0 .0 == id)
+        .filter_map(|(_, cache)| {
+            let cache = cache.lock();
+            cache.uncommitted.then_some((cache.block_id, cache.cache))
+        })
+        .collect()
+}
+
+/// 将某个块设备上所有未提交的修改标记为已提交，此后它们可被写回
+pub fn block_cache_commit(block_device: &Arc<dyn BlockDevice>) {
+    let id = device_id(block_device);
+    let manager = BLOCK_CACHE_MANAGER.lock();
+    for (_, cache) in manager.queue.iter().filter(|pair| pair.0 .0 == id) {
+        cache.lock().uncommitted = false;
+    }
+}
+
+/// 写回并丢弃某个块设备的所有块缓存，用于卸载设备；未提交的修改随之丢弃
+pub fn block_cache_release_device(block_device: &Arc<dyn BlockDevice>) {
+    let id = device_id(block_device);
+    let mut manager = BLOCK_CACHE_MANAGER.lock();
+    manager.queue.retain(|pair| pair.0 .0 != id);
+}
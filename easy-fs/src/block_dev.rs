@@ -0,0 +1,14 @@
+//! easy-fs/src/block_dev.rs <br>
+//! 块设备接口，由内核驱动或宿主机上的镜像文件实现
+
+use core::any::Any;
+
+/// 块设备接口，以块为单位读写
+pub trait BlockDevice: Send + Sync + Any {
+    /// 读取编号为block_id的块至buf，buf长度须为BLOCK_SZ
+    fn read_block(&self, block_id: usize, buf: &mut [u8]);
+    /// 将buf写入编号为block_id的块，buf长度须为BLOCK_SZ
+    fn write_block(&self, block_id: usize, buf: &[u8]);
+    /// 设备的块数
+    fn num_blocks(&self) -> usize;
+}
@@ -0,0 +1,232 @@
+//! easy-fs/src/efs.rs <br>
+//! 文件系统：管理磁盘布局与索引节点、数据块的分配 <br>
+//! 对文件系统的修改均须在事务中进行，由begin/commit包围
+
+use alloc::sync::Arc;
+
+use spin::Mutex;
+
+use super::{
+    block_cache_sync_device, get_block_cache, Bitmap, BlockDevice, DiskInode, DiskInodeType, Inode,
+    InodeHandles, Journal, SuperBlock, BLOCK_SZ,
+};
+
+/// 全零的数据块
+type DataBlock = [u8; BLOCK_SZ];
+
+/// 日志区的块数
+const JOURNAL_BLOCKS: u32 = 256;
+
+/// 文件系统
+pub struct EasyFileSystem {
+    pub block_device: Arc<dyn BlockDevice>,
+    pub inode_bitmap: Bitmap,
+    pub data_bitmap: Bitmap,
+    journal: Journal,
+    inode_area_start_block: u32,
+    data_area_start_block: u32,
+    data_area_blocks: u32,
+    /// 该文件系统中各索引节点的句柄
+    handles: Arc<Mutex<InodeHandles>>,
+}
+
+impl EasyFileSystem {
+    /// 在块设备上创建一个总共total_blocks块的文件系统，其中索引节点位图占inode_bitmap_blocks块
+    pub fn create(
+        block_device: Arc<dyn BlockDevice>,
+        total_blocks: u32,
+        inode_bitmap_blocks: u32,
+    ) -> Arc<Mutex<Self>> {
+        // 计算各区域的大小，超级块之后依次为日志区、索引节点位图、索引节点区、数据块位图与数据块区
+        let inode_bitmap = Bitmap::new((1 + JOURNAL_BLOCKS) as usize, inode_bitmap_blocks as usize);
+        let inode_num = inode_bitmap.maximum();
+        let inode_area_blocks =
+            (inode_num * core::mem::size_of::<DiskInode>()).div_ceil(BLOCK_SZ) as u32;
+        let inode_total_blocks = inode_bitmap_blocks + inode_area_blocks;
+        let data_total_blocks = total_blocks - 1 - JOURNAL_BLOCKS - inode_total_blocks;
+        // 每个数据位图块可管理4096个数据块
+        let data_bitmap_blocks = data_total_blocks.div_ceil(4097);
+        let data_area_blocks = data_total_blocks - data_bitmap_blocks;
+        let data_bitmap = Bitmap::new(
+            (1 + JOURNAL_BLOCKS + inode_total_blocks) as usize,
+            data_bitmap_blocks as usize,
+        );
+        // 清空所有块，此时还没有日志，直接写入块设备
+        let zero_block: DataBlock = [0u8; BLOCK_SZ];
+        for i in 0..total_blocks {
+            block_device.write_block(i as usize, &zero_block);
+        }
+        let journal = Journal::format(&block_device, 1, JOURNAL_BLOCKS as usize);
+        let mut efs = Self {
+            block_device: Arc::clone(&block_device),
+            inode_bitmap,
+            data_bitmap,
+            journal,
+            inode_area_start_block: 1 + JOURNAL_BLOCKS + inode_bitmap_blocks,
+            data_area_start_block: 1 + JOURNAL_BLOCKS + inode_total_blocks + data_bitmap_blocks,
+            data_area_blocks,
+            handles: Arc::default(),
+        };
+        efs.begin();
+        // 初始化超级块
+        get_block_cache(0, Arc::clone(&block_device)).lock().modify(
+            0,
+            |super_block: &mut SuperBlock| {
+                super_block.initialize(
+                    total_blocks,
+                    JOURNAL_BLOCKS,
+                    inode_bitmap_blocks,
+                    inode_area_blocks,
+                    data_bitmap_blocks,
+                    data_area_blocks,
+                );
+            },
+        );
+        // 创建根目录，其索引节点编号为0
+        assert_eq!(efs.alloc_inode(), 0);
+        let (root_inode_block_id, root_inode_offset) = efs.get_disk_inode_pos(0);
+        get_block_cache(root_inode_block_id as usize, Arc::clone(&block_device))
+            .lock()
+            .modify(root_inode_offset, |disk_inode: &mut DiskInode| {
+                disk_inode.initialize(DiskInodeType::Directory);
+            });
+        efs.commit();
+        let efs = Arc::new(Mutex::new(efs));
+        Self::root_inode(&efs).initialize_root();
+        block_cache_sync_device(&block_device);
+        efs
+    }
+
+    /// 打开块设备上已有的文件系统，先重放日志中已提交的事务
+    pub fn open(block_device: Arc<dyn BlockDevice>) -> Arc<Mutex<Self>> {
+        // 重放日志前不能经过块缓存读取超级块，以免缓存中留下过时的内容
+        let mut super_block_data: DataBlock = [0u8; BLOCK_SZ];
+        block_device.read_block(0, &mut super_block_data);
+        let super_block = unsafe { &*(super_block_data.as_ptr() as *const SuperBlock) };
+        assert!(super_block.is_valid(), "Error loading EFS!");
+        let journal_blocks = super_block.journal_blocks;
+        let (journal, _) = Journal::recover(&block_device, 1, journal_blocks as usize);
+        get_block_cache(0, Arc::clone(&block_device))
+            .lock()
+            .read(0, |super_block: &SuperBlock| {
+                let inode_total_blocks =
+                    super_block.inode_bitmap_blocks + super_block.inode_area_blocks;
+                let efs = Self {
+                    block_device,
+                    inode_bitmap: Bitmap::new(
+                        (1 + journal_blocks) as usize,
+                        super_block.inode_bitmap_blocks as usize,
+                    ),
+                    data_bitmap: Bitmap::new(
+                        (1 + journal_blocks + inode_total_blocks) as usize,
+                        super_block.data_bitmap_blocks as usize,
+                    ),
+                    journal,
+                    inode_area_start_block: 1 + journal_blocks + super_block.inode_bitmap_blocks,
+                    data_area_start_block: 1
+                        + journal_blocks
+                        + inode_total_blocks
+                        + super_block.data_bitmap_blocks,
+                    data_area_blocks: super_block.data_area_blocks,
+                    handles: Arc::default(),
+                };
+                Arc::new(Mutex::new(efs))
+            })
+    }
+
+    /// 开始一个事务，此后对块缓存的修改在提交前不会写回块设备
+    pub fn begin(&mut self) {
+        self.journal.begin(&self.block_device);
+    }
+
+    /// 提交当前事务，将其修改的块写入日志
+    pub fn commit(&mut self) {
+        self.journal.commit(&self.block_device);
+    }
+
+    /// 获取根目录的索引节点
+    pub fn root_inode(efs: &Arc<Mutex<Self>>) -> Inode {
+        let fs = efs.lock();
+        let (block_id, block_offset) = fs.get_disk_inode_pos(0);
+        Inode::new(
+            block_id,
+            block_offset,
+            Arc::clone(efs),
+            Arc::clone(&fs.block_device),
+            Arc::clone(&fs.handles),
+        )
+    }
+
+    /// 获取编号为inode_id的磁盘索引节点所在的(块号, 块内偏移)
+    pub fn get_disk_inode_pos(&self, inode_id: u32) -> (u32, usize) {
+        let inode_size = core::mem::size_of::<DiskInode>();
+        let inodes_per_block = (BLOCK_SZ / inode_size) as u32;
+        let block_id = self.inode_area_start_block + inode_id / inodes_per_block;
+        (
+            block_id,
+            (inode_id % inodes_per_block) as usize * inode_size,
+        )
+    }
+
+    /// 由磁盘索引节点所在的(块号, 块内偏移)反求其编号
+    pub fn get_inode_id(&self, block_id: u32, block_offset: usize) -> u32 {
+        let inode_size = core::mem::size_of::<DiskInode>();
+        let inodes_per_block = (BLOCK_SZ / inode_size) as u32;
+        (block_id - self.inode_area_start_block) * inodes_per_block
+            + (block_offset / inode_size) as u32
+    }
+
+    /// 数据块位号对应的块号
+    pub fn get_data_block_id(&self, data_block_id: u32) -> u32 {
+        self.data_area_start_block + data_block_id
+    }
+
+    /// 块号为block_id的块是否位于数据块区
+    pub fn is_data_block(&self, block_id: u32) -> bool {
+        (self.data_area_start_block..self.data_area_start_block + self.data_area_blocks)
+            .contains(&block_id)
+    }
+
+    /// 索引节点的总数
+    pub fn inode_count(&self) -> u32 {
+        self.inode_bitmap.maximum() as u32
+    }
+
+    /// 数据块的总数
+    pub fn data_count(&self) -> u32 {
+        self.data_area_blocks
+    }
+
+    /// 分配一个索引节点，返回其编号
+    pub fn alloc_inode(&mut self) -> u32 {
+        self.inode_bitmap.alloc(&self.block_device).unwrap() as u32
+    }
+
+    /// 回收编号为inode_id的索引节点
+    pub fn dealloc_inode(&mut self, inode_id: u32) {
+        self.inode_bitmap
+            .dealloc(&self.block_device, inode_id as usize)
+    }
+
+    /// 分配一个数据块并将其清零，返回其块号
+    pub fn alloc_data(&mut self) -> u32 {
+        let block_id =
+            self.data_bitmap.alloc(&self.block_device).unwrap() as u32 + self.data_area_start_block;
+        get_block_cache(block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(0, |data_block: &mut DataBlock| {
+                data_block.iter_mut().for_each(|p| {
+                    *p = 0;
+                })
+            });
+        block_id
+    }
+
+    /// 回收块号为block_id的数据块
+    pub fn dealloc_data(&mut self, block_id: u32) {
+        self.data_bitmap.dealloc(
+            &self.block_device,
+            (block_id - self.data_area_start_block) as usize,
+        )
+    }
+}
@@ -0,0 +1,157 @@
+//! easy-fs/src/fsck.rs <br>
+//! 一致性检查：从根目录出发遍历整棵目录树，核对位图、索引节点与目录项之间是否一致 <br>
+//! 检查的内容：可达的索引节点与其占用的块均已在位图中分配、块不被重复占用、
+//! 目录中的"."与".."指向正确、硬链接数等于指向该索引节点的目录项数，以及位图中没有泄漏的位
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+use super::{get_block_cache, DirEntry, DiskInode, EasyFileSystem, DIRENT_SZ};
+
+/// 检查文件系统，返回发现的所有问题，为空表示文件系统一致
+pub fn fsck(efs: &Arc<Mutex<EasyFileSystem>>) -> Vec<String> {
+    let fs = efs.lock();
+    let block_device = Arc::clone(&fs.block_device);
+    let mut errors: Vec<String> = Vec::new();
+    // 读取编号为inode_id的磁盘索引节点的副本
+    let read_inode = |inode_id: u32| -> DiskInode {
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        get_block_cache(block_id as usize, Arc::clone(&block_device))
+            .lock()
+            .read(block_offset, |disk_inode: &DiskInode| disk_inode.clone())
+    };
+    // 每个索引节点被目录项指向的次数
+    let mut links: BTreeMap<u32, u32> = BTreeMap::new();
+    // 每个块的占用者
+    let mut owners: BTreeMap<u32, u32> = BTreeMap::new();
+    let mut visited: BTreeSet<u32> = BTreeSet::new();
+    // 待检查的(索引节点编号, 父目录编号, 路径)
+    let mut stack: Vec<(u32, u32, String)> = Vec::from([(0, 0, String::from("/"))]);
+    while let Some((inode_id, parent_id, path)) = stack.pop() {
+        if !visited.insert(inode_id) {
+            continue;
+        }
+        if !fs
+            .inode_bitmap
+            .is_allocated(&block_device, inode_id as usize)
+        {
+            errors.push(format!(
+                "{}: inode {} is not allocated in bitmap",
+                path, inode_id
+            ));
+        }
+        let disk_inode = read_inode(inode_id);
+        for block_id in disk_inode.blocks(&block_device) {
+            if !fs.is_data_block(block_id) {
+                errors.push(format!("{}: block {} is out of data area", path, block_id));
+                continue;
+            }
+            let bit = (block_id - fs.get_data_block_id(0)) as usize;
+            if !fs.data_bitmap.is_allocated(&block_device, bit) {
+                errors.push(format!(
+                    "{}: block {} is not allocated in bitmap",
+                    path, block_id
+                ));
+            }
+            if let Some(owner) = owners.insert(block_id, inode_id) {
+                errors.push(format!(
+                    "{}: block {} is shared with inode {}",
+                    path, block_id, owner
+                ));
+            }
+        }
+        if !disk_inode.is_dir() {
+            continue;
+        }
+        if !(disk_inode.size as usize).is_multiple_of(DIRENT_SZ) {
+            errors.push(format!(
+                "{}: directory size {} is not aligned",
+                path, disk_inode.size
+            ));
+            continue;
+        }
+        let (mut has_dot, mut has_dotdot) = (false, false);
+        for i in 0..disk_inode.size as usize / DIRENT_SZ {
+            let mut dirent = DirEntry::empty();
+            disk_inode.read_at(i * DIRENT_SZ, dirent.as_bytes_mut(), &block_device);
+            if dirent.is_empty() {
+                continue;
+            }
+            let name = dirent.name();
+            let child_id = dirent.inode_number();
+            if child_id >= fs.inode_count() {
+                errors.push(format!(
+                    "{}: entry {} points to invalid inode {}",
+                    path, name, child_id
+                ));
+                continue;
+            }
+            *links.entry(child_id).or_insert(0) += 1;
+            match name {
+                "." => {
+                    has_dot = true;
+                    if child_id != inode_id {
+                        errors.push(format!("{}: \".\" points to inode {}", path, child_id));
+                    }
+                }
+                ".." => {
+                    has_dotdot = true;
+                    if child_id != parent_id {
+                        errors.push(format!("{}: \"..\" points to inode {}", path, child_id));
+                    }
+                }
+                _ => {
+                    let child_path = if inode_id == 0 {
+                        format!("/{}", name)
+                    } else {
+                        format!("{}/{}", path, name)
+                    };
+                    if read_inode(child_id).is_dir() && visited.contains(&child_id) {
+                        errors.push(format!(
+                            "{}: directory is linked more than once",
+                            child_path
+                        ));
+                        continue;
+                    }
+                    stack.push((child_id, inode_id, child_path));
+                }
+            }
+        }
+        if !has_dot || !has_dotdot {
+            errors.push(format!("{}: missing \".\" or \"..\"", path));
+        }
+    }
+    for inode_id in visited.iter() {
+        let nlink = read_inode(*inode_id).nlink;
+        let count = links.get(inode_id).copied().unwrap_or(0);
+        if nlink != count {
+            errors.push(format!(
+                "inode {}: nlink is {} but {} entries point to it",
+                inode_id, nlink, count
+            ));
+        }
+    }
+    for inode_id in 0..fs.inode_count() {
+        if !visited.contains(&inode_id)
+            && fs
+                .inode_bitmap
+                .is_allocated(&block_device, inode_id as usize)
+        {
+            errors.push(format!("inode {} is allocated but unreachable", inode_id));
+        }
+    }
+    for bit in 0..fs.data_count() {
+        let block_id = fs.get_data_block_id(bit);
+        if !owners.contains_key(&block_id)
+            && fs.data_bitmap.is_allocated(&block_device, bit as usize)
+        {
+            errors.push(format!("block {} is allocated but unused", block_id));
+        }
+    }
+    errors
+}
@@ -0,0 +1,248 @@
+//! easy-fs/src/journal.rs <br>
+//! 日志：事务中被修改的块在提交时先整块写入日志区，写完提交块后才允许写回原位置；
+//! 挂载时重放日志中所有已提交的事务，使掉电后的文件系统仍保持一致 <br>
+//! 日志区布局：日志头 | 事务(描述块 | 被修改的块... | 提交块) | 事务 | ... <br>
+//! 日志头记录日志中第一个事务的序号；检查点将所有已提交的修改写回原位置后推进该序号，日志区随之清空
+
+use alloc::sync::Arc;
+
+use super::{
+    block_cache_commit, block_cache_sync_device, block_cache_uncommitted, BlockDevice, BLOCK_SZ,
+};
+
+/// 日志头魔数
+const JOURNAL_HEADER_MAGIC: u32 = 0x4a52_4e4c;
+/// 描述块魔数
+const JOURNAL_DESCRIPTOR_MAGIC: u32 = 0x4a44_5343;
+/// 提交块魔数
+const JOURNAL_COMMIT_MAGIC: u32 = 0x4a43_4d54;
+/// 一个描述块中最多记录的块号数量
+const DESCRIPTOR_CAPACITY: usize = (BLOCK_SZ - 12) / 4;
+/// 一个事务最多修改的块数，受块缓存容量的限制（未提交的块不能被替换）
+pub const MAX_TRANSACTION_BLOCKS: usize = 32;
+
+/// 日志头，位于日志区的第一块
+#[repr(C)]
+struct JournalHeader {
+    magic: u32,
+    /// 日志中第一个事务的序号
+    sequence: u32,
+}
+
+/// 描述块，记录事务所修改的块的原位置
+#[repr(C)]
+struct JournalDescriptor {
+    magic: u32,
+    sequence: u32,
+    count: u32,
+    block_ids: [u32; DESCRIPTOR_CAPACITY],
+}
+
+/// 提交块，写入后事务才生效
+#[repr(C)]
+struct JournalCommit {
+    magic: u32,
+    sequence: u32,
+    /// 描述块与被修改的块的校验和
+    checksum: u32,
+}
+
+/// 直接读写块设备时使用的块缓冲区
+#[repr(C, align(4))]
+struct RawBlock([u8; BLOCK_SZ]);
+
+impl RawBlock {
+    fn zeroed() -> Self {
+        Self([0u8; BLOCK_SZ])
+    }
+
+    fn read(block_device: &Arc<dyn BlockDevice>, block_id: usize) -> Self {
+        let mut block = Self::zeroed();
+        block_device.read_block(block_id, &mut block.0);
+        block
+    }
+
+    fn get_ref<T>(&self) -> &T {
+        assert!(core::mem::size_of::<T>() <= BLOCK_SZ);
+        unsafe { &*(self.0.as_ptr() as *const T) }
+    }
+
+    fn get_mut<T>(&mut self) -> &mut T {
+        assert!(core::mem::size_of::<T>() <= BLOCK_SZ);
+        unsafe { &mut *(self.0.as_mut_ptr() as *mut T) }
+    }
+}
+
+/// FNV-1a校验和
+fn checksum(init: u32, data: &[u8]) -> u32 {
+    data.iter().fold(init, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+const CHECKSUM_INIT: u32 = 0x811c_9dc5;
+
+/// 日志，占据从start_block开始的blocks个块
+pub struct Journal {
+    start_block: usize,
+    blocks: usize,
+    /// 下一个事务的序号
+    sequence: u32,
+    /// 下一个事务的描述块在日志区内的位置
+    tail: usize,
+    /// 是否有正在进行的事务
+    running: bool,
+}
+
+impl Journal {
+    /// 在块设备上初始化一个空的日志
+    pub fn format(block_device: &Arc<dyn BlockDevice>, start_block: usize, blocks: usize) -> Self {
+        assert!(blocks >= MAX_TRANSACTION_BLOCKS + 3, "Journal too small!");
+        let journal = Self {
+            start_block,
+            blocks,
+            sequence: 1,
+            tail: 1,
+            running: false,
+        };
+        journal.write_header(block_device);
+        journal
+    }
+
+    /// 打开块设备上已有的日志，并重放其中所有已提交的事务，返回日志与重放的事务数 <br>
+    /// 须在块缓存中还没有该设备的块时调用
+    pub fn recover(
+        block_device: &Arc<dyn BlockDevice>,
+        start_block: usize,
+        blocks: usize,
+    ) -> (Self, usize) {
+        let header = RawBlock::read(block_device, start_block);
+        let header: &JournalHeader = header.get_ref();
+        assert!(
+            header.magic == JOURNAL_HEADER_MAGIC,
+            "Error loading journal!"
+        );
+        let mut journal = Self {
+            start_block,
+            blocks,
+            sequence: header.sequence,
+            tail: 1,
+            running: false,
+        };
+        let mut replayed = 0;
+        while let Some(next) = journal.replay_one(block_device) {
+            journal.tail = next;
+            journal.sequence += 1;
+            replayed += 1;
+        }
+        // 重放的块已直接写回原位置，推进日志头即可清空日志
+        journal.tail = 1;
+        if replayed > 0 {
+            journal.write_header(block_device);
+        }
+        (journal, replayed)
+    }
+
+    /// 重放位于tail处的事务，返回下一个事务的位置；事务不完整或不属于当前日志时返回None
+    fn replay_one(&self, block_device: &Arc<dyn BlockDevice>) -> Option<usize> {
+        if self.tail + 2 > self.blocks {
+            return None;
+        }
+        let descriptor_block = RawBlock::read(block_device, self.start_block + self.tail);
+        let descriptor: &JournalDescriptor = descriptor_block.get_ref();
+        let count = descriptor.count as usize;
+        if descriptor.magic != JOURNAL_DESCRIPTOR_MAGIC
+            || descriptor.sequence != self.sequence
+            || count == 0
+            || count > MAX_TRANSACTION_BLOCKS
+            || self.tail + count + 2 > self.blocks
+        {
+            return None;
+        }
+        let commit_block = RawBlock::read(block_device, self.start_block + self.tail + count + 1);
+        let commit: &JournalCommit = commit_block.get_ref();
+        if commit.magic != JOURNAL_COMMIT_MAGIC || commit.sequence != self.sequence {
+            return None;
+        }
+        let mut sum = checksum(CHECKSUM_INIT, &descriptor_block.0);
+        for i in 0..count {
+            let block = RawBlock::read(block_device, self.start_block + self.tail + 1 + i);
+            sum = checksum(sum, &block.0);
+        }
+        if sum != commit.checksum {
+            return None;
+        }
+        for (i, block_id) in descriptor.block_ids.iter().take(count).enumerate() {
+            let block = RawBlock::read(block_device, self.start_block + self.tail + 1 + i);
+            block_device.write_block(*block_id as usize, &block.0);
+        }
+        Some(self.tail + count + 2)
+    }
+
+    fn write_header(&self, block_device: &Arc<dyn BlockDevice>) {
+        let mut block = RawBlock::zeroed();
+        *block.get_mut() = JournalHeader {
+            magic: JOURNAL_HEADER_MAGIC,
+            sequence: self.sequence,
+        };
+        block_device.write_block(self.start_block, &block.0);
+    }
+
+    /// 开始一个事务；日志区剩余空间不足时先做检查点
+    pub fn begin(&mut self, block_device: &Arc<dyn BlockDevice>) {
+        assert!(!self.running, "Nested transaction!");
+        if self.tail + MAX_TRANSACTION_BLOCKS + 2 > self.blocks {
+            self.checkpoint(block_device);
+        }
+        self.running = true;
+    }
+
+    /// 提交当前事务：依次写入描述块、被修改的块与提交块，此后被修改的块才可以写回原位置
+    pub fn commit(&mut self, block_device: &Arc<dyn BlockDevice>) {
+        assert!(self.running, "No running transaction!");
+        self.running = false;
+        let blocks = block_cache_uncommitted(block_device);
+        if blocks.is_empty() {
+            return;
+        }
+        assert!(
+            blocks.len() <= MAX_TRANSACTION_BLOCKS,
+            "Transaction too large!"
+        );
+        let mut descriptor_block = RawBlock::zeroed();
+        let descriptor: &mut JournalDescriptor = descriptor_block.get_mut();
+        descriptor.magic = JOURNAL_DESCRIPTOR_MAGIC;
+        descriptor.sequence = self.sequence;
+        descriptor.count = blocks.len() as u32;
+        for (i, (block_id, _)) in blocks.iter().enumerate() {
+            descriptor.block_ids[i] = *block_id as u32;
+        }
+        let mut sum = checksum(CHECKSUM_INIT, &descriptor_block.0);
+        block_device.write_block(self.start_block + self.tail, &descriptor_block.0);
+        for (i, (_, data)) in blocks.iter().enumerate() {
+            sum = checksum(sum, data);
+            block_device.write_block(self.start_block + self.tail + 1 + i, data);
+        }
+        let mut commit_block = RawBlock::zeroed();
+        *commit_block.get_mut() = JournalCommit {
+            magic: JOURNAL_COMMIT_MAGIC,
+            sequence: self.sequence,
+            checksum: sum,
+        };
+        block_device.write_block(
+            self.start_block + self.tail + blocks.len() + 1,
+            &commit_block.0,
+        );
+        block_cache_commit(block_device);
+        self.tail += blocks.len() + 2;
+        self.sequence += 1;
+    }
+
+    /// 检查点：将所有已提交的修改写回原位置，然后清空日志
+    pub fn checkpoint(&mut self, block_device: &Arc<dyn BlockDevice>) {
+        assert!(!self.running, "Checkpoint during a transaction!");
+        block_cache_sync_device(block_device);
+        self.tail = 1;
+        self.write_header(block_device);
+    }
+}
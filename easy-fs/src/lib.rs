@@ -0,0 +1,34 @@
+//! easy-fs/src/lib.rs <br>
+//! 简单的索引节点文件系统，同时供内核与宿主机上的打包工具使用 <br>
+//! 磁盘布局：超级块 | 日志区 | 索引节点位图 | 索引节点区 | 数据块位图 | 数据块区
+
+#![no_std]
+
+extern crate alloc;
+
+pub use block_cache::{block_cache_release_device, block_cache_sync_all, block_cache_sync_device};
+pub use block_dev::BlockDevice;
+pub use efs::EasyFileSystem;
+pub use fsck::fsck;
+pub use layout::DiskInodeType;
+pub use ram_disk::RamDisk;
+pub use vfs::Inode;
+
+use block_cache::{block_cache_commit, block_cache_uncommitted, get_block_cache};
+use bitmap::Bitmap;
+use journal::Journal;
+use layout::*;
+use vfs::InodeHandles;
+
+mod bitmap;
+mod block_cache;
+mod block_dev;
+mod efs;
+mod fsck;
+mod journal;
+mod layout;
+mod ram_disk;
+mod vfs;
+
+/// 块大小（字节）
+pub const BLOCK_SZ: usize = 512;
@@ -0,0 +1,158 @@
+//! easy-fs/src/ram_disk.rs <br>
+//! 以内存区域为存储介质的块设备，不依赖任何外设，供没有块设备驱动的平台与测试使用
+
+use core::ops::Range;
+
+use spin::Mutex;
+
+use super::{BlockDevice, BLOCK_SZ};
+
+/// 内存块设备
+pub struct RamDisk {
+    data: Mutex<&'static mut [u8]>,
+}
+
+impl RamDisk {
+    /// 以一段内存区域（如链接进内核镜像的磁盘镜像）作为RamDisk的存储
+    pub fn from_region(region: &'static mut [u8]) -> Self {
+        assert!(
+            region.len().is_multiple_of(BLOCK_SZ),
+            "RamDisk size {:#x} is not a multiple of block size",
+            region.len()
+        );
+        Self {
+            data: Mutex::new(region),
+        }
+    }
+
+    fn block_range(&self, block_id: usize) -> Range<usize> {
+        assert!(
+            block_id < self.num_blocks(),
+            "block {} out of range (capacity {})",
+            block_id,
+            self.num_blocks()
+        );
+        block_id * BLOCK_SZ..(block_id + 1) * BLOCK_SZ
+    }
+}
+
+impl BlockDevice for RamDisk {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        let range = self.block_range(block_id);
+        buf.copy_from_slice(&self.data.lock()[range]);
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        let range = self.block_range(block_id);
+        self.data.lock()[range].copy_from_slice(buf);
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.data.lock().len() / BLOCK_SZ
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::sync::Arc;
+    use alloc::vec;
+    use alloc::vec::Vec;
+
+    use super::*;
+    use crate::{block_cache_release_device, block_cache_sync_device, fsck, EasyFileSystem};
+
+    /// 在堆上分配blocks块、初始内容全零的RamDisk
+    fn ram_disk(blocks: usize) -> RamDisk {
+        RamDisk::from_region(vec![0u8; blocks * BLOCK_SZ].leak())
+    }
+
+    #[test]
+    fn read_write_round_trip() {
+        let disk = ram_disk(4);
+        assert_eq!(disk.num_blocks(), 4);
+        let pattern: [u8; BLOCK_SZ] = core::array::from_fn(|i| i as u8);
+        disk.write_block(2, &pattern);
+        let mut buf = [0xffu8; BLOCK_SZ];
+        disk.read_block(2, &mut buf);
+        assert_eq!(buf, pattern);
+        // 相邻的块不受影响
+        disk.read_block(1, &mut buf);
+        assert_eq!(buf, [0u8; BLOCK_SZ]);
+        disk.read_block(3, &mut buf);
+        assert_eq!(buf, [0u8; BLOCK_SZ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn read_out_of_range() {
+        let disk = ram_disk(4);
+        let mut buf = [0u8; BLOCK_SZ];
+        disk.read_block(4, &mut buf);
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn write_out_of_range() {
+        let disk = ram_disk(4);
+        disk.write_block(usize::MAX, &[0u8; BLOCK_SZ]);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a multiple of block size")]
+    fn unaligned_region() {
+        RamDisk::from_region(vec![0u8; BLOCK_SZ + 1].leak());
+    }
+
+    #[test]
+    fn mkfs_open_round_trip() {
+        let disk: Arc<dyn BlockDevice> = Arc::new(ram_disk(4096));
+        let efs = EasyFileSystem::create(Arc::clone(&disk), 4096, 1);
+        let root = EasyFileSystem::root_inode(&efs);
+        let file = root.create("hello").unwrap();
+        let data: Vec<u8> = (0..BLOCK_SZ * 3 + 7).map(|i| (i % 251) as u8).collect();
+        assert_eq!(file.write_at(0, &data), data.len());
+        root.create_dir("dir").unwrap();
+        drop((file, root, efs));
+        // 写回并丢弃块缓存，重新打开时只能从RamDisk中读到文件系统
+        block_cache_sync_device(&disk);
+        block_cache_release_device(&disk);
+
+        let efs = EasyFileSystem::open(Arc::clone(&disk));
+        assert!(fsck(&efs).is_empty());
+        let root = EasyFileSystem::root_inode(&efs);
+        let file = root.find("hello").unwrap();
+        assert_eq!(file.read_all(), data);
+        assert!(root.find("dir").unwrap().is_dir());
+        assert!(root.find("missing").is_none());
+        drop((file, root, efs));
+        block_cache_release_device(&disk);
+    }
+
+    #[test]
+    fn unlink_in_use_defers_free() {
+        let disk: Arc<dyn BlockDevice> = Arc::new(ram_disk(4096));
+        let efs = EasyFileSystem::create(Arc::clone(&disk), 4096, 1);
+        let root = EasyFileSystem::root_inode(&efs);
+        let file = root.create("a").unwrap();
+        let data: Vec<u8> = (0..BLOCK_SZ * 2).map(|i| (i % 253) as u8).collect();
+        assert_eq!(file.write_at(0, &data), data.len());
+        let dir = root.create_dir("d").unwrap();
+        assert!(root.unlink("a"));
+        assert!(root.unlink("d"));
+        // 仍被打开的文件不会被回收，其索引节点与数据块不被新文件复用
+        let other = root.create("b").unwrap();
+        assert_ne!(other.inode_id(), file.inode_id());
+        assert_eq!(other.write_at(0, &[0xffu8; BLOCK_SZ * 2]), BLOCK_SZ * 2);
+        assert_eq!(file.read_all(), data);
+        assert_eq!(file.nlink(), 0);
+        // 已删除的目录中不能再创建文件
+        assert!(dir.create("x").is_none());
+        drop((file, dir));
+        assert!(root.unlink("b"));
+        drop(other);
+        // 最后一个句柄释放后索引节点与数据块均被回收
+        assert!(fsck(&efs).is_empty());
+        drop((root, efs));
+        block_cache_release_device(&disk);
+    }
+}
@@ -0,0 +1,476 @@
+//! easy-fs/src/vfs.rs <br>
+//! 索引节点：对文件与目录的操作接口 <br>
+//! 每个目录都含有指向自身的"."与指向父目录的".."，根目录的".."指向其自身；
+//! 删除目录项时将其置为空目录项，新建文件时优先复用空目录项；
+//! 目录项全部删除后，索引节点与数据块在该索引节点的最后一个句柄释放时才回收 <br>
+//! 每个修改文件系统的操作都在一个或多个事务中完成，掉电后重放日志可恢复到某个操作完成前后的状态
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use spin::{Mutex, MutexGuard};
+
+use super::{
+    block_cache_sync_device, get_block_cache, BlockDevice, DirEntry, DiskInode, DiskInodeType,
+    EasyFileSystem, BLOCK_SZ, DIRENT_SZ, NAME_LENGTH_LIMIT,
+};
+
+/// 一个事务中最多写入的字节数，使事务修改的块数不超过日志的限制
+const TRANSACTION_WRITE_SIZE: usize = 8 * BLOCK_SZ;
+
+/// 内存中的索引节点，指向磁盘上的DiskInode
+pub struct Inode {
+    block_id: usize,
+    block_offset: usize,
+    fs: Arc<Mutex<EasyFileSystem>>,
+    block_device: Arc<dyn BlockDevice>,
+    handles: Arc<Mutex<InodeHandles>>,
+}
+
+/// 文件系统中各索引节点的存活句柄数，以及目录项已全部删除、待最后一个句柄释放时回收的索引节点 <br>
+/// 以磁盘索引节点所在的(块号, 块内偏移)为键；句柄常在持有文件系统的锁时创建与释放，因此单独加锁
+#[derive(Default)]
+pub(crate) struct InodeHandles {
+    count: BTreeMap<(usize, usize), usize>,
+    orphans: BTreeSet<(usize, usize)>,
+}
+
+impl Inode {
+    pub(crate) fn new(
+        block_id: u32,
+        block_offset: usize,
+        fs: Arc<Mutex<EasyFileSystem>>,
+        block_device: Arc<dyn BlockDevice>,
+        handles: Arc<Mutex<InodeHandles>>,
+    ) -> Self {
+        *handles
+            .lock()
+            .count
+            .entry((block_id as usize, block_offset))
+            .or_default() += 1;
+        Self {
+            block_id: block_id as usize,
+            block_offset,
+            fs,
+            block_device,
+            handles,
+        }
+    }
+
+    fn read_disk_inode<V>(&self, f: impl FnOnce(&DiskInode) -> V) -> V {
+        get_block_cache(self.block_id, Arc::clone(&self.block_device))
+            .lock()
+            .read(self.block_offset, f)
+    }
+
+    fn modify_disk_inode<V>(&self, f: impl FnOnce(&mut DiskInode) -> V) -> V {
+        get_block_cache(self.block_id, Arc::clone(&self.block_device))
+            .lock()
+            .modify(self.block_offset, f)
+    }
+
+    /// 修改编号为inode_id的磁盘索引节点
+    fn modify_other_disk_inode<V>(
+        &self,
+        inode_id: u32,
+        fs: &MutexGuard<EasyFileSystem>,
+        f: impl FnOnce(&mut DiskInode) -> V,
+    ) -> V {
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        get_block_cache(block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(block_offset, f)
+    }
+
+    /// 构造编号为inode_id的索引节点
+    fn inode_by_id(&self, inode_id: u32, fs: &MutexGuard<EasyFileSystem>) -> Arc<Inode> {
+        let (block_id, block_offset) = fs.get_disk_inode_pos(inode_id);
+        Arc::new(Self::new(
+            block_id,
+            block_offset,
+            Arc::clone(&self.fs),
+            Arc::clone(&self.block_device),
+            Arc::clone(&self.handles),
+        ))
+    }
+
+    /// 读取目录中的第index个目录项
+    fn read_dirent(&self, disk_inode: &DiskInode, index: usize) -> DirEntry {
+        let mut dirent = DirEntry::empty();
+        assert_eq!(
+            disk_inode.read_at(DIRENT_SZ * index, dirent.as_bytes_mut(), &self.block_device),
+            DIRENT_SZ,
+        );
+        dirent
+    }
+
+    /// 在目录中查找名为name的目录项，返回(目录项序号, 索引节点编号)
+    fn find_dirent(&self, name: &str, disk_inode: &DiskInode) -> Option<(usize, u32)> {
+        assert!(disk_inode.is_dir());
+        let file_count = (disk_inode.size as usize) / DIRENT_SZ;
+        (0..file_count).find_map(|i| {
+            let dirent = self.read_dirent(disk_inode, i);
+            if !dirent.is_empty() && dirent.name() == name {
+                Some((i, dirent.inode_number()))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// 在目录中查找名为name的目录项，返回其索引节点编号
+    fn find_inode_id(&self, name: &str, disk_inode: &DiskInode) -> Option<u32> {
+        self.find_dirent(name, disk_inode).map(|(_, inode_id)| inode_id)
+    }
+
+    /// 在目录中写入一个目录项，优先复用空目录项
+    fn add_dirent(
+        &self,
+        name: &str,
+        inode_id: u32,
+        disk_inode: &mut DiskInode,
+        fs: &mut MutexGuard<EasyFileSystem>,
+    ) {
+        let file_count = (disk_inode.size as usize) / DIRENT_SZ;
+        let index = (0..file_count)
+            .find(|i| self.read_dirent(disk_inode, *i).is_empty())
+            .unwrap_or_else(|| {
+                self.increase_size(((file_count + 1) * DIRENT_SZ) as u32, disk_inode, fs);
+                file_count
+            });
+        let dirent = DirEntry::new(name, inode_id);
+        disk_inode.write_at(index * DIRENT_SZ, dirent.as_bytes(), &self.block_device);
+    }
+
+    /// 名字是否可以作为目录项
+    fn valid_name(name: &str) -> bool {
+        !name.is_empty() && name.len() <= NAME_LENGTH_LIMIT && !name.contains('/')
+    }
+
+    /// 在当前目录中查找名为name的文件或目录
+    pub fn find(&self, name: &str) -> Option<Arc<Inode>> {
+        let fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| {
+            self.find_inode_id(name, disk_inode)
+                .map(|inode_id| self.inode_by_id(inode_id, &fs))
+        })
+    }
+
+    /// 将文件扩大至new_size字节，按需分配数据块与索引块
+    fn increase_size(
+        &self,
+        new_size: u32,
+        disk_inode: &mut DiskInode,
+        fs: &mut MutexGuard<EasyFileSystem>,
+    ) {
+        if new_size < disk_inode.size {
+            return;
+        }
+        let blocks_needed = disk_inode.blocks_num_needed(new_size);
+        let v: Vec<u32> = (0..blocks_needed).map(|_| fs.alloc_data()).collect();
+        disk_inode.increase_size(new_size, v, &self.block_device);
+    }
+
+    /// 在当前目录中创建名为name、类型为type_的索引节点，同名目录项已存在或名字不合法时返回None
+    fn create_inode(&self, name: &str, type_: DiskInodeType) -> Option<Arc<Inode>> {
+        if !Self::valid_name(name) {
+            return None;
+        }
+        let mut fs = self.fs.lock();
+        // 已被删除的目录中不能再创建文件，否则回收目录时新文件将无从回收
+        if self.read_disk_inode(|dir_inode| {
+            assert!(dir_inode.is_dir());
+            dir_inode.nlink == 0 || self.find_inode_id(name, dir_inode).is_some()
+        }) {
+            return None;
+        }
+        let self_id = fs.get_inode_id(self.block_id as u32, self.block_offset);
+        fs.begin();
+        // 分配并初始化新的索引节点
+        let new_inode_id = fs.alloc_inode();
+        let (new_inode_block_id, new_inode_block_offset) = fs.get_disk_inode_pos(new_inode_id);
+        get_block_cache(new_inode_block_id as usize, Arc::clone(&self.block_device))
+            .lock()
+            .modify(new_inode_block_offset, |new_inode: &mut DiskInode| {
+                new_inode.initialize(type_);
+                new_inode.nlink = 1;
+                if type_ == DiskInodeType::Directory {
+                    // 新目录的"."指向自身，".."指向当前目录
+                    new_inode.nlink = 2;
+                    self.add_dirent(".", new_inode_id, new_inode, &mut fs);
+                    self.add_dirent("..", self_id, new_inode, &mut fs);
+                }
+            });
+        // 在当前目录中添加目录项，子目录的".."使当前目录的链接数加一
+        self.modify_disk_inode(|dir_inode| {
+            self.add_dirent(name, new_inode_id, dir_inode, &mut fs);
+            if type_ == DiskInodeType::Directory {
+                dir_inode.nlink += 1;
+            }
+        });
+        fs.commit();
+        Some(self.inode_by_id(new_inode_id, &fs))
+    }
+
+    /// 在当前目录中创建名为name的文件，若已存在则返回None
+    pub fn create(&self, name: &str) -> Option<Arc<Inode>> {
+        self.create_inode(name, DiskInodeType::File)
+    }
+
+    /// 在当前目录中创建名为name的子目录，若已存在则返回None
+    pub fn create_dir(&self, name: &str) -> Option<Arc<Inode>> {
+        self.create_inode(name, DiskInodeType::Directory)
+    }
+
+    /// 将当前目录初始化为根目录，其"."与".."均指向自身
+    pub(crate) fn initialize_root(&self) {
+        let mut fs = self.fs.lock();
+        let self_id = fs.get_inode_id(self.block_id as u32, self.block_offset);
+        fs.begin();
+        self.modify_disk_inode(|root_inode| {
+            root_inode.nlink = 2;
+            self.add_dirent(".", self_id, root_inode, &mut fs);
+            self.add_dirent("..", self_id, root_inode, &mut fs);
+        });
+        fs.commit();
+    }
+
+    /// 在当前目录中创建指向target的硬链接name，target须为同一文件系统中的普通文件 <br>
+    /// name已存在或不合法、target为目录时返回false
+    pub fn link(&self, name: &str, target: &Inode) -> bool {
+        if !Self::valid_name(name) || !Arc::ptr_eq(&self.fs, &target.fs) {
+            return false;
+        }
+        let mut fs = self.fs.lock();
+        if target.read_disk_inode(|disk_inode| disk_inode.is_dir() || disk_inode.nlink == 0)
+            || self.read_disk_inode(|dir_inode| {
+                dir_inode.nlink == 0 || self.find_inode_id(name, dir_inode).is_some()
+            })
+        {
+            return false;
+        }
+        let target_id = fs.get_inode_id(target.block_id as u32, target.block_offset);
+        fs.begin();
+        self.modify_disk_inode(|dir_inode| {
+            self.add_dirent(name, target_id, dir_inode, &mut fs);
+        });
+        target.modify_disk_inode(|disk_inode| disk_inode.nlink += 1);
+        fs.commit();
+        true
+    }
+
+    /// 删除当前目录中名为name的目录项，目录须为空；链接数减为零时回收其索引节点与数据块，
+    /// 仍有其他句柄（如打开的文件、共享映射的缓存页）时推迟到最后一个句柄释放时回收 <br>
+    /// 目录项不存在、为"."或".."或目录非空时返回false
+    pub fn unlink(&self, name: &str) -> bool {
+        if name == "." || name == ".." {
+            return false;
+        }
+        let mut fs = self.fs.lock();
+        let Some((index, inode_id)) =
+            self.read_disk_inode(|dir_inode| self.find_dirent(name, dir_inode))
+        else {
+            return false;
+        };
+        let child = self.inode_by_id(inode_id, &fs);
+        let is_dir = child.read_disk_inode(|disk_inode| disk_inode.is_dir());
+        if is_dir && !child.read_disk_inode(|disk_inode| child.is_empty_dir(disk_inode)) {
+            return false;
+        }
+        fs.begin();
+        self.modify_disk_inode(|dir_inode| {
+            dir_inode.write_at(index * DIRENT_SZ, DirEntry::empty().as_bytes(), &self.block_device);
+            // 子目录的".."不再指向当前目录
+            if is_dir {
+                dir_inode.nlink -= 1;
+            }
+        });
+        let nlink = self.modify_other_disk_inode(inode_id, &fs, |disk_inode| {
+            // 目录被删除后其"."也随之失效
+            disk_inode.nlink -= if is_dir { 2 } else { 1 };
+            disk_inode.nlink
+        });
+        if nlink == 0 && !child.defer_free() {
+            child.clear_locked(&mut fs);
+            fs.dealloc_inode(inode_id);
+        }
+        fs.commit();
+        true
+    }
+
+    /// 除自身外还有该索引节点的句柄时，将其记为待回收并返回true，由最后一个句柄释放时回收
+    fn defer_free(&self) -> bool {
+        let pos = (self.block_id, self.block_offset);
+        let mut handles = self.handles.lock();
+        if handles.count[&pos] == 1 {
+            return false;
+        }
+        handles.orphans.insert(pos);
+        true
+    }
+
+    /// 目录中是否只有"."与".."
+    fn is_empty_dir(&self, disk_inode: &DiskInode) -> bool {
+        let file_count = (disk_inode.size as usize) / DIRENT_SZ;
+        (0..file_count).all(|i| {
+            let dirent = self.read_dirent(disk_inode, i);
+            dirent.is_empty() || dirent.name() == "." || dirent.name() == ".."
+        })
+    }
+
+    /// 列出当前目录中的所有文件名（含"."与".."）
+    pub fn ls(&self) -> Vec<String> {
+        self.dirents()
+            .into_iter()
+            .map(|(name, _, _)| name)
+            .collect()
+    }
+
+    /// 列出当前目录中的所有目录项：(文件名, 索引节点编号, 类型)
+    pub fn dirents(&self) -> Vec<(String, u32, DiskInodeType)> {
+        let fs = self.fs.lock();
+        let entries: Vec<(String, u32)> = self.read_disk_inode(|disk_inode| {
+            let file_count = (disk_inode.size as usize) / DIRENT_SZ;
+            (0..file_count)
+                .map(|i| self.read_dirent(disk_inode, i))
+                .filter(|dirent| !dirent.is_empty())
+                .map(|dirent| (String::from(dirent.name()), dirent.inode_number()))
+                .collect()
+        });
+        // 子节点可能与当前目录位于同一块中（如"."），需在释放当前目录的块缓存后再读取
+        entries
+            .into_iter()
+            .map(|(name, inode_id)| {
+                let type_ = self
+                    .inode_by_id(inode_id, &fs)
+                    .read_disk_inode(|child| child.type_());
+                (name, inode_id, type_)
+            })
+            .collect()
+    }
+
+    /// 从偏移offset处读取数据至buf，返回读取的字节数
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.read_at(offset, buf, &self.block_device))
+    }
+
+    /// 将buf写入偏移offset处，文件不够大时自动扩大，返回写入的字节数 <br>
+    /// 较大的写入被拆分为多个事务，掉电时可能只完成其中的一部分
+    pub fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+        let mut fs = self.fs.lock();
+        // 偏移超出文件末尾时，先逐段将文件扩大至offset处
+        let mut size = self.read_disk_inode(|disk_inode| disk_inode.size as usize);
+        while size < offset {
+            size = (size + TRANSACTION_WRITE_SIZE).min(offset);
+            fs.begin();
+            self.modify_disk_inode(|disk_inode| {
+                self.increase_size(size as u32, disk_inode, &mut fs);
+            });
+            fs.commit();
+        }
+        let mut write_size = 0usize;
+        for chunk in buf.chunks(TRANSACTION_WRITE_SIZE) {
+            let start = offset + write_size;
+            fs.begin();
+            write_size += self.modify_disk_inode(|disk_inode| {
+                self.increase_size((start + chunk.len()) as u32, disk_inode, &mut fs);
+                disk_inode.write_at(start, chunk, &self.block_device)
+            });
+            fs.commit();
+        }
+        write_size
+    }
+
+    /// 读取整个文件的内容
+    pub fn read_all(&self) -> Vec<u8> {
+        let mut offset = 0usize;
+        let mut buffer = [0u8; 512];
+        let mut v: Vec<u8> = Vec::new();
+        loop {
+            let len = self.read_at(offset, &mut buffer);
+            if len == 0 {
+                break;
+            }
+            offset += len;
+            v.extend_from_slice(&buffer[..len]);
+        }
+        v
+    }
+
+    /// 将文件清空并回收其所有数据块
+    pub fn clear(&self) {
+        let mut fs = self.fs.lock();
+        fs.begin();
+        self.clear_locked(&mut fs);
+        fs.commit();
+    }
+
+    fn clear_locked(&self, fs: &mut MutexGuard<EasyFileSystem>) {
+        self.modify_disk_inode(|disk_inode| {
+            let size = disk_inode.size;
+            let data_blocks_dealloc = disk_inode.clear_size(&self.block_device);
+            assert!(data_blocks_dealloc.len() == DiskInode::total_blocks(size) as usize);
+            for data_block in data_blocks_dealloc.into_iter() {
+                fs.dealloc_data(data_block);
+            }
+        });
+    }
+
+    /// 将该文件所在块设备上被修改的块缓存写回
+    pub fn sync(&self) {
+        block_cache_sync_device(&self.block_device);
+    }
+
+    /// 索引节点编号
+    pub fn inode_id(&self) -> u32 {
+        self.fs
+            .lock()
+            .get_inode_id(self.block_id as u32, self.block_offset)
+    }
+
+    /// 是否为目录
+    pub fn is_dir(&self) -> bool {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.is_dir())
+    }
+
+    /// 硬链接数
+    pub fn nlink(&self) -> u32 {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.nlink)
+    }
+
+    /// 文件大小（字节）
+    pub fn size(&self) -> usize {
+        let _fs = self.fs.lock();
+        self.read_disk_inode(|disk_inode| disk_inode.size as usize)
+    }
+}
+
+impl Drop for Inode {
+    /// 释放句柄；若这是已被删除的索引节点的最后一个句柄，回收其索引节点与数据块
+    fn drop(&mut self) {
+        let pos = (self.block_id, self.block_offset);
+        let mut handles = self.handles.lock();
+        let count = handles.count.get_mut(&pos).unwrap();
+        *count -= 1;
+        if *count > 0 {
+            return;
+        }
+        handles.count.remove(&pos);
+        if !handles.orphans.remove(&pos) {
+            return;
+        }
+        drop(handles);
+        // 被删除的索引节点不可达，其句柄不会在持有文件系统的锁时释放
+        let mut fs = self.fs.lock();
+        let inode_id = fs.get_inode_id(self.block_id as u32, self.block_offset);
+        fs.begin();
+        self.clear_locked(&mut fs);
+        fs.dealloc_inode(inode_id);
+        fs.commit();
+    }
+}
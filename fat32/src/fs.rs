@@ -0,0 +1,167 @@
+//! fat32/src/fs.rs <br>
+//! 文件系统：解析引导扇区，沿文件分配表中的簇链读取数据
+
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use super::{BiosParameterBlock, BlockDevice, FatInode, SECTOR_SZ};
+
+/// 文件分配表项中的有效位
+const FAT_ENTRY_MASK: u32 = 0x0fff_ffff;
+/// 不小于该值的文件分配表项表示簇链结束
+const FAT_END_OF_CHAIN: u32 = 0x0fff_fff8;
+
+type Sector = [u8; SECTOR_SZ];
+
+/// FAT32文件系统
+pub struct Fat32FileSystem {
+    pub block_device: Arc<dyn BlockDevice>,
+    sectors_per_cluster: u32,
+    /// 第一个文件分配表的起始扇区
+    fat_start_sector: u32,
+    /// 数据区的起始扇区，即2号簇的位置
+    data_start_sector: u32,
+    /// 数据区中的簇数
+    cluster_count: u32,
+    root_cluster: u32,
+}
+
+impl Fat32FileSystem {
+    /// 打开块设备上的FAT32文件系统，不是FAT32时返回None
+    pub fn open(block_device: Arc<dyn BlockDevice>) -> Option<Arc<Self>> {
+        let mut sector: Sector = [0u8; SECTOR_SZ];
+        block_device.read_block(0, &mut sector);
+        let bpb = BiosParameterBlock::parse(&sector)?;
+        let data_start_sector = bpb.reserved_sectors + bpb.fat_count * bpb.fat_sectors;
+        let total_sectors = bpb.total_sectors.min(block_device.num_blocks() as u32);
+        if data_start_sector >= total_sectors {
+            return None;
+        }
+        Some(Arc::new(Self {
+            block_device,
+            sectors_per_cluster: bpb.sectors_per_cluster,
+            fat_start_sector: bpb.reserved_sectors,
+            data_start_sector,
+            cluster_count: (total_sectors - data_start_sector) / bpb.sectors_per_cluster,
+            root_cluster: bpb.root_cluster,
+        }))
+    }
+
+    /// 获取根目录
+    pub fn root_inode(fs: &Arc<Self>) -> FatInode {
+        FatInode::new_root(Arc::clone(fs), fs.root_cluster)
+    }
+
+    /// 根目录的起始簇号
+    pub fn root_cluster(&self) -> u32 {
+        self.root_cluster
+    }
+
+    /// 簇大小（字节）
+    pub fn cluster_size(&self) -> usize {
+        self.sectors_per_cluster as usize * SECTOR_SZ
+    }
+
+    /// 簇号是否位于数据区内
+    fn valid_cluster(&self, cluster: u32) -> bool {
+        cluster >= 2 && cluster < self.cluster_count + 2
+    }
+
+    /// 获取从first_cluster开始的簇链，遇到无效的簇号或回到已经过的簇（成环）时截断
+    pub fn cluster_chain(&self, first_cluster: u32) -> Vec<u32> {
+        let mut chain = Vec::new();
+        let mut visited = BTreeSet::new();
+        let mut cluster = first_cluster;
+        let mut cached: Option<(u32, Sector)> = None;
+        while self.valid_cluster(cluster) && visited.insert(cluster) {
+            chain.push(cluster);
+            let fat_offset = cluster as usize * 4;
+            let sector_id = self.fat_start_sector + (fat_offset / SECTOR_SZ) as u32;
+            // 相邻簇的表项通常位于同一扇区
+            if cached.as_ref().is_none_or(|(id, _)| *id != sector_id) {
+                let mut sector: Sector = [0u8; SECTOR_SZ];
+                self.block_device
+                    .read_block(sector_id as usize, &mut sector);
+                cached = Some((sector_id, sector));
+            }
+            let sector = &cached.as_ref().unwrap().1;
+            let offset = fat_offset % SECTOR_SZ;
+            let next =
+                u32::from_le_bytes(sector[offset..offset + 4].try_into().unwrap()) & FAT_ENTRY_MASK;
+            if next >= FAT_END_OF_CHAIN {
+                break;
+            }
+            cluster = next;
+        }
+        chain
+    }
+
+    /// 读取cluster号簇内偏移offset处的数据至buf，buf不能越过簇的末尾
+    pub fn read_cluster(&self, cluster: u32, offset: usize, buf: &mut [u8]) {
+        assert!(offset + buf.len() <= self.cluster_size());
+        let first_sector = self.data_start_sector + (cluster - 2) * self.sectors_per_cluster;
+        let mut sector: Sector = [0u8; SECTOR_SZ];
+        let mut read_size = 0usize;
+        while read_size < buf.len() {
+            let pos = offset + read_size;
+            let len = (SECTOR_SZ - pos % SECTOR_SZ).min(buf.len() - read_size);
+            self.block_device
+                .read_block((first_sector as usize) + pos / SECTOR_SZ, &mut sector);
+            buf[read_size..read_size + len]
+                .copy_from_slice(&sector[pos % SECTOR_SZ..pos % SECTOR_SZ + len]);
+            read_size += len;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc::vec;
+
+    use easy_fs::RamDisk;
+
+    use super::*;
+    use crate::layout::tests::boot_sector;
+
+    const FAT_END: u32 = 0x0fff_ffff;
+
+    /// 在内存块设备上构造共16个扇区、每簇1个扇区的FAT32文件系统，按entries设置文件分配表的表项；
+    /// 数据区起始于4号扇区，有效簇号为2..14
+    fn fs_with_fat(entries: &[(u32, u32)]) -> Arc<Fat32FileSystem> {
+        let image = vec![0u8; 16 * SECTOR_SZ].leak();
+        image[..SECTOR_SZ].copy_from_slice(&boot_sector(1, 2, 1, 16));
+        let fat_start = 2 * SECTOR_SZ;
+        for &(cluster, next) in entries {
+            let offset = fat_start + cluster as usize * 4;
+            image[offset..offset + 4].copy_from_slice(&next.to_le_bytes());
+        }
+        Fat32FileSystem::open(Arc::new(RamDisk::from_region(image))).unwrap()
+    }
+
+    #[test]
+    fn follow_cluster_chain() {
+        // 表项的高4位保留，不属于簇号
+        let fs = fs_with_fat(&[(2, 5), (5, 0xf000_0003), (3, FAT_END)]);
+        assert_eq!(fs.cluster_chain(2), [2, 5, 3]);
+        assert_eq!(fs.cluster_chain(3), [3]);
+    }
+
+    #[test]
+    fn cut_cluster_chain_at_loop() {
+        let fs = fs_with_fat(&[(2, 3), (3, 4), (4, 3), (6, 6)]);
+        assert_eq!(fs.cluster_chain(2), [2, 3, 4]);
+        assert_eq!(fs.cluster_chain(6), [6]);
+    }
+
+    #[test]
+    fn cut_cluster_chain_at_invalid_cluster() {
+        let fs = fs_with_fat(&[(2, 3), (3, 14), (7, 0), (8, 1)]);
+        assert_eq!(fs.cluster_chain(2), [2, 3]);
+        assert_eq!(fs.cluster_chain(7), [7]);
+        assert_eq!(fs.cluster_chain(8), [8]);
+        // 空文件的起始簇号为0
+        assert!(fs.cluster_chain(0).is_empty());
+        assert!(fs.cluster_chain(14).is_empty());
+    }
+}
@@ -0,0 +1,426 @@
+//! fat32/src/layout.rs <br>
+//! 磁盘数据结构：引导扇区中的BIOS参数块与目录项（含长文件名目录项）
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::SECTOR_SZ;
+
+/// 目录项大小
+pub const DIRENT_SZ: usize = 32;
+
+/// 目录项属性：卷标
+const ATTR_VOLUME_ID: u8 = 0x08;
+/// 目录项属性：目录
+const ATTR_DIRECTORY: u8 = 0x10;
+/// 长文件名目录项的属性
+const ATTR_LONG_NAME: u8 = 0x0f;
+/// 已删除的目录项的首字节
+const DIRENT_DELETED: u8 = 0xe5;
+/// 长文件名目录项中表示最后一项的序号标志
+const LAST_LONG_ENTRY: u8 = 0x40;
+/// 每个长文件名目录项中的字符数
+const LONG_NAME_CHARS: usize = 13;
+/// 短文件名的主文件名与扩展名为小写的标志
+const NT_LOWER_BASE: u8 = 0x08;
+const NT_LOWER_EXT: u8 = 0x10;
+
+fn read_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+/// 引导扇区中与FAT32相关的BIOS参数块字段
+#[derive(Debug)]
+pub struct BiosParameterBlock {
+    pub sectors_per_cluster: u32,
+    pub reserved_sectors: u32,
+    pub fat_count: u32,
+    pub total_sectors: u32,
+    /// 每个文件分配表占用的扇区数
+    pub fat_sectors: u32,
+    /// 根目录的起始簇号
+    pub root_cluster: u32,
+}
+
+impl BiosParameterBlock {
+    /// 解析引导扇区，不是扇区大小为512字节的FAT32时返回None
+    pub fn parse(sector: &[u8; SECTOR_SZ]) -> Option<Self> {
+        if sector[510] != 0x55 || sector[511] != 0xaa {
+            return None;
+        }
+        let bytes_per_sector = read_u16(sector, 0x0b) as usize;
+        let sectors_per_cluster = sector[0x0d] as u32;
+        let root_entry_count = read_u16(sector, 0x11);
+        let fat_size_16 = read_u16(sector, 0x16);
+        // FAT32的根目录位于数据区，且只使用32位的文件分配表大小字段
+        if bytes_per_sector != SECTOR_SZ
+            || !sectors_per_cluster.is_power_of_two()
+            || root_entry_count != 0
+            || fat_size_16 != 0
+        {
+            return None;
+        }
+        let bpb = Self {
+            sectors_per_cluster,
+            reserved_sectors: read_u16(sector, 0x0e) as u32,
+            fat_count: sector[0x10] as u32,
+            total_sectors: read_u32(sector, 0x20),
+            fat_sectors: read_u32(sector, 0x24),
+            root_cluster: read_u32(sector, 0x2c),
+        };
+        if bpb.fat_count == 0 || bpb.fat_sectors == 0 || bpb.root_cluster < 2 {
+            return None;
+        }
+        Some(bpb)
+    }
+}
+
+/// 目录中的一个文件或子目录
+#[derive(Clone, Debug)]
+pub struct FatDirEntry {
+    /// 文件名，有长文件名时为长文件名
+    pub name: String,
+    pub is_dir: bool,
+    /// 文件大小（字节），目录为0
+    pub size: u32,
+    /// 数据的起始簇号，空文件为0
+    pub first_cluster: u32,
+}
+
+/// 短文件名目录项的校验和，用于确认长文件名目录项属于该短文件名
+fn short_name_checksum(short_name: &[u8]) -> u8 {
+    short_name.iter().fold(0u8, |sum, byte| {
+        ((sum & 1) << 7).wrapping_add(sum >> 1).wrapping_add(*byte)
+    })
+}
+
+/// 解析8.3格式的短文件名
+fn short_name(raw: &[u8]) -> String {
+    let case_flags = raw[12];
+    let mut name = String::new();
+    for (i, byte) in raw[0..8].iter().enumerate() {
+        // 首字节0x05表示实际为0xe5
+        let byte = if i == 0 && *byte == 0x05 { 0xe5 } else { *byte };
+        if byte == b' ' {
+            break;
+        }
+        name.push(if case_flags & NT_LOWER_BASE != 0 {
+            byte.to_ascii_lowercase() as char
+        } else {
+            byte as char
+        });
+    }
+    if raw[8] != b' ' {
+        name.push('.');
+        for byte in raw[8..11].iter().take_while(|byte| **byte != b' ') {
+            name.push(if case_flags & NT_LOWER_EXT != 0 {
+                byte.to_ascii_lowercase() as char
+            } else {
+                *byte as char
+            });
+        }
+    }
+    name
+}
+
+/// 正在拼接的长文件名
+struct LongName {
+    /// 各长文件名目录项中的UCS-2字符，按序号排列
+    chars: Vec<u16>,
+    /// 尚未读到的长文件名目录项数
+    remaining: u8,
+    checksum: u8,
+}
+
+impl LongName {
+    fn new(count: u8, checksum: u8) -> Self {
+        Self {
+            chars: alloc::vec![0xffff; count as usize * LONG_NAME_CHARS],
+            remaining: count,
+            checksum,
+        }
+    }
+
+    /// 填入序号为ord（从1开始）的长文件名目录项
+    fn fill(&mut self, ord: u8, raw: &[u8]) {
+        let base = (ord as usize - 1) * LONG_NAME_CHARS;
+        let offsets = (1..11)
+            .step_by(2)
+            .chain((14..26).step_by(2))
+            .chain((28..32).step_by(2));
+        for (i, offset) in offsets.enumerate() {
+            self.chars[base + i] = read_u16(raw, offset);
+        }
+        self.remaining -= 1;
+    }
+
+    /// 长文件名完整且属于该短文件名目录项时返回长文件名
+    fn finish(&self, short_name_raw: &[u8]) -> Option<String> {
+        if self.remaining != 0 || self.checksum != short_name_checksum(&short_name_raw[..11]) {
+            return None;
+        }
+        let len = self
+            .chars
+            .iter()
+            .position(|c| *c == 0 || *c == 0xffff)
+            .unwrap_or(self.chars.len());
+        Some(
+            char::decode_utf16(self.chars[..len].iter().copied())
+                .map(|c| c.unwrap_or(char::REPLACEMENT_CHARACTER))
+                .collect(),
+        )
+    }
+}
+
+/// 解析目录的全部内容，返回其中的文件与子目录（含"."与".."，不含卷标）
+pub fn parse_dirents(data: &[u8]) -> Vec<FatDirEntry> {
+    let mut entries = Vec::new();
+    let mut long_name: Option<LongName> = None;
+    for raw in data.chunks_exact(DIRENT_SZ) {
+        // 首字节为0表示之后没有目录项
+        if raw[0] == 0 {
+            break;
+        }
+        if raw[0] == DIRENT_DELETED {
+            long_name = None;
+            continue;
+        }
+        let attr = raw[11];
+        if attr & 0x3f == ATTR_LONG_NAME {
+            let ord = raw[0] & !LAST_LONG_ENTRY;
+            if raw[0] & LAST_LONG_ENTRY != 0 {
+                long_name = Some(LongName::new(ord, raw[13]));
+            }
+            // 序号须依次递减，否则丢弃已拼接的部分
+            long_name = long_name
+                .filter(|name| ord >= 1 && ord == name.remaining && raw[13] == name.checksum);
+            if let Some(name) = long_name.as_mut() {
+                name.fill(ord, raw);
+            }
+            continue;
+        }
+        let long = long_name.take().and_then(|name| name.finish(raw));
+        if attr & ATTR_VOLUME_ID != 0 {
+            continue;
+        }
+        let first_cluster = (read_u16(raw, 20) as u32) << 16 | read_u16(raw, 26) as u32;
+        entries.push(FatDirEntry {
+            name: long.unwrap_or_else(|| short_name(raw)),
+            is_dir: attr & ATTR_DIRECTORY != 0,
+            size: read_u32(raw, 28),
+            first_cluster,
+        });
+    }
+    entries
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use alloc::string::String;
+    use alloc::vec::Vec;
+
+    use super::*;
+
+    /// 构造根目录起始于2号簇、有两个文件分配表的FAT32引导扇区
+    pub(crate) fn boot_sector(
+        sectors_per_cluster: u8,
+        reserved_sectors: u16,
+        fat_sectors: u32,
+        total_sectors: u32,
+    ) -> [u8; SECTOR_SZ] {
+        let mut sector = [0u8; SECTOR_SZ];
+        sector[0x0b..0x0d].copy_from_slice(&(SECTOR_SZ as u16).to_le_bytes());
+        sector[0x0d] = sectors_per_cluster;
+        sector[0x0e..0x10].copy_from_slice(&reserved_sectors.to_le_bytes());
+        sector[0x10] = 2;
+        sector[0x20..0x24].copy_from_slice(&total_sectors.to_le_bytes());
+        sector[0x24..0x28].copy_from_slice(&fat_sectors.to_le_bytes());
+        sector[0x2c..0x30].copy_from_slice(&2u32.to_le_bytes());
+        sector[510] = 0x55;
+        sector[511] = 0xaa;
+        sector
+    }
+
+    /// 短文件名目录项
+    fn short_entry(
+        name: &[u8; 11],
+        attr: u8,
+        case_flags: u8,
+        cluster: u32,
+        size: u32,
+    ) -> [u8; DIRENT_SZ] {
+        let mut raw = [0u8; DIRENT_SZ];
+        raw[..11].copy_from_slice(name);
+        raw[11] = attr;
+        raw[12] = case_flags;
+        raw[20..22].copy_from_slice(&((cluster >> 16) as u16).to_le_bytes());
+        raw[26..28].copy_from_slice(&(cluster as u16).to_le_bytes());
+        raw[28..32].copy_from_slice(&size.to_le_bytes());
+        raw
+    }
+
+    /// 长文件名name的各长文件名目录项，按在磁盘上的顺序（序号递减）排列
+    fn long_entries(name: &str, checksum: u8) -> Vec<[u8; DIRENT_SZ]> {
+        let mut chars: Vec<u16> = name.encode_utf16().collect();
+        let count = chars.len().div_ceil(LONG_NAME_CHARS);
+        // 未填满最后一项时以0结尾，其余位置填充0xffff
+        if !chars.len().is_multiple_of(LONG_NAME_CHARS) {
+            chars.push(0);
+        }
+        chars.resize(count * LONG_NAME_CHARS, 0xffff);
+        (1..=count)
+            .rev()
+            .map(|ord| {
+                let mut raw = [0u8; DIRENT_SZ];
+                raw[0] = ord as u8 | if ord == count { LAST_LONG_ENTRY } else { 0 };
+                raw[11] = ATTR_LONG_NAME;
+                raw[13] = checksum;
+                let offsets = (1..11)
+                    .step_by(2)
+                    .chain((14..26).step_by(2))
+                    .chain((28..32).step_by(2));
+                for (i, offset) in offsets.enumerate() {
+                    let c = chars[(ord - 1) * LONG_NAME_CHARS + i];
+                    raw[offset..offset + 2].copy_from_slice(&c.to_le_bytes());
+                }
+                raw
+            })
+            .collect()
+    }
+
+    fn names(entries: &[FatDirEntry]) -> Vec<&str> {
+        entries.iter().map(|entry| entry.name.as_str()).collect()
+    }
+
+    #[test]
+    fn parse_boot_sector() {
+        let bpb = BiosParameterBlock::parse(&boot_sector(8, 32, 100, 65536)).unwrap();
+        assert_eq!(bpb.sectors_per_cluster, 8);
+        assert_eq!(bpb.reserved_sectors, 32);
+        assert_eq!(bpb.fat_count, 2);
+        assert_eq!(bpb.total_sectors, 65536);
+        assert_eq!(bpb.fat_sectors, 100);
+        assert_eq!(bpb.root_cluster, 2);
+    }
+
+    #[test]
+    fn reject_non_fat32_boot_sector() {
+        let parse_modified = |modify: fn(&mut [u8; SECTOR_SZ])| {
+            let mut sector = boot_sector(8, 32, 100, 65536);
+            modify(&mut sector);
+            BiosParameterBlock::parse(&sector)
+        };
+        // 缺少引导扇区签名
+        assert!(parse_modified(|s| s[511] = 0).is_none());
+        // 扇区大小不是512字节
+        assert!(
+            parse_modified(|s| s[0x0b..0x0d].copy_from_slice(&4096u16.to_le_bytes())).is_none()
+        );
+        // 每簇扇区数不是2的幂
+        assert!(parse_modified(|s| s[0x0d] = 3).is_none());
+        assert!(parse_modified(|s| s[0x0d] = 0).is_none());
+        // FAT12/16：根目录区有目录项，或使用16位的文件分配表大小
+        assert!(parse_modified(|s| s[0x11] = 0x02).is_none());
+        assert!(parse_modified(|s| s[0x16] = 1).is_none());
+        // 没有文件分配表，或根目录簇号无效
+        assert!(parse_modified(|s| s[0x10] = 0).is_none());
+        assert!(parse_modified(|s| s[0x24..0x28].fill(0)).is_none());
+        assert!(parse_modified(|s| s[0x2c..0x30].copy_from_slice(&1u32.to_le_bytes())).is_none());
+    }
+
+    #[test]
+    fn assemble_long_names() {
+        let short = b"ALONGF~1TXT";
+        let exact = b"EXACTL~1TXT";
+        let mut entries = long_entries("A long file name.txt", short_name_checksum(short));
+        entries.push(short_entry(short, 0, 0, 0x0001_2345, 42));
+        // 恰好填满一项的长文件名没有结尾的0
+        entries.extend(long_entries("exactly13.txt", short_name_checksum(exact)));
+        entries.push(short_entry(exact, 0, 0, 3, 0));
+        entries.push(short_entry(
+            b"README  MD ",
+            0,
+            NT_LOWER_BASE | NT_LOWER_EXT,
+            4,
+            7,
+        ));
+        entries.push(short_entry(b"SUBDIR     ", ATTR_DIRECTORY, 0, 5, 0));
+        // 首字节为0的目录项之后的内容被忽略
+        entries.push([0u8; DIRENT_SZ]);
+        entries.push(short_entry(b"HIDDEN     ", 0, 0, 6, 0));
+        let parsed = parse_dirents(&entries.concat());
+        assert_eq!(
+            names(&parsed),
+            [
+                "A long file name.txt",
+                "exactly13.txt",
+                "readme.md",
+                "SUBDIR"
+            ]
+        );
+        assert!(!parsed[0].is_dir && parsed[3].is_dir);
+        assert_eq!(parsed[0].first_cluster, 0x0001_2345);
+        assert_eq!(parsed[0].size, 42);
+        assert_eq!(parsed[2].size, 7);
+    }
+
+    #[test]
+    fn discard_mismatched_long_names() {
+        let short = b"ALONGF~1TXT";
+        let checksum = short_name_checksum(short);
+        // 校验和不符
+        let mut entries = long_entries("A long file name.txt", checksum.wrapping_add(1));
+        entries.push(short_entry(short, 0, 0, 2, 0));
+        // 缺少序号最大的一项
+        entries.extend(
+            long_entries("A long file name.txt", checksum)
+                .into_iter()
+                .skip(1),
+        );
+        entries.push(short_entry(short, 0, 0, 3, 0));
+        // 序号不连续
+        let mut broken = long_entries("A much longer file name.txt", checksum);
+        broken.remove(1);
+        entries.extend(broken);
+        entries.push(short_entry(short, 0, 0, 4, 0));
+        let parsed = parse_dirents(&entries.concat());
+        assert_eq!(names(&parsed), ["ALONGF~1.TXT"; 3]);
+    }
+
+    #[test]
+    fn skip_deleted_entries_and_volume_labels() {
+        let short = b"ALONGF~1TXT";
+        let mut entries = Vec::from([short_entry(b"MYDISK     ", ATTR_VOLUME_ID, 0, 0, 0)]);
+        // 已删除的短文件名目录项连同其长文件名一起被跳过，长文件名不会被下一项误用
+        entries.extend(long_entries(
+            "A long file name.txt",
+            short_name_checksum(short),
+        ));
+        let mut deleted = short_entry(short, 0, 0, 2, 0);
+        deleted[0] = DIRENT_DELETED;
+        entries.push(deleted);
+        entries.push(short_entry(short, 0, 0, 3, 0));
+        // 已删除的长文件名目录项
+        let mut long = long_entries("deleted name", short_name_checksum(b"B       TXT"));
+        long[0][0] = DIRENT_DELETED;
+        entries.extend(long);
+        entries.push(short_entry(b"B       TXT", 0, 0, 4, 0));
+        let parsed = parse_dirents(&entries.concat());
+        assert_eq!(names(&parsed), ["ALONGF~1.TXT", "B.TXT"]);
+        assert_eq!(parsed[0].first_cluster, 3);
+    }
+
+    #[test]
+    fn first_byte_0x05_is_0xe5() {
+        let entries = [short_entry(b"\x05AB     TXT", 0, 0, 2, 0)];
+        let parsed = parse_dirents(&entries.concat());
+        let expected: String = ['\u{e5}', 'A', 'B', '.', 'T', 'X', 'T']
+            .into_iter()
+            .collect();
+        assert_eq!(parsed[0].name, expected);
+    }
+}
@@ -0,0 +1,21 @@
+//! fat32/src/lib.rs <br>
+//! 只读的FAT32文件系统，用于读取在宿主机上用`mkfs.vfat`/`mcopy`等工具准备的磁盘镜像 <br>
+//! 磁盘布局：保留区（含引导扇区） | 文件分配表 | 数据区（以簇为单位，根目录同样位于数据区）
+
+#![no_std]
+
+extern crate alloc;
+
+pub use easy_fs::BlockDevice;
+pub use fs::Fat32FileSystem;
+pub use layout::FatDirEntry;
+pub use vfs::FatInode;
+
+use layout::*;
+
+mod fs;
+mod layout;
+mod vfs;
+
+/// 扇区大小（字节），仅支持512字节的扇区
+pub const SECTOR_SZ: usize = 512;
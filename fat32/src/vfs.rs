@@ -0,0 +1,114 @@
+//! fat32/src/vfs.rs <br>
+//! 索引节点：对FAT32中文件与目录的只读访问接口 <br>
+//! FAT32没有索引节点，以目录项中记录的起始簇号标识文件；簇链在首次访问数据时读出并缓存
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use spin::Mutex;
+
+use super::{parse_dirents, Fat32FileSystem, FatDirEntry};
+
+/// 内存中的文件或目录
+pub struct FatInode {
+    fs: Arc<Fat32FileSystem>,
+    first_cluster: u32,
+    is_dir: bool,
+    /// 文件大小（字节），目录的大小由簇链决定
+    size: u32,
+    /// 簇链，首次访问数据时读出
+    clusters: Mutex<Option<Arc<Vec<u32>>>>,
+}
+
+impl FatInode {
+    pub(crate) fn new_root(fs: Arc<Fat32FileSystem>, root_cluster: u32) -> Self {
+        Self {
+            fs,
+            first_cluster: root_cluster,
+            is_dir: true,
+            size: 0,
+            clusters: Mutex::new(None),
+        }
+    }
+
+    fn child(&self, dirent: &FatDirEntry) -> Self {
+        // ".."的起始簇号为0时指向根目录
+        let first_cluster = if dirent.is_dir && dirent.first_cluster == 0 {
+            self.fs.root_cluster()
+        } else {
+            dirent.first_cluster
+        };
+        Self {
+            fs: Arc::clone(&self.fs),
+            first_cluster,
+            is_dir: dirent.is_dir,
+            size: dirent.size,
+            clusters: Mutex::new(None),
+        }
+    }
+
+    fn clusters(&self) -> Arc<Vec<u32>> {
+        let mut clusters = self.clusters.lock();
+        Arc::clone(
+            clusters.get_or_insert_with(|| Arc::new(self.fs.cluster_chain(self.first_cluster))),
+        )
+    }
+
+    /// 是否为目录
+    pub fn is_dir(&self) -> bool {
+        self.is_dir
+    }
+
+    /// 文件大小（字节），目录为其簇链的总长度
+    pub fn size(&self) -> usize {
+        if self.is_dir {
+            self.clusters().len() * self.fs.cluster_size()
+        } else {
+            self.size as usize
+        }
+    }
+
+    /// 标识文件的编号：起始簇号，空文件为0
+    pub fn inode_id(&self) -> u32 {
+        self.first_cluster
+    }
+
+    /// 从偏移offset处读取数据至buf，返回读取的字节数
+    pub fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let clusters = self.clusters();
+        let cluster_size = self.fs.cluster_size();
+        let end = (offset + buf.len())
+            .min(self.size())
+            .min(clusters.len() * cluster_size);
+        let mut pos = offset;
+        while pos < end {
+            let len = (cluster_size - pos % cluster_size).min(end - pos);
+            self.fs.read_cluster(
+                clusters[pos / cluster_size],
+                pos % cluster_size,
+                &mut buf[pos - offset..pos - offset + len],
+            );
+            pos += len;
+        }
+        end.saturating_sub(offset)
+    }
+
+    /// 列出目录中的所有目录项（含"."与".."，根目录中没有这两项）
+    pub fn dirents(&self) -> Vec<FatDirEntry> {
+        assert!(self.is_dir);
+        let mut data = alloc::vec![0u8; self.size()];
+        self.read_at(0, &mut data);
+        parse_dirents(&data)
+    }
+
+    /// 在目录中查找名为name的文件或子目录，文件名不区分大小写
+    pub fn find(&self, name: &str) -> Option<Arc<FatInode>> {
+        if !self.is_dir {
+            return None;
+        }
+        self.dirents()
+            .iter()
+            .find(|dirent| dirent.name.eq_ignore_ascii_case(name))
+            .map(|dirent| Arc::new(self.child(dirent)))
+    }
+}
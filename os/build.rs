@@ -1,62 +1,39 @@
 //! os/build.rs <br>
 //! 构建模块，会在加载项目时自动运行。
-//! 用于生成将user中的用户应用程序链入内核的.S文件
+//! 用于生成将磁盘镜像链入内核的.S文件，供没有块设备的平台（K210或启用ramdisk特性时）作为RamDisk使用
 
-use std::fs::{File, read_dir};
+use std::env;
+use std::fs::File;
 use std::io::{Result, Write};
 
 fn main() {
-    println!(" cargo:rerun-if-changed=../user/src/");
-    println!(" cargo:rerun-if-changed={}", TARGET_PATH);
-    insert_app_data().unwrap();
+    // 只有K210或启用ramdisk特性时才将磁盘镜像链入内核，否则内核镜像不必携带整个磁盘镜像
+    let ramdisk = env::var_os("CARGO_FEATURE_BOARD_K210").is_some()
+        || env::var_os("CARGO_FEATURE_RAMDISK").is_some();
+    if !ramdisk {
+        println!("cargo:rerun-if-changed=build.rs");
+        return;
+    }
+    println!("cargo:rerun-if-changed={}", FS_IMG_PATH);
+    insert_fs_img().unwrap();
 }
 
-static TARGET_PATH: &str = "../user/target/riscv64gc-unknown-none-elf/release/";
-
-fn insert_app_data() -> Result<()> {
-    let mut f = File::create("src/link_app.S").unwrap();        // 创建link_app.S
-    let mut apps: Vec<_> = read_dir("../user/src/bin")          // 读取"../user/src/bin"目录中的代码
-        .unwrap()
-        .into_iter()
-        .map(|dir_entry| {
-            let mut name_with_ext = dir_entry.unwrap().file_name().into_string().unwrap();
-            name_with_ext.drain(name_with_ext.find('.').unwrap()..name_with_ext.len());
-            name_with_ext
-        })
-        .collect();
-    apps.sort();
+static FS_IMG_PATH: &str = "target/fs.img";
 
-    // 写入头部索引
+fn insert_fs_img() -> Result<()> {
+    let mut f = File::create("src/link_fs.S").unwrap(); // 创建link_fs.S
+    // 磁盘镜像按页对齐，作为RamDisk时可被读写
     writeln!(
         f,
         r#"
-    .align 3
     .section .data
-    .global _num_app
-_num_app:
-    .quad {}"#,
-        apps.len()
+    .global sfs_img
+    .global efs_img
+    .align 12
+sfs_img:
+    .incbin "{}"
+efs_img:"#,
+        FS_IMG_PATH
     )?;
-
-    // 为每个APP编入
-    for i in 0..apps.len() {
-        writeln!(f, r#"    .quad app_{}_start"#, i)?;
-    }
-    writeln!(f, r#"    .quad app_{}_end"#, apps.len() - 1)?;
-
-    for (idx, app) in apps.iter().enumerate() {
-        println!("app_{}: {}", idx, app);
-        writeln!(
-            f,
-            r#"
-    .section .data
-    .global app_{0}_start
-    .global app_{0}_end
-app_{0}_start:
-    .incbin "{2}{1}.bin"
-app_{0}_end:"#,
-            idx, app, TARGET_PATH
-        )?;
-    }
     Ok(())
-}
\ No newline at end of file
+}
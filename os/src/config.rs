@@ -0,0 +1,89 @@
+//! os/src/config.rs <br>
+//! 内核全局配置常量
+
+/// 用户栈大小
+pub const USER_STACK_SIZE: usize = 4096 * 2;
+/// 内核栈大小
+pub const KERNEL_STACK_SIZE: usize = 4096 * 2;
+/// 内核堆大小
+pub const KERNEL_HEAP_SIZE: usize = 0x30_0000;
+
+/// 页大小
+pub const PAGE_SIZE: usize = 0x1000;
+/// 页内偏移位宽
+pub const PAGE_SIZE_BITS: usize = 0xc;
+
+/// 跳板页位于虚拟地址空间的最高页，在所有地址空间中均映射至同一物理页
+pub const TRAMPOLINE: usize = usize::MAX - PAGE_SIZE + 1;
+/// 应用地址空间中主线程TrapContext所在页，紧邻跳板页之下，其余线程的TrapContext页依次向下排列
+pub const TRAP_CONTEXT: usize = TRAMPOLINE - PAGE_SIZE;
+/// 每个进程至多同时拥有的线程数
+pub const MAX_THREADS: usize = 256;
+/// 文件描述符的上界，分配的描述符与dup2的目标描述符均不能达到该值
+pub const MAX_FD: usize = 1024;
+/// 线程的默认优先级，数值越大优先级越高
+pub const DEFAULT_PRIORITY: usize = 16;
+/// 线程可设置的最高优先级
+pub const MAX_PRIORITY: usize = 31;
+/// 内核栈区域的上界，位于所有线程的TrapContext页之下
+const KERNEL_STACK_TOP: usize = TRAP_CONTEXT - (MAX_THREADS - 1) * PAGE_SIZE;
+/// 应用可使用的虚拟地址上界，即SV39低半部分地址空间的上界
+pub const USER_SPACE_END: usize = 0x40_0000_0000;
+/// mmap从该地址起寻找空闲区间，远高于应用程序与各线程的用户栈
+pub const MMAP_BASE: usize = 0x10_0000_0000;
+
+/// 物理内存的结束地址
+#[cfg(feature = "board_qemu")]
+pub const MEMORY_END: usize = 0x8800_0000;
+#[cfg(feature = "board_k210")]
+pub const MEMORY_END: usize = 0x8080_0000;
+
+/// 需要在内核地址空间中恒等映射的MMIO区间(起始地址, 长度)
+#[cfg(feature = "board_qemu")]
+pub const MMIO: &[(usize, usize)] = &[
+    (0x0010_0000, 0x00_2000), // VIRT_TEST/RTC
+    (0x1000_1000, 0x00_2000), // VirtIO Block x2
+];
+#[cfg(feature = "board_k210")]
+pub const MMIO: &[(usize, usize)] = &[];
+
+/// 时钟频率
+#[cfg(feature = "board_qemu")]
+pub const CLOCK_FREQ: usize = 12500000;
+#[cfg(feature = "board_k210")]
+pub const CLOCK_FREQ: usize = 403000000 / 62;
+
+/// 获取线程tid的TrapContext页在应用地址空间中的位置
+pub fn trap_cx_position(tid: usize) -> usize {
+    TRAP_CONTEXT - tid * PAGE_SIZE
+}
+
+/// 获取线程tid的用户栈在应用地址空间中的位置，返回(栈底, 栈顶) <br>
+/// ustack_base为程序最高段之上隔一个保护页的地址，各线程的用户栈自此向上排列，彼此间同样隔一个保护页
+pub fn user_stack_position(ustack_base: usize, tid: usize) -> (usize, usize) {
+    let bottom = ustack_base + tid * (USER_STACK_SIZE + PAGE_SIZE);
+    (bottom, bottom + USER_STACK_SIZE)
+}
+
+/// 获取编号为id的内核栈在内核地址空间中的位置，返回(栈底, 栈顶) <br>
+/// 各内核栈之间相隔一个不映射的保护页，栈溢出时将触发缺页异常而不是破坏相邻的内核栈 <br>
+/// 内核栈位于所有TrapContext页之下，使内核地址空间除跳板页外不与应用地址空间重叠
+pub fn kernel_stack_position(id: usize) -> (usize, usize) {
+    let top = KERNEL_STACK_TOP - id * (KERNEL_STACK_SIZE + PAGE_SIZE);
+    let bottom = top - KERNEL_STACK_SIZE;
+    (bottom, top)
+}
+
+/// 若地址位于某个内核栈的保护页中，返回该内核栈的编号
+pub fn kernel_stack_guard_owner(addr: usize) -> Option<usize> {
+    if addr >= KERNEL_STACK_TOP {
+        return None;
+    }
+    let id = (KERNEL_STACK_TOP - 1 - addr) / (KERNEL_STACK_SIZE + PAGE_SIZE);
+    let (bottom, _) = kernel_stack_position(id);
+    if addr < bottom && addr >= bottom - PAGE_SIZE {
+        Some(id)
+    } else {
+        None
+    }
+}
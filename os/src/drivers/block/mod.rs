@@ -0,0 +1,73 @@
+//! os/src/drivers/block/mod.rs <br>
+//! 块设备，块设备接口由easy-fs定义
+
+use alloc::sync::Arc;
+use alloc::vec;
+
+#[cfg(any(feature = "board_k210", feature = "ramdisk"))]
+use easy_fs::RamDisk;
+pub use easy_fs::{BlockDevice, BLOCK_SZ as BLOCK_SIZE};
+use lazy_static::lazy_static;
+use log::*;
+
+#[cfg(feature = "board_qemu")]
+pub use virtio_blk::VirtIOBlock;
+
+#[cfg(feature = "board_qemu")]
+mod virtio_blk;
+
+#[cfg(all(feature = "board_qemu", not(feature = "ramdisk")))]
+lazy_static! {
+    /// 全局块设备，未挂载磁盘时为None
+    pub static ref BLOCK_DEVICE: Option<Arc<dyn BlockDevice>> =
+        VirtIOBlock::new(virtio_blk::VIRTIO0).map(|dev| Arc::new(dev) as Arc<dyn BlockDevice>);
+}
+
+#[cfg(any(feature = "board_k210", feature = "ramdisk"))]
+lazy_static! {
+    /// 全局块设备，K210上暂无SD卡驱动，使用链接进内核镜像的磁盘镜像作为RamDisk
+    pub static ref BLOCK_DEVICE: Option<Arc<dyn BlockDevice>> = {
+        extern "C" {
+            fn sfs_img();
+            fn efs_img();
+        }
+        let region = unsafe {
+            core::slice::from_raw_parts_mut(
+                sfs_img as usize as *mut u8,
+                efs_img as usize - sfs_img as usize,
+            )
+        };
+        Some(Arc::new(RamDisk::from_region(region)))
+    };
+}
+
+#[cfg(feature = "board_qemu")]
+lazy_static! {
+    /// 第二个块设备，用于挂载与宿主机交换文件的FAT32镜像，未挂载时为None
+    pub static ref FAT_BLOCK_DEVICE: Option<Arc<dyn BlockDevice>> =
+        VirtIOBlock::new(virtio_blk::VIRTIO1).map(|dev| Arc::new(dev) as Arc<dyn BlockDevice>);
+}
+
+#[cfg(feature = "board_k210")]
+lazy_static! {
+    /// 第二个块设备，K210上没有
+    pub static ref FAT_BLOCK_DEVICE: Option<Arc<dyn BlockDevice>> = None;
+}
+
+/// 初始化块设备，并对最后一块做一次不破坏数据的读写自检
+pub fn init() {
+    let Some(dev) = BLOCK_DEVICE.as_ref() else {
+        warn!("No block device found.");
+        return;
+    };
+    let block_id = dev.num_blocks() - 1;
+    let mut origin = vec![0u8; BLOCK_SIZE];
+    let mut buf = vec![0u8; BLOCK_SIZE];
+    dev.read_block(block_id, &mut origin);
+    let pattern: alloc::vec::Vec<u8> = (0..BLOCK_SIZE).map(|i| (i as u8) ^ 0x5a).collect();
+    dev.write_block(block_id, &pattern);
+    dev.read_block(block_id, &mut buf);
+    assert_eq!(buf, pattern, "Block device self-test failed!");
+    dev.write_block(block_id, &origin);
+    info!("Block device self-test passed, {} blocks.", dev.num_blocks());
+}
@@ -0,0 +1,145 @@
+//! os/src/drivers/block/virtio_blk.rs <br>
+//! QEMU virt平台上的VirtIO块设备驱动（轮询模式）
+
+use log::*;
+
+use crate::drivers::virtio::{BufferDirection, DeviceType, VirtIOHeader, VirtQueue};
+use crate::mm::{frame_alloc, FrameTracker, PhysAddr};
+use crate::sync::UPSafeCell;
+
+use super::{BlockDevice, BLOCK_SIZE};
+
+/// QEMU virt平台上第一个VirtIO-MMIO设备的地址
+pub const VIRTIO0: usize = 0x1000_1000;
+/// QEMU virt平台上第二个VirtIO-MMIO设备的地址
+pub const VIRTIO1: usize = 0x1000_2000;
+
+/// 块设备请求类型：读
+const VIRTIO_BLK_T_IN: u32 = 0;
+/// 块设备请求类型：写
+const VIRTIO_BLK_T_OUT: u32 = 1;
+/// 请求状态：成功
+const VIRTIO_BLK_S_OK: u8 = 0;
+
+/// 请求队列长度，每个请求占用3个描述符
+const QUEUE_SIZE: u16 = 8;
+
+/// 块设备请求头
+#[repr(C)]
+struct BlkReqHeader {
+    req_type: u32,
+    reserved: u32,
+    sector: u64,
+}
+
+// DMA页内各部分的偏移：请求头、数据、状态
+const DMA_HEADER_OFFSET: usize = 0;
+const DMA_DATA_OFFSET: usize = 512;
+const DMA_STATUS_OFFSET: usize = DMA_DATA_OFFSET + BLOCK_SIZE;
+
+struct VirtIOBlockInner {
+    header: VirtIOHeader,
+    queue: VirtQueue,
+    /// 请求使用的DMA页。内核栈并非恒等映射，因此数据先经由该页中转
+    dma: FrameTracker,
+}
+
+/// VirtIO块设备
+pub struct VirtIOBlock {
+    inner: UPSafeCell<VirtIOBlockInner>,
+    /// 设备容量（扇区数）
+    capacity: usize,
+}
+
+impl VirtIOBlock {
+    /// 探测并初始化base处的VirtIO块设备
+    pub fn new(base: usize) -> Option<Self> {
+        let header = VirtIOHeader::probe(base, DeviceType::Block)?;
+        header.begin_init();
+        let queue = VirtQueue::new(&header, 0, QUEUE_SIZE);
+        header.finish_init();
+        // 配置空间起始处为64位的容量字段
+        let capacity =
+            header.read_config(0) as usize | (header.read_config(4) as usize) << 32;
+        info!(
+            "VirtIO block device at {:#x}, capacity = {} sectors ({} KiB)",
+            base,
+            capacity,
+            capacity * BLOCK_SIZE / 1024
+        );
+        Some(Self {
+            inner: unsafe {
+                UPSafeCell::new(VirtIOBlockInner {
+                    header,
+                    queue,
+                    dma: frame_alloc().unwrap(),
+                })
+            },
+            capacity,
+        })
+    }
+
+    /// 发起一次块请求并等待完成，数据已位于或将写入DMA页中
+    fn request(&self, req_type: u32, block_id: usize) {
+        assert!(
+            block_id < self.capacity,
+            "block {} out of range (capacity {})",
+            block_id,
+            self.capacity
+        );
+        let mut inner = self.inner.exclusive_access();
+        let dma: PhysAddr = inner.dma.ppn.into();
+        *PhysAddr::from(dma.0 + DMA_HEADER_OFFSET).get_mut::<BlkReqHeader>() = BlkReqHeader {
+            req_type,
+            reserved: 0,
+            sector: block_id as u64,
+        };
+        *PhysAddr::from(dma.0 + DMA_STATUS_OFFSET).get_mut::<u8>() = 0xff;
+        let data_direction = if req_type == VIRTIO_BLK_T_IN {
+            BufferDirection::DeviceToDriver
+        } else {
+            BufferDirection::DriverToDevice
+        };
+        let inner = &mut *inner;
+        inner.queue.submit_and_wait(
+            &inner.header,
+            &[
+                (
+                    dma.0 + DMA_HEADER_OFFSET,
+                    core::mem::size_of::<BlkReqHeader>(),
+                    BufferDirection::DriverToDevice,
+                ),
+                (dma.0 + DMA_DATA_OFFSET, BLOCK_SIZE, data_direction),
+                (dma.0 + DMA_STATUS_OFFSET, 1, BufferDirection::DeviceToDriver),
+            ],
+        );
+        let status = *PhysAddr::from(dma.0 + DMA_STATUS_OFFSET).get_mut::<u8>();
+        assert_eq!(
+            status, VIRTIO_BLK_S_OK,
+            "VirtIO block request failed, type = {}, block = {}",
+            req_type, block_id
+        );
+    }
+
+    /// DMA页中数据区的切片
+    fn dma_data(&self) -> &'static mut [u8] {
+        let dma: PhysAddr = self.inner.exclusive_access().dma.ppn.into();
+        unsafe { core::slice::from_raw_parts_mut((dma.0 + DMA_DATA_OFFSET) as *mut u8, BLOCK_SIZE) }
+    }
+}
+
+impl BlockDevice for VirtIOBlock {
+    fn read_block(&self, block_id: usize, buf: &mut [u8]) {
+        self.request(VIRTIO_BLK_T_IN, block_id);
+        buf.copy_from_slice(self.dma_data());
+    }
+
+    fn write_block(&self, block_id: usize, buf: &[u8]) {
+        self.dma_data().copy_from_slice(buf);
+        self.request(VIRTIO_BLK_T_OUT, block_id);
+    }
+
+    fn num_blocks(&self) -> usize {
+        self.capacity
+    }
+}
@@ -0,0 +1,8 @@
+//! os/src/drivers/mod.rs <br>
+//! 设备驱动
+
+pub use block::{BLOCK_DEVICE, FAT_BLOCK_DEVICE};
+
+pub mod block;
+#[cfg(feature = "board_qemu")]
+mod virtio;
@@ -0,0 +1,291 @@
+//! os/src/drivers/virtio.rs <br>
+//! VirtIO-MMIO传输层与virtqueue，同时支持legacy(v1)与modern(v2)两种寄存器布局
+
+use alloc::vec::Vec;
+use core::mem::size_of;
+use core::sync::atomic::{fence, Ordering};
+
+use log::*;
+
+use crate::config::PAGE_SIZE;
+use crate::mm::{frame_alloc_contiguous, FrameTracker, PhysAddr};
+
+/// MagicValue寄存器的固定值"virt"
+const VIRTIO_MAGIC: u32 = 0x7472_6976;
+
+// MMIO寄存器偏移
+const REG_MAGIC_VALUE: usize = 0x000;
+const REG_VERSION: usize = 0x004;
+const REG_DEVICE_ID: usize = 0x008;
+const REG_DEVICE_FEATURES: usize = 0x010;
+const REG_DEVICE_FEATURES_SEL: usize = 0x014;
+const REG_DRIVER_FEATURES: usize = 0x020;
+const REG_DRIVER_FEATURES_SEL: usize = 0x024;
+const REG_GUEST_PAGE_SIZE: usize = 0x028; // legacy
+const REG_QUEUE_SEL: usize = 0x030;
+const REG_QUEUE_NUM_MAX: usize = 0x034;
+const REG_QUEUE_NUM: usize = 0x038;
+const REG_QUEUE_ALIGN: usize = 0x03c; // legacy
+const REG_QUEUE_PFN: usize = 0x040; // legacy
+const REG_QUEUE_READY: usize = 0x044; // modern
+const REG_QUEUE_NOTIFY: usize = 0x050;
+const REG_INTERRUPT_STATUS: usize = 0x060;
+const REG_INTERRUPT_ACK: usize = 0x064;
+const REG_STATUS: usize = 0x070;
+const REG_QUEUE_DESC_LOW: usize = 0x080; // modern
+const REG_QUEUE_DESC_HIGH: usize = 0x084;
+const REG_QUEUE_DRIVER_LOW: usize = 0x090;
+const REG_QUEUE_DRIVER_HIGH: usize = 0x094;
+const REG_QUEUE_DEVICE_LOW: usize = 0x0a0;
+const REG_QUEUE_DEVICE_HIGH: usize = 0x0a4;
+/// 设备配置空间
+const REG_CONFIG: usize = 0x100;
+
+// 设备状态位
+const STATUS_ACKNOWLEDGE: u32 = 1;
+const STATUS_DRIVER: u32 = 2;
+const STATUS_DRIVER_OK: u32 = 4;
+const STATUS_FEATURES_OK: u32 = 8;
+
+/// modern设备要求驱动接受的VIRTIO_F_VERSION_1特性（第32位）
+const VIRTIO_F_VERSION_1_HIGH: u32 = 1 << 0;
+
+/// VirtIO设备类型
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum DeviceType {
+    Block = 2,
+}
+
+/// VirtIO-MMIO设备的寄存器组
+pub struct VirtIOHeader {
+    base: usize,
+    legacy: bool,
+}
+
+impl VirtIOHeader {
+    /// 探测base处的VirtIO-MMIO设备，若不存在或类型不符则返回None
+    pub fn probe(base: usize, device_type: DeviceType) -> Option<Self> {
+        let header = Self {
+            base,
+            legacy: false,
+        };
+        if header.read(REG_MAGIC_VALUE) != VIRTIO_MAGIC {
+            return None;
+        }
+        let version = header.read(REG_VERSION);
+        let device_id = header.read(REG_DEVICE_ID);
+        if device_id != device_type as u32 {
+            debug!(
+                "VirtIO device at {:#x} has id {}, expect {:?}",
+                base, device_id, device_type
+            );
+            return None;
+        }
+        debug!("VirtIO {:?} device at {:#x}, version {}", device_type, base, version);
+        Some(Self {
+            base,
+            legacy: version == 1,
+        })
+    }
+
+    fn read(&self, offset: usize) -> u32 {
+        unsafe { ((self.base + offset) as *const u32).read_volatile() }
+    }
+
+    fn write(&self, offset: usize, value: u32) {
+        unsafe { ((self.base + offset) as *mut u32).write_volatile(value) }
+    }
+
+    /// 读取设备配置空间中偏移为offset的32位值
+    pub fn read_config(&self, offset: usize) -> u32 {
+        self.read(REG_CONFIG + offset)
+    }
+
+    /// 设备初始化的前半部分：复位、设置ACKNOWLEDGE/DRIVER并完成特性协商（不启用任何可选特性）
+    pub fn begin_init(&self) {
+        self.write(REG_STATUS, 0);
+        self.write(REG_STATUS, STATUS_ACKNOWLEDGE);
+        self.write(REG_STATUS, STATUS_ACKNOWLEDGE | STATUS_DRIVER);
+        self.write(REG_DEVICE_FEATURES_SEL, 0);
+        let features = self.read(REG_DEVICE_FEATURES);
+        trace!("VirtIO device features: {:#x}", features);
+        self.write(REG_DRIVER_FEATURES_SEL, 0);
+        self.write(REG_DRIVER_FEATURES, 0);
+        self.write(REG_DRIVER_FEATURES_SEL, 1);
+        self.write(
+            REG_DRIVER_FEATURES,
+            if self.legacy { 0 } else { VIRTIO_F_VERSION_1_HIGH },
+        );
+        if self.legacy {
+            self.write(REG_GUEST_PAGE_SIZE, PAGE_SIZE as u32);
+        } else {
+            self.write(
+                REG_STATUS,
+                STATUS_ACKNOWLEDGE | STATUS_DRIVER | STATUS_FEATURES_OK,
+            );
+            assert!(
+                self.read(REG_STATUS) & STATUS_FEATURES_OK != 0,
+                "VirtIO device rejected features"
+            );
+        }
+    }
+
+    /// 设备初始化的后半部分：设置DRIVER_OK
+    pub fn finish_init(&self) {
+        let status = self.read(REG_STATUS);
+        self.write(REG_STATUS, status | STATUS_DRIVER_OK);
+    }
+
+    /// 设备支持的最大队列长度
+    fn max_queue_size(&self, idx: u32) -> u32 {
+        self.write(REG_QUEUE_SEL, idx);
+        self.read(REG_QUEUE_NUM_MAX)
+    }
+
+    /// 将virtqueue的物理地址告知设备
+    fn setup_queue(&self, idx: u32, size: u32, desc: usize, avail: usize, used: usize) {
+        self.write(REG_QUEUE_SEL, idx);
+        self.write(REG_QUEUE_NUM, size);
+        if self.legacy {
+            // legacy布局要求三个区域连续存放，且used环按页对齐
+            self.write(REG_QUEUE_ALIGN, PAGE_SIZE as u32);
+            self.write(REG_QUEUE_PFN, (desc / PAGE_SIZE) as u32);
+        } else {
+            self.write(REG_QUEUE_DESC_LOW, desc as u32);
+            self.write(REG_QUEUE_DESC_HIGH, (desc >> 32) as u32);
+            self.write(REG_QUEUE_DRIVER_LOW, avail as u32);
+            self.write(REG_QUEUE_DRIVER_HIGH, (avail >> 32) as u32);
+            self.write(REG_QUEUE_DEVICE_LOW, used as u32);
+            self.write(REG_QUEUE_DEVICE_HIGH, (used >> 32) as u32);
+            self.write(REG_QUEUE_READY, 1);
+        }
+    }
+
+    /// 通知设备队列中有新的请求
+    fn notify(&self, idx: u32) {
+        self.write(REG_QUEUE_NOTIFY, idx);
+    }
+
+    /// 应答设备的中断（轮询模式下也需应答，否则中断状态将一直保持）
+    pub fn ack_interrupt(&self) {
+        let status = self.read(REG_INTERRUPT_STATUS);
+        if status != 0 {
+            self.write(REG_INTERRUPT_ACK, status);
+        }
+    }
+}
+
+/// 描述符标志：存在下一个描述符
+const VIRTQ_DESC_F_NEXT: u16 = 1;
+/// 描述符标志：设备可写（否则设备只读）
+const VIRTQ_DESC_F_WRITE: u16 = 2;
+
+/// 描述符表项
+#[repr(C)]
+struct Descriptor {
+    addr: u64,
+    len: u32,
+    flags: u16,
+    next: u16,
+}
+
+/// 缓冲区在描述符链中的方向
+#[derive(Copy, Clone, PartialEq)]
+pub enum BufferDirection {
+    /// 驱动写入、设备读取
+    DriverToDevice,
+    /// 设备写入、驱动读取
+    DeviceToDriver,
+}
+
+/// 采用轮询方式、每次只提交一条描述符链的virtqueue
+pub struct VirtQueue {
+    idx: u32,
+    size: u16,
+    /// desc/avail/used三个区域所在的连续物理页，随队列一同释放
+    _frames: Vec<FrameTracker>,
+    desc: usize,
+    avail: usize,
+    used: usize,
+    /// 驱动已提交的请求数
+    avail_idx: u16,
+    /// 驱动已处理的完成请求数
+    last_used_idx: u16,
+}
+
+impl VirtQueue {
+    /// 创建第idx个virtqueue并告知设备
+    pub fn new(header: &VirtIOHeader, idx: u32, size: u16) -> Self {
+        let max = header.max_queue_size(idx);
+        assert!(max != 0, "VirtIO queue {} is unavailable", idx);
+        let size = size.min(max as u16);
+        assert!(size.is_power_of_two());
+        let desc_size = size_of::<Descriptor>() * size as usize;
+        let avail_size = size_of::<u16>() * (3 + size as usize);
+        let used_size = size_of::<u16>() * 3 + size_of::<[u32; 2]>() * size as usize;
+        let used_offset = (desc_size + avail_size).div_ceil(PAGE_SIZE) * PAGE_SIZE;
+        let pages = (used_offset + used_size).div_ceil(PAGE_SIZE);
+        let frames = frame_alloc_contiguous(pages).expect("no memory for VirtIO queue");
+        let base: PhysAddr = frames[0].ppn.into();
+        let desc = base.0;
+        let avail = desc + desc_size;
+        let used = desc + used_offset;
+        header.setup_queue(idx, size as u32, desc, avail, used);
+        Self {
+            idx,
+            size,
+            _frames: frames,
+            desc,
+            avail,
+            used,
+            avail_idx: 0,
+            last_used_idx: 0,
+        }
+    }
+
+    /// 提交由若干缓冲区(物理地址, 长度, 方向)组成的描述符链，并轮询等待设备处理完成
+    pub fn submit_and_wait(&mut self, header: &VirtIOHeader, buffers: &[(usize, usize, BufferDirection)]) {
+        assert!(!buffers.is_empty() && buffers.len() <= self.size as usize);
+        let descs = self.desc as *mut Descriptor;
+        for (i, &(addr, len, direction)) in buffers.iter().enumerate() {
+            let mut flags = 0;
+            if direction == BufferDirection::DeviceToDriver {
+                flags |= VIRTQ_DESC_F_WRITE;
+            }
+            if i + 1 < buffers.len() {
+                flags |= VIRTQ_DESC_F_NEXT;
+            }
+            unsafe {
+                descs.add(i).write_volatile(Descriptor {
+                    addr: addr as u64,
+                    len: len as u32,
+                    flags,
+                    next: (i + 1) as u16,
+                });
+            }
+        }
+        // avail环：flags(u16) idx(u16) ring[size](u16)
+        let avail_ring = (self.avail + 4) as *mut u16;
+        let avail_idx = (self.avail + 2) as *mut u16;
+        unsafe {
+            avail_ring
+                .add((self.avail_idx % self.size) as usize)
+                .write_volatile(0);
+        }
+        fence(Ordering::SeqCst);
+        self.avail_idx = self.avail_idx.wrapping_add(1);
+        unsafe {
+            avail_idx.write_volatile(self.avail_idx);
+        }
+        fence(Ordering::SeqCst);
+        header.notify(self.idx);
+        // used环：flags(u16) idx(u16) ring[size]({id: u32, len: u32})
+        let used_idx = (self.used + 2) as *const u16;
+        while unsafe { used_idx.read_volatile() } == self.last_used_idx {
+            core::hint::spin_loop();
+        }
+        fence(Ordering::SeqCst);
+        self.last_used_idx = self.last_used_idx.wrapping_add(1);
+        header.ack_interrupt();
+    }
+}
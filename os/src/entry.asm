@@ -7,6 +7,10 @@ _start:
     call rust_main                  # transfer control to kernel Func
 
     .section .bss.stack
+    .align 12                       # the guard page must be page aligned
+    .globl boot_stack_guard         # guard page below the boot stack, left unmapped by the kernel address space
+boot_stack_guard:
+    .space 4096
     .globl boot_stack_lower_bound   # mark the stack lower bound
 boot_stack_lower_bound:
     .space 4096 * 16                # set the stack space as 4096*16Byte = 64KB
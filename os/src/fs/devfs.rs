@@ -0,0 +1,233 @@
+//! os/src/fs/devfs.rs <br>
+//! 设备文件系统：挂载于/dev，以字符设备文件的形式提供null、zero、console与urandom <br>
+//! 设备文件不记录读写偏移，读写总是作用于设备的当前状态
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use crate::sbi_call::console_putchar;
+use crate::sync::UPSafeCell;
+use crate::timer::get_time;
+
+use super::stdio::read_char;
+use super::{DirEntry, Inode, StatMode, SuperBlock};
+
+/// 设备文件系统的挂载点
+pub const DEVFS_MOUNT_POINT: &str = "/dev";
+
+/// 设备的行为
+trait Device: Send + Sync {
+    /// 从设备读取数据至buf，返回读取的字节数
+    fn read(&self, buf: &mut [u8]) -> usize;
+    /// 将buf写入设备，返回写入的字节数
+    fn write(&self, buf: &[u8]) -> usize;
+}
+
+/// 读取时立即返回文件结束，写入的数据全部丢弃
+struct Null;
+
+impl Device for Null {
+    fn read(&self, _buf: &mut [u8]) -> usize {
+        0
+    }
+
+    fn write(&self, buf: &[u8]) -> usize {
+        buf.len()
+    }
+}
+
+/// 读取时返回无穷多的0，写入的数据全部丢弃
+struct Zero;
+
+impl Device for Zero {
+    fn read(&self, buf: &mut [u8]) -> usize {
+        buf.fill(0);
+        buf.len()
+    }
+
+    fn write(&self, buf: &[u8]) -> usize {
+        buf.len()
+    }
+}
+
+/// 串口控制台，每次读取一个字符，暂无输入时阻塞
+struct Console;
+
+impl Device for Console {
+    fn read(&self, buf: &mut [u8]) -> usize {
+        if buf.is_empty() {
+            return 0;
+        }
+        buf[0] = read_char();
+        1
+    }
+
+    fn write(&self, buf: &[u8]) -> usize {
+        for &c in buf {
+            console_putchar(c as usize);
+        }
+        buf.len()
+    }
+}
+
+/// 伪随机数发生器，以xorshift64*生成，首次读取时以mtime为种子；写入的数据混入状态
+struct Urandom {
+    state: UPSafeCell<u64>,
+}
+
+impl Urandom {
+    fn next(state: &mut u64) -> u64 {
+        if *state == 0 {
+            *state = get_time() as u64 | 1;
+        }
+        *state ^= *state >> 12;
+        *state ^= *state << 25;
+        *state ^= *state >> 27;
+        state.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+}
+
+impl Device for Urandom {
+    fn read(&self, buf: &mut [u8]) -> usize {
+        let mut state = self.state.exclusive_access();
+        for chunk in buf.chunks_mut(8) {
+            let bytes = Self::next(&mut state).to_le_bytes();
+            chunk.copy_from_slice(&bytes[..chunk.len()]);
+        }
+        buf.len()
+    }
+
+    fn write(&self, buf: &[u8]) -> usize {
+        let mut state = self.state.exclusive_access();
+        for &c in buf {
+            *state = (*state).rotate_left(8) ^ c as u64;
+        }
+        buf.len()
+    }
+}
+
+/// 设备文件
+struct DevInode {
+    ino: u64,
+    device: Arc<dyn Device>,
+}
+
+impl Inode for DevInode {
+    fn ino(&self) -> u64 {
+        self.ino
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::CHAR
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+
+    fn read_at(&self, _offset: usize, buf: &mut [u8]) -> usize {
+        self.device.read(buf)
+    }
+
+    fn write_at(&self, _offset: usize, buf: &[u8]) -> usize {
+        self.device.write(buf)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// /dev目录，设备固定，不能创建或删除文件
+struct DevDir {
+    devices: Vec<(&'static str, Arc<DevInode>)>,
+}
+
+impl Inode for DevDir {
+    fn ino(&self) -> u64 {
+        1
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::DIR
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+
+    fn nlink(&self) -> u32 {
+        2
+    }
+
+    fn read_at(&self, _offset: usize, _buf: &mut [u8]) -> usize {
+        0
+    }
+
+    fn find(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        self.devices
+            .iter()
+            .find(|(dev_name, _)| *dev_name == name)
+            .map(|(_, inode)| inode.clone() as Arc<dyn Inode>)
+    }
+
+    fn dirents(&self) -> Vec<DirEntry> {
+        self.devices
+            .iter()
+            .map(|(name, inode)| DirEntry {
+                name: String::from(*name),
+                ino: inode.ino,
+                mode: StatMode::CHAR,
+            })
+            .collect()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// 设备文件系统
+pub struct DevFs {
+    root: Arc<DevDir>,
+}
+
+impl DevFs {
+    pub fn new() -> Self {
+        let devices: [(&'static str, Arc<dyn Device>); 4] = [
+            ("null", Arc::new(Null)),
+            ("zero", Arc::new(Zero)),
+            ("console", Arc::new(Console)),
+            (
+                "urandom",
+                Arc::new(Urandom {
+                    state: unsafe { UPSafeCell::new(0) },
+                }),
+            ),
+        ];
+        // 编号1为/dev目录自身
+        let devices = devices
+            .into_iter()
+            .enumerate()
+            .map(|(i, (name, device))| {
+                let ino = i as u64 + 2;
+                (name, Arc::new(DevInode { ino, device }))
+            })
+            .collect();
+        Self {
+            root: Arc::new(DevDir { devices }),
+        }
+    }
+}
+
+impl SuperBlock for DevFs {
+    fn fs_type(&self) -> &'static str {
+        "devfs"
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+}
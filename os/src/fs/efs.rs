@@ -0,0 +1,124 @@
+//! os/src/fs/efs.rs <br>
+//! easy-fs在虚拟文件系统中的实现，作为根文件系统挂载
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use easy_fs::{block_cache_sync_device, BlockDevice, DiskInodeType, EasyFileSystem};
+
+use super::{DirEntry, Inode, StatMode, SuperBlock};
+
+/// 一个easy-fs文件系统实例
+pub struct EfsSuperBlock {
+    block_device: Arc<dyn BlockDevice>,
+    root: Arc<easy_fs::Inode>,
+}
+
+impl EfsSuperBlock {
+    /// 打开块设备上的easy-fs，挂载时会重放日志中已提交的事务
+    pub fn open(block_device: Arc<dyn BlockDevice>) -> Self {
+        let efs = EasyFileSystem::open(block_device.clone());
+        Self {
+            block_device,
+            root: Arc::new(EasyFileSystem::root_inode(&efs)),
+        }
+    }
+}
+
+impl SuperBlock for EfsSuperBlock {
+    fn fs_type(&self) -> &'static str {
+        "easy-fs"
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn sync(&self) {
+        block_cache_sync_device(&self.block_device);
+    }
+}
+
+impl Inode for easy_fs::Inode {
+    fn ino(&self) -> u64 {
+        self.inode_id() as u64
+    }
+
+    fn mode(&self) -> StatMode {
+        if easy_fs::Inode::is_dir(self) {
+            StatMode::DIR
+        } else {
+            StatMode::FILE
+        }
+    }
+
+    fn size(&self) -> usize {
+        easy_fs::Inode::size(self)
+    }
+
+    fn nlink(&self) -> u32 {
+        easy_fs::Inode::nlink(self)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        easy_fs::Inode::read_at(self, offset, buf)
+    }
+
+    fn write_at(&self, offset: usize, buf: &[u8]) -> usize {
+        easy_fs::Inode::write_at(self, offset, buf)
+    }
+
+    fn find(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        easy_fs::Inode::find(self, name).map(|inode| inode as Arc<dyn Inode>)
+    }
+
+    fn dirents(&self) -> Vec<DirEntry> {
+        easy_fs::Inode::dirents(self)
+            .into_iter()
+            .map(|(name, inode_id, type_)| DirEntry {
+                name,
+                ino: inode_id as u64,
+                mode: match type_ {
+                    DiskInodeType::Directory => StatMode::DIR,
+                    DiskInodeType::File => StatMode::FILE,
+                },
+            })
+            .collect()
+    }
+
+    fn create(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        easy_fs::Inode::create(self, name).map(|inode| inode as Arc<dyn Inode>)
+    }
+
+    fn create_dir(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        easy_fs::Inode::create_dir(self, name).map(|inode| inode as Arc<dyn Inode>)
+    }
+
+    fn link(&self, name: &str, target: &dyn Inode) -> bool {
+        match target.as_any().downcast_ref::<easy_fs::Inode>() {
+            Some(target) => easy_fs::Inode::link(self, name, target),
+            None => false,
+        }
+    }
+
+    fn unlink(&self, name: &str) -> bool {
+        easy_fs::Inode::unlink(self, name)
+    }
+
+    fn clear(&self) {
+        easy_fs::Inode::clear(self)
+    }
+
+    fn sync(&self) {
+        easy_fs::Inode::sync(self)
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_dir(&self) -> bool {
+        easy_fs::Inode::is_dir(self)
+    }
+}
@@ -0,0 +1,99 @@
+//! os/src/fs/fat.rs <br>
+//! FAT32在虚拟文件系统中的实现：以只读方式挂载第二个块设备上的FAT32文件系统，用于读取宿主机准备的文件
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use fat32::{Fat32FileSystem, FatInode};
+use log::*;
+
+use crate::drivers::FAT_BLOCK_DEVICE;
+
+use super::{mount, DirEntry, Inode, StatMode, SuperBlock};
+
+/// FAT32文件系统的挂载点
+pub const FAT_MOUNT_POINT: &str = "/fat";
+
+/// 一个只读的FAT32文件系统实例
+pub struct FatSuperBlock {
+    root: Arc<FatInode>,
+}
+
+impl SuperBlock for FatSuperBlock {
+    fn fs_type(&self) -> &'static str {
+        "fat32"
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        self.root.clone()
+    }
+
+    fn read_only(&self) -> bool {
+        true
+    }
+}
+
+/// 若存在第二个块设备且其上为FAT32，将其挂载于FAT_MOUNT_POINT
+pub fn mount_fat() {
+    let Some(block_device) = FAT_BLOCK_DEVICE.clone() else {
+        return;
+    };
+    let Some(fs) = Fat32FileSystem::open(block_device) else {
+        warn!("The second block device is not FAT32, ignored.");
+        return;
+    };
+    let root = Arc::new(Fat32FileSystem::root_inode(&fs));
+    if !mount(FAT_MOUNT_POINT, Arc::new(FatSuperBlock { root })) {
+        warn!("Failed to mount FAT32 at {}.", FAT_MOUNT_POINT);
+    }
+}
+
+impl Inode for FatInode {
+    fn ino(&self) -> u64 {
+        self.inode_id() as u64
+    }
+
+    fn mode(&self) -> StatMode {
+        if FatInode::is_dir(self) {
+            StatMode::DIR
+        } else {
+            StatMode::FILE
+        }
+    }
+
+    fn size(&self) -> usize {
+        FatInode::size(self)
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        FatInode::read_at(self, offset, buf)
+    }
+
+    fn find(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        FatInode::find(self, name).map(|inode| inode as Arc<dyn Inode>)
+    }
+
+    fn dirents(&self) -> Vec<DirEntry> {
+        FatInode::dirents(self)
+            .into_iter()
+            .map(|dirent| DirEntry {
+                name: dirent.name,
+                ino: dirent.first_cluster as u64,
+                mode: if dirent.is_dir {
+                    StatMode::DIR
+                } else {
+                    StatMode::FILE
+                },
+            })
+            .collect()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_dir(&self) -> bool {
+        FatInode::is_dir(self)
+    }
+}
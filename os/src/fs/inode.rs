@@ -0,0 +1,247 @@
+//! os/src/fs/inode.rs <br>
+//! 打开的文件：将虚拟文件系统中的索引节点封装为带读写偏移的文件，并按路径打开文件
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use bitflags::*;
+
+use crate::mm::{reload_file_pages, writeback_file_pages, UserBuffer};
+use crate::println;
+use crate::sync::UPSafeCell;
+
+use super::{lookup, lookup_parent, write_dirents, File, Inode, Stat, StatMode};
+
+/// 进程打开的文件系统中的文件
+pub struct OSInode {
+    readable: bool,
+    writable: bool,
+    /// 每次写入前将偏移移至文件末尾
+    append: bool,
+    /// 打开时的规范化绝对路径，用于按目录文件描述符解析相对路径
+    path: String,
+    /// 所在文件系统的设备编号
+    dev: u64,
+    inner: UPSafeCell<OSInodeInner>,
+}
+
+struct OSInodeInner {
+    offset: usize,
+    inode: Arc<dyn Inode>,
+}
+
+impl OSInode {
+    pub fn new(
+        readable: bool,
+        writable: bool,
+        append: bool,
+        path: String,
+        dev: u64,
+        inode: Arc<dyn Inode>,
+    ) -> Self {
+        Self {
+            readable,
+            writable,
+            append,
+            path,
+            dev,
+            inner: unsafe { UPSafeCell::new(OSInodeInner { offset: 0, inode }) },
+        }
+    }
+
+    /// 从当前偏移处读取文件的剩余内容
+    pub fn read_all(&self) -> Vec<u8> {
+        let mut inner = self.inner.exclusive_access();
+        writeback_file_pages(self.dev, inner.inode.ino());
+        let mut buffer = [0u8; 512];
+        let mut v: Vec<u8> = Vec::new();
+        loop {
+            let len = inner.inode.read_at(inner.offset, &mut buffer);
+            if len == 0 {
+                break;
+            }
+            inner.offset += len;
+            v.extend_from_slice(&buffer[..len]);
+        }
+        v
+    }
+
+    /// 对应的索引节点
+    pub fn inode(&self) -> Arc<dyn Inode> {
+        self.inner.exclusive_access().inode.clone()
+    }
+
+    /// 打开时的绝对路径
+    pub fn path(&self) -> &str {
+        self.path.as_str()
+    }
+}
+
+/// 列出根目录中的所有应用
+pub fn list_apps() {
+    println!("/**** APPS ****");
+    let (root, _) = lookup("/").expect("No root filesystem mounted!");
+    for dirent in root.dirents() {
+        if dirent.name != "." && dirent.name != ".." {
+            println!("{}", dirent.name);
+        }
+    }
+    println!("**************/");
+}
+
+bitflags! {
+    /// 打开文件的标志，取值与Linux一致
+    pub struct OpenFlags: u32 {
+        /// 只读
+        const RDONLY = 0;
+        /// 只写
+        const WRONLY = 1 << 0;
+        /// 读写
+        const RDWR = 1 << 1;
+        /// 文件不存在时创建
+        const CREATE = 0o100;
+        /// 打开时清空文件
+        const TRUNC = 0o1000;
+        /// 追加写入
+        const APPEND = 0o2000;
+    }
+}
+
+impl OpenFlags {
+    /// 返回(可读, 可写)
+    pub fn read_write(&self) -> (bool, bool) {
+        if self.contains(Self::WRONLY) {
+            (false, true)
+        } else if self.contains(Self::RDWR) {
+            (true, true)
+        } else {
+            (true, false)
+        }
+    }
+}
+
+/// 按规范化的绝对路径与flags打开文件，失败时返回None <br>
+/// 目录只能以只读方式打开，只读文件系统中的文件不能以可写方式打开或创建
+pub fn open_file(path: &str, flags: OpenFlags) -> Option<Arc<OSInode>> {
+    let (readable, writable) = flags.read_write();
+    let append = flags.contains(OpenFlags::APPEND);
+    let (inode, mount) = match lookup(path) {
+        Some(found) => found,
+        None if flags.contains(OpenFlags::CREATE) => {
+            let (parent, mount, name) = lookup_parent(path)?;
+            if mount.sb.read_only() {
+                return None;
+            }
+            (parent.create(name)?, mount)
+        }
+        None => return None,
+    };
+    if (inode.is_dir() || mount.sb.read_only()) && writable {
+        return None;
+    }
+    if flags.contains(OpenFlags::TRUNC) && writable {
+        inode.clear();
+    }
+    Some(Arc::new(OSInode::new(
+        readable,
+        writable,
+        append,
+        String::from(path),
+        mount.dev,
+        inode,
+    )))
+}
+
+impl File for OSInode {
+    fn readable(&self) -> bool {
+        self.readable
+    }
+
+    fn writable(&self) -> bool {
+        self.writable
+    }
+
+    fn read(&self, mut buf: UserBuffer) -> usize {
+        // 读取设备时可能切换任务，不能持有对inner的借用
+        let (inode, mut offset) = {
+            let inner = self.inner.exclusive_access();
+            (inner.inode.clone(), inner.offset)
+        };
+        // 先写回mmap共享映射中的修改，使读到的数据与映射一致
+        writeback_file_pages(self.dev, inode.ino());
+        let mut total_read_size = 0usize;
+        for slice in buf.buffers.iter_mut() {
+            let read_size = inode.read_at(offset, slice);
+            if read_size == 0 {
+                break;
+            }
+            offset += read_size;
+            total_read_size += read_size;
+            if read_size < slice.len() {
+                break;
+            }
+        }
+        self.inner.exclusive_access().offset = offset;
+        total_read_size
+    }
+
+    fn write(&self, buf: UserBuffer) -> usize {
+        let (inode, mut offset) = {
+            let inner = self.inner.exclusive_access();
+            (inner.inode.clone(), inner.offset)
+        };
+        // 先写回mmap共享映射中的修改，写入后再重新读入受影响的缓存页，使各映射看到写入的数据
+        writeback_file_pages(self.dev, inode.ino());
+        if self.append {
+            offset = inode.size();
+        }
+        let start = offset;
+        let mut total_write_size = 0usize;
+        for slice in buf.buffers.iter() {
+            let write_size = inode.write_at(offset, slice);
+            offset += write_size;
+            total_write_size += write_size;
+            if write_size < slice.len() {
+                break;
+            }
+        }
+        reload_file_pages(self.dev, &inode, start, total_write_size);
+        self.inner.exclusive_access().offset = offset;
+        total_write_size
+    }
+
+    fn stat(&self) -> Stat {
+        let inode = self.inode();
+        Stat::new(
+            self.dev,
+            inode.ino(),
+            inode.mode(),
+            inode.nlink(),
+            inode.size() as u64,
+        )
+    }
+
+    fn mode(&self) -> StatMode {
+        self.inode().mode()
+    }
+
+    fn sync(&self) {
+        let inode = self.inode();
+        writeback_file_pages(self.dev, inode.ino());
+        inode.sync();
+    }
+
+    fn getdents(&self, buf: UserBuffer) -> isize {
+        let mut inner = self.inner.exclusive_access();
+        if !inner.inode.is_dir() {
+            return -1;
+        }
+        let dirents = inner.inode.dirents();
+        write_dirents(&dirents, &mut inner.offset, buf)
+    }
+
+    fn as_os_inode(&self) -> Option<&OSInode> {
+        Some(self)
+    }
+}
@@ -0,0 +1,178 @@
+//! os/src/fs/mod.rs <br>
+//! 文件系统：进程通过文件描述符访问的各类文件，以及挂载于虚拟文件系统中的各个文件系统 <br>
+//! 根目录挂载easy-fs，设备文件挂载于/dev，内核状态挂载于/proc，第二个块设备上的FAT32以只读方式挂载于/fat
+
+pub use devfs::{DevFs, DEVFS_MOUNT_POINT};
+pub use efs::EfsSuperBlock;
+pub use fat::mount_fat;
+pub use inode::{list_apps, open_file, OSInode, OpenFlags};
+pub use pipe::make_pipe;
+pub use procfs::{ProcFs, PROCFS_MOUNT_POINT};
+pub use stdio::{Stdin, Stdout};
+pub use vfs::{
+    is_mount_point, lookup, lookup_parent, mount, mounts, normalize_path, DirEntry, Inode,
+    SuperBlock,
+};
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use bitflags::*;
+
+use crate::drivers::BLOCK_DEVICE;
+use crate::mm::{writeback_all_pages, UserBuffer};
+
+mod devfs;
+mod efs;
+mod fat;
+mod inode;
+mod pipe;
+mod procfs;
+mod stdio;
+mod vfs;
+
+/// 进程可通过文件描述符读写的对象
+pub trait File: Send + Sync {
+    /// 是否可读
+    fn readable(&self) -> bool;
+    /// 是否可写
+    fn writable(&self) -> bool;
+    /// 从文件读取数据至应用缓冲区，返回读取的字节数
+    fn read(&self, buf: UserBuffer) -> usize;
+    /// 将应用缓冲区中的数据写入文件，返回写入的字节数
+    fn write(&self, buf: UserBuffer) -> usize;
+    /// 获取文件的元数据
+    fn stat(&self) -> Stat;
+    /// 文件的种类，与stat不同，获取时不计算文件大小等需要访问文件内容的信息
+    fn mode(&self) -> StatMode;
+    /// 将文件的脏数据写回磁盘，默认无需写回
+    fn sync(&self) {}
+    /// 读取目录项，按linux_dirent64的格式写入buf，默认不是目录，返回-1
+    fn getdents(&self, _buf: UserBuffer) -> isize {
+        -1
+    }
+    /// 若为磁盘文件则返回自身，用于按目录文件描述符解析路径与读取目录项
+    fn as_os_inode(&self) -> Option<&OSInode> {
+        None
+    }
+}
+
+/// 文件元数据，与用户库中的定义保持一致
+#[repr(C)]
+#[derive(Debug)]
+pub struct Stat {
+    /// 文件所在设备的编号
+    pub dev: u64,
+    /// 索引节点编号
+    pub ino: u64,
+    /// 文件类型与权限
+    pub mode: StatMode,
+    /// 硬链接数
+    pub nlink: u32,
+    /// 文件大小（字节）
+    pub size: u64,
+    pad: [u64; 6],
+}
+
+impl Stat {
+    /// 以字节序列的形式访问，用于复制到应用地址空间
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe {
+            core::slice::from_raw_parts(
+                self as *const _ as *const u8,
+                core::mem::size_of::<Self>(),
+            )
+        }
+    }
+
+    pub fn new(dev: u64, ino: u64, mode: StatMode, nlink: u32, size: u64) -> Self {
+        Self {
+            dev,
+            ino,
+            mode,
+            nlink,
+            size,
+            pad: [0; 6],
+        }
+    }
+}
+
+bitflags! {
+    /// 文件类型
+    pub struct StatMode: u32 {
+        const NULL  = 0;
+        /// 目录
+        const DIR   = 0o040000;
+        /// 普通文件
+        const FILE  = 0o100000;
+        /// 字符设备
+        const CHAR  = 0o020000;
+        /// 管道
+        const FIFO  = 0o010000;
+    }
+}
+
+impl StatMode {
+    /// 对应的linux_dirent64目录项类型
+    fn d_type(&self) -> u8 {
+        if self.contains(Self::DIR) {
+            4
+        } else if self.contains(Self::FILE) {
+            8
+        } else if self.contains(Self::CHAR) {
+            2
+        } else if self.contains(Self::FIFO) {
+            1
+        } else {
+            0
+        }
+    }
+}
+
+/// 从offset（目录项序号）处起将目录项按linux_dirent64的格式写入buf并推进offset <br>
+/// 返回写入的字节数，已读完时返回0，buf容纳不下一个目录项时返回-1
+fn write_dirents(dirents: &[DirEntry], offset: &mut usize, buf: UserBuffer) -> isize {
+    let capacity = buf.len();
+    let mut data: Vec<u8> = Vec::new();
+    for dirent in dirents.iter().skip(*offset) {
+        let name = dirent.name.as_str();
+        // d_ino(8) d_off(8) d_reclen(2) d_type(1) d_name(以'\0'结尾)，按8字节对齐
+        let reclen = (19 + name.len() + 1).next_multiple_of(8);
+        if data.len() + reclen > capacity {
+            break;
+        }
+        *offset += 1;
+        data.extend_from_slice(&dirent.ino.to_le_bytes());
+        data.extend_from_slice(&(*offset as u64).to_le_bytes());
+        data.extend_from_slice(&(reclen as u16).to_le_bytes());
+        data.push(dirent.mode.d_type());
+        data.extend_from_slice(name.as_bytes());
+        data.resize(data.len() + reclen - 19 - name.len(), 0);
+    }
+    if data.is_empty() && *offset < dirents.len() {
+        return -1;
+    }
+    for (dst, src) in buf.into_iter().zip(data.iter()) {
+        unsafe {
+            *dst = *src;
+        }
+    }
+    data.len() as isize
+}
+
+/// 挂载根文件系统与其他文件系统
+pub fn init() {
+    let block_device = BLOCK_DEVICE.clone().expect("No block device to mount!");
+    assert!(mount("/", Arc::new(EfsSuperBlock::open(block_device))));
+    assert!(mount(DEVFS_MOUNT_POINT, Arc::new(DevFs::new())));
+    assert!(mount(PROCFS_MOUNT_POINT, Arc::new(ProcFs)));
+    mount_fat();
+}
+
+/// 将所有挂载的文件系统的脏数据（含mmap共享映射中修改过的页）写回块设备
+pub fn sync_all() {
+    writeback_all_pages();
+    for mount in mounts() {
+        mount.sb.sync();
+    }
+}
@@ -0,0 +1,419 @@
+//! os/src/fs/procfs.rs <br>
+//! 进程文件系统：挂载于/proc，以文本文件的形式展示内核状态 <br>
+//! 全局文件：meminfo、uptime、apps、sched；每个存活进程对应目录/proc/<pid>，含status、maps、fds、syscalls <br>
+//! 文件内容在每次读取时重新生成
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+use core::fmt::Write;
+
+use crate::config::PAGE_SIZE;
+use crate::mm::{frame_stats, heap_stats, MapPermission};
+use crate::syscall::syscall_name;
+use crate::task::{
+    all_processes, current_task, find_process, ready_tasks, switch_count, ProcessControlBlock,
+    TaskStatus,
+};
+use crate::timer::get_time_us;
+
+use super::{lookup, DirEntry, Inode, StatMode, SuperBlock};
+
+/// 进程文件系统的挂载点
+pub const PROCFS_MOUNT_POINT: &str = "/proc";
+
+/// 全局文件的名称
+const GLOBAL_FILES: [(&str, ProcFileKind); 4] = [
+    ("meminfo", ProcFileKind::Meminfo),
+    ("uptime", ProcFileKind::Uptime),
+    ("apps", ProcFileKind::Apps),
+    ("sched", ProcFileKind::Sched),
+];
+
+/// 进程目录中文件的名称
+const PID_FILES: [(&str, ProcFileKind); 4] = [
+    ("status", ProcFileKind::Status),
+    ("maps", ProcFileKind::Maps),
+    ("fds", ProcFileKind::Fds),
+    ("syscalls", ProcFileKind::Syscalls),
+];
+
+/// procfs中文件的种类
+#[derive(Copy, Clone)]
+enum ProcFileKind {
+    /// 物理内存与内核堆的使用情况
+    Meminfo,
+    /// 启动以来经过的时间
+    Uptime,
+    /// 根目录中的应用
+    Apps,
+    /// 调度器状态
+    Sched,
+    /// 进程的基本信息
+    Status,
+    /// 进程地址空间中的逻辑段
+    Maps,
+    /// 进程打开的文件
+    Fds,
+    /// 进程各系统调用的调用次数
+    Syscalls,
+}
+
+/// procfs中的文件，pid为0时表示全局文件
+struct ProcFile {
+    kind: ProcFileKind,
+    pid: usize,
+}
+
+impl ProcFile {
+    /// 生成文件内容，进程已被回收时返回None
+    fn content(&self) -> Option<String> {
+        let mut s = String::new();
+        match self.kind {
+            ProcFileKind::Meminfo => meminfo(&mut s),
+            ProcFileKind::Uptime => {
+                let us = get_time_us();
+                writeln!(s, "{}.{:02}", us / 1_000_000, us % 1_000_000 / 10_000).unwrap();
+            }
+            ProcFileKind::Apps => apps(&mut s),
+            ProcFileKind::Sched => sched(&mut s),
+            ProcFileKind::Status => status(&find_process(self.pid)?, &mut s),
+            ProcFileKind::Maps => maps(&find_process(self.pid)?, &mut s),
+            ProcFileKind::Fds => fds(&find_process(self.pid)?, &mut s),
+            ProcFileKind::Syscalls => syscalls(&find_process(self.pid)?, &mut s),
+        }
+        Some(s)
+    }
+}
+
+fn meminfo(s: &mut String) {
+    let (total, free) = frame_stats();
+    let (heap_total, heap_used) = heap_stats();
+    writeln!(s, "MemTotal:  {:>8} kB", total * PAGE_SIZE / 1024).unwrap();
+    writeln!(s, "MemFree:   {:>8} kB", free * PAGE_SIZE / 1024).unwrap();
+    writeln!(s, "MemUsed:   {:>8} kB", (total - free) * PAGE_SIZE / 1024).unwrap();
+    writeln!(s, "HeapTotal: {:>8} kB", heap_total / 1024).unwrap();
+    writeln!(s, "HeapUsed:  {:>8} kB", heap_used / 1024).unwrap();
+}
+
+fn apps(s: &mut String) {
+    let Some((root, _)) = lookup("/") else {
+        return;
+    };
+    for dirent in root.dirents() {
+        if dirent.mode.contains(StatMode::FILE) {
+            writeln!(s, "{}", dirent.name).unwrap();
+        }
+    }
+}
+
+/// 就绪任务按调度顺序列出，每项为pid/tid:有效优先级
+fn sched(s: &mut String) {
+    let ready: Vec<String> = ready_tasks()
+        .iter()
+        .map(|task| {
+            let inner = task.inner_exclusive_access();
+            let tid = inner.res.as_ref().map_or(0, |res| res.tid);
+            format!("{}/{}:{}", task.getpid(), tid, inner.priority)
+        })
+        .collect();
+    writeln!(s, "Policy:\tPriority (FIFO within a priority; yielders defer to ready tasks)").unwrap();
+    match current_task() {
+        Some(task) => writeln!(s, "Current:\t{}", task.getpid()).unwrap(),
+        None => writeln!(s, "Current:\t-").unwrap(),
+    }
+    writeln!(s, "Ready:\t{}", ready.join(" ")).unwrap();
+    writeln!(s, "Processes:\t{}", all_processes().len()).unwrap();
+    writeln!(s, "Switches:\t{}", switch_count()).unwrap();
+}
+
+fn join_pids(pids: &[usize]) -> String {
+    pids.iter()
+        .map(|pid| pid.to_string())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn status(process: &Arc<ProcessControlBlock>, s: &mut String) {
+    let inner = process.inner_exclusive_access();
+    let ppid = inner
+        .parent
+        .as_ref()
+        .and_then(|parent| parent.upgrade())
+        .map_or(0, |parent| parent.getpid());
+    // 按未退出线程中最活跃者的状态确定进程状态：运行、就绪，或全部阻塞时为睡眠
+    let statuses: Vec<TaskStatus> = inner
+        .tasks
+        .iter()
+        .flatten()
+        .filter_map(|task| {
+            let task_inner = task.inner_exclusive_access();
+            task_inner.exit_code.is_none().then_some(task_inner.task_status)
+        })
+        .collect();
+    let state = if inner.is_zombie {
+        "Z (zombie)"
+    } else if statuses.contains(&TaskStatus::Running) {
+        "R (running)"
+    } else if statuses.contains(&TaskStatus::Ready) {
+        "R (ready)"
+    } else {
+        "S (sleeping)"
+    };
+    let children: Vec<usize> = inner.children.iter().map(|child| child.getpid()).collect();
+    let pages: usize = inner
+        .memory_set
+        .areas_info()
+        .iter()
+        .map(|(_, _, _, frames)| frames)
+        .sum();
+    writeln!(s, "Name:\t{}", inner.name).unwrap();
+    writeln!(s, "Pid:\t{}", process.getpid()).unwrap();
+    writeln!(s, "PPid:\t{}", ppid).unwrap();
+    writeln!(s, "State:\t{}", state).unwrap();
+    writeln!(s, "Cwd:\t{}", inner.cwd).unwrap();
+    writeln!(s, "Threads:\t{}", statuses.len()).unwrap();
+    writeln!(s, "Children:\t{}", join_pids(&children)).unwrap();
+    writeln!(s, "VmRSS:\t{} kB", pages * PAGE_SIZE / 1024).unwrap();
+    if inner.is_zombie {
+        writeln!(s, "ExitCode:\t{}", inner.exit_code).unwrap();
+    }
+}
+
+fn maps(process: &Arc<ProcessControlBlock>, s: &mut String) {
+    let inner = process.inner_exclusive_access();
+    for (start, end, perm, frames) in inner.memory_set.areas_info() {
+        let flag = |bit: MapPermission, c: char| if perm.contains(bit) { c } else { '-' };
+        writeln!(
+            s,
+            "{:016x}-{:016x} {}{}{}{} {}",
+            start,
+            end,
+            flag(MapPermission::R, 'r'),
+            flag(MapPermission::W, 'w'),
+            flag(MapPermission::X, 'x'),
+            flag(MapPermission::U, 'u'),
+            frames
+        )
+        .unwrap();
+    }
+}
+
+fn fds(process: &Arc<ProcessControlBlock>, s: &mut String) {
+    let files: Vec<_> = process
+        .inner_exclusive_access()
+        .fd_table
+        .iter()
+        .enumerate()
+        .filter_map(|(fd, file)| Some((fd, file.clone()?)))
+        .collect();
+    for (fd, file) in files {
+        let mode = file.mode();
+        let kind = if mode.contains(StatMode::DIR) {
+            "dir"
+        } else if mode.contains(StatMode::FILE) {
+            "file"
+        } else if mode.contains(StatMode::CHAR) {
+            "char"
+        } else if mode.contains(StatMode::FIFO) {
+            "fifo"
+        } else {
+            "-"
+        };
+        let path = file.as_os_inode().map_or("-", |os_inode| os_inode.path());
+        writeln!(
+            s,
+            "{}\t{}{}{}\t{}",
+            fd,
+            kind,
+            if file.readable() { " r" } else { "" },
+            if file.writable() { " w" } else { "" },
+            path
+        )
+        .unwrap();
+    }
+}
+
+fn syscalls(process: &Arc<ProcessControlBlock>, s: &mut String) {
+    let inner = process.inner_exclusive_access();
+    for (id, count) in inner.syscall_counts.iter() {
+        writeln!(s, "{:<16}{:>5}{:>10}", syscall_name(*id), id, count).unwrap();
+    }
+}
+
+impl Inode for ProcFile {
+    fn ino(&self) -> u64 {
+        ((self.pid as u64) << 8) | (self.kind as u64 + 2)
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::FILE
+    }
+
+    /// 内容在读取时才生成，大小记为0
+    fn size(&self) -> usize {
+        0
+    }
+
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize {
+        let Some(content) = self.content() else {
+            return 0;
+        };
+        let content = content.as_bytes();
+        if offset >= content.len() {
+            return 0;
+        }
+        let len = buf.len().min(content.len() - offset);
+        buf[..len].copy_from_slice(&content[offset..offset + len]);
+        len
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// 进程目录/proc/<pid>
+struct ProcPidDir {
+    pid: usize,
+}
+
+impl Inode for ProcPidDir {
+    fn ino(&self) -> u64 {
+        (self.pid as u64) << 8
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::DIR
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+
+    fn nlink(&self) -> u32 {
+        2
+    }
+
+    fn read_at(&self, _offset: usize, _buf: &mut [u8]) -> usize {
+        0
+    }
+
+    fn find(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        find_process(self.pid)?;
+        let (_, kind) = PID_FILES.iter().find(|(file_name, _)| *file_name == name)?;
+        Some(Arc::new(ProcFile {
+            kind: *kind,
+            pid: self.pid,
+        }))
+    }
+
+    fn dirents(&self) -> Vec<DirEntry> {
+        if find_process(self.pid).is_none() {
+            return Vec::new();
+        }
+        PID_FILES
+            .iter()
+            .map(|(name, kind)| DirEntry {
+                name: String::from(*name),
+                ino: ProcFile {
+                    kind: *kind,
+                    pid: self.pid,
+                }
+                .ino(),
+                mode: StatMode::FILE,
+            })
+            .collect()
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// /proc目录：全局文件与各存活进程的目录
+struct ProcRoot;
+
+impl Inode for ProcRoot {
+    fn ino(&self) -> u64 {
+        1
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::DIR
+    }
+
+    fn size(&self) -> usize {
+        0
+    }
+
+    fn nlink(&self) -> u32 {
+        2
+    }
+
+    fn read_at(&self, _offset: usize, _buf: &mut [u8]) -> usize {
+        0
+    }
+
+    fn find(&self, name: &str) -> Option<Arc<dyn Inode>> {
+        if let Some((_, kind)) = GLOBAL_FILES
+            .iter()
+            .find(|(file_name, _)| *file_name == name)
+        {
+            return Some(Arc::new(ProcFile {
+                kind: *kind,
+                pid: 0,
+            }));
+        }
+        // pid以十进制表示，不接受前导0
+        let pid: usize = name.parse().ok()?;
+        if pid.to_string() != name {
+            return None;
+        }
+        find_process(pid)?;
+        Some(Arc::new(ProcPidDir { pid }))
+    }
+
+    fn dirents(&self) -> Vec<DirEntry> {
+        let mut dirents: Vec<DirEntry> = GLOBAL_FILES
+            .iter()
+            .map(|(name, kind)| DirEntry {
+                name: String::from(*name),
+                ino: ProcFile {
+                    kind: *kind,
+                    pid: 0,
+                }
+                .ino(),
+                mode: StatMode::FILE,
+            })
+            .collect();
+        for process in all_processes() {
+            let pid = process.getpid();
+            dirents.push(DirEntry {
+                name: format!("{}", pid),
+                ino: ProcPidDir { pid }.ino(),
+                mode: StatMode::DIR,
+            });
+        }
+        dirents
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+/// 进程文件系统
+pub struct ProcFs;
+
+impl SuperBlock for ProcFs {
+    fn fs_type(&self) -> &'static str {
+        "procfs"
+    }
+
+    fn root_inode(&self) -> Arc<dyn Inode> {
+        Arc::new(ProcRoot)
+    }
+}
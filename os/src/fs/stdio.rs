@@ -0,0 +1,89 @@
+//! os/src/fs/stdio.rs <br>
+//! 标准输入输出：通过SBI读写串口
+
+use crate::mm::UserBuffer;
+use crate::print;
+use crate::sbi_call::console_getchar;
+use crate::task::yield_current_and_run_next;
+
+use super::{File, Stat, StatMode};
+
+/// 从串口读取一个字符，暂无输入时让出CPU
+pub fn read_char() -> u8 {
+    loop {
+        let c = console_getchar();
+        if c == 0 || c == usize::MAX {
+            yield_current_and_run_next();
+            continue;
+        }
+        return c as u8;
+    }
+}
+
+/// 标准输入
+pub struct Stdin;
+
+/// 标准输出
+pub struct Stdout;
+
+impl File for Stdin {
+    fn readable(&self) -> bool {
+        true
+    }
+
+    fn writable(&self) -> bool {
+        false
+    }
+
+    /// 每次只读取一个字符
+    fn read(&self, user_buf: UserBuffer) -> usize {
+        if user_buf.is_empty() {
+            return 0;
+        }
+        unsafe {
+            *user_buf.into_iter().next().unwrap() = read_char();
+        }
+        1
+    }
+
+    fn write(&self, _user_buf: UserBuffer) -> usize {
+        panic!("Cannot write to stdin!");
+    }
+
+    fn stat(&self) -> Stat {
+        Stat::new(0, 0, self.mode(), 1, 0)
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::CHAR
+    }
+}
+
+impl File for Stdout {
+    fn readable(&self) -> bool {
+        false
+    }
+
+    fn writable(&self) -> bool {
+        true
+    }
+
+    fn read(&self, _user_buf: UserBuffer) -> usize {
+        panic!("Cannot read from stdout!");
+    }
+
+    fn write(&self, user_buf: UserBuffer) -> usize {
+        for buffer in user_buf.buffers.iter() {
+            print!("{}", core::str::from_utf8(buffer).unwrap());
+        }
+        user_buf.len()
+    }
+
+    fn stat(&self) -> Stat {
+        Stat::new(0, 1, self.mode(), 1, 0)
+    }
+
+    fn mode(&self) -> StatMode {
+        StatMode::CHAR
+    }
+}
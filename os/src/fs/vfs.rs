@@ -0,0 +1,239 @@
+//! os/src/fs/vfs.rs <br>
+//! 虚拟文件系统：各文件系统实现的索引节点与超级块接口，以及挂载表与跨挂载点的路径解析 <br>
+//! 路径总是先规范化为绝对路径，再由最长匹配的挂载点所在的文件系统逐级查找
+
+use alloc::format;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::any::Any;
+
+use lazy_static::lazy_static;
+use log::*;
+
+use crate::sync::UPSafeCell;
+
+use super::StatMode;
+
+/// 目录项
+pub struct DirEntry {
+    pub name: String,
+    /// 索引节点编号
+    pub ino: u64,
+    /// 文件类型
+    pub mode: StatMode,
+}
+
+/// 文件系统中的文件、目录或设备 <br>
+/// 修改目录的操作默认不支持，只读文件系统无需实现
+pub trait Inode: Send + Sync {
+    /// 索引节点编号
+    fn ino(&self) -> u64;
+    /// 文件类型
+    fn mode(&self) -> StatMode;
+    /// 文件大小（字节）
+    fn size(&self) -> usize;
+    /// 硬链接数
+    fn nlink(&self) -> u32 {
+        1
+    }
+    /// 从偏移offset处读取数据至buf，返回读取的字节数
+    fn read_at(&self, offset: usize, buf: &mut [u8]) -> usize;
+    /// 将buf写入偏移offset处，返回写入的字节数
+    fn write_at(&self, _offset: usize, _buf: &[u8]) -> usize {
+        0
+    }
+    /// 在目录中查找名为name的目录项
+    fn find(&self, _name: &str) -> Option<Arc<dyn Inode>> {
+        None
+    }
+    /// 列出目录中的所有目录项
+    fn dirents(&self) -> Vec<DirEntry> {
+        Vec::new()
+    }
+    /// 在目录中创建名为name的文件
+    fn create(&self, _name: &str) -> Option<Arc<dyn Inode>> {
+        None
+    }
+    /// 在目录中创建名为name的子目录
+    fn create_dir(&self, _name: &str) -> Option<Arc<dyn Inode>> {
+        None
+    }
+    /// 在目录中创建指向target的目录项name，target须位于同一文件系统
+    fn link(&self, _name: &str, _target: &dyn Inode) -> bool {
+        false
+    }
+    /// 删除目录中名为name的目录项，目录须为空
+    fn unlink(&self, _name: &str) -> bool {
+        false
+    }
+    /// 清空文件内容
+    fn clear(&self) {}
+    /// 将文件的脏数据写回块设备
+    fn sync(&self) {}
+    /// 用于在同一文件系统内将trait对象还原为具体类型
+    fn as_any(&self) -> &dyn Any;
+
+    /// 是否为目录
+    fn is_dir(&self) -> bool {
+        self.mode().contains(StatMode::DIR)
+    }
+}
+
+/// 一个文件系统实例
+pub trait SuperBlock: Send + Sync {
+    /// 文件系统类型名
+    fn fs_type(&self) -> &'static str;
+    /// 根目录
+    fn root_inode(&self) -> Arc<dyn Inode>;
+    /// 是否只读
+    fn read_only(&self) -> bool {
+        false
+    }
+    /// 将文件系统的脏数据写回块设备
+    fn sync(&self) {}
+}
+
+/// 挂载表中的一项
+pub struct Mount {
+    /// 挂载点的规范化绝对路径
+    pub path: String,
+    /// 设备编号，即挂载的次序
+    pub dev: u64,
+    pub sb: Arc<dyn SuperBlock>,
+}
+
+impl Mount {
+    /// 若绝对路径path位于该挂载点之下，返回其相对于挂载点的部分
+    fn relative<'a>(&self, path: &'a str) -> Option<&'a str> {
+        if self.path == "/" {
+            return Some(path);
+        }
+        let rest = path.strip_prefix(self.path.as_str())?;
+        if rest.is_empty() || rest.starts_with('/') {
+            Some(rest)
+        } else {
+            None
+        }
+    }
+}
+
+lazy_static! {
+    /// 挂载表
+    static ref MOUNT_TABLE: UPSafeCell<Vec<Arc<Mount>>> = unsafe { UPSafeCell::new(Vec::new()) };
+}
+
+/// 将文件系统挂载于绝对路径path，挂载点不存在时在其所在的文件系统中创建目录 <br>
+/// 第一个挂载的文件系统须挂载于根目录；挂载点不是目录或已被挂载时返回false
+pub fn mount(path: &str, sb: Arc<dyn SuperBlock>) -> bool {
+    let path = normalize_path("/", path);
+    if MOUNT_TABLE.exclusive_access().is_empty() {
+        if path != "/" {
+            return false;
+        }
+    } else {
+        if MOUNT_TABLE
+            .exclusive_access()
+            .iter()
+            .any(|mount| mount.path == path)
+        {
+            return false;
+        }
+        let mount_point = lookup(path.as_str()).or_else(|| {
+            let (parent, mount, name) = lookup_parent(path.as_str())?;
+            parent.create_dir(name).map(|inode| (inode, mount))
+        });
+        match mount_point {
+            Some((inode, _)) if inode.is_dir() => {}
+            _ => return false,
+        }
+    }
+    let mut mount_table = MOUNT_TABLE.exclusive_access();
+    let dev = mount_table.len() as u64;
+    info!("Mount {} at {} (dev {}).", sb.fs_type(), path, dev);
+    mount_table.push(Arc::new(Mount { path, dev, sb }));
+    true
+}
+
+/// 所有挂载的文件系统
+pub fn mounts() -> Vec<Arc<Mount>> {
+    MOUNT_TABLE.exclusive_access().clone()
+}
+
+/// 绝对路径path是否为挂载点
+pub fn is_mount_point(path: &str) -> bool {
+    let path = normalize_path("/", path);
+    MOUNT_TABLE
+        .exclusive_access()
+        .iter()
+        .any(|mount| mount.path == path)
+}
+
+/// 按绝对路径查找索引节点，返回(索引节点, 所在的挂载项)，"."与".."在查找前由路径规范化处理
+pub fn lookup(path: &str) -> Option<(Arc<dyn Inode>, Arc<Mount>)> {
+    let path = normalize_path("/", path);
+    // 最长匹配的挂载点，即最后挂载、覆盖在其他文件系统之上的那一个
+    let mount = MOUNT_TABLE
+        .exclusive_access()
+        .iter()
+        .filter_map(|mount| Some((mount.relative(path.as_str())?, mount)))
+        .min_by_key(|(rest, _)| rest.len())
+        .map(|(_, mount)| mount.clone())?;
+    let rest = mount.relative(path.as_str()).unwrap();
+    let mut inode = mount.sb.root_inode();
+    for name in rest.split('/').filter(|name| !name.is_empty()) {
+        if !inode.is_dir() {
+            return None;
+        }
+        inode = inode.find(name)?;
+    }
+    Some((inode, mount))
+}
+
+/// 按绝对路径查找父目录，返回(父目录, 所在的挂载项, 最后一级文件名)
+pub fn lookup_parent(path: &str) -> Option<(Arc<dyn Inode>, Arc<Mount>, &str)> {
+    let (parent_path, name) = split_path(path);
+    let (parent, mount) = lookup(parent_path)?;
+    if !parent.is_dir() || name.is_empty() || name == "." || name == ".." {
+        return None;
+    }
+    Some((parent, mount, name))
+}
+
+/// 将路径拆分为(父目录路径, 最后一级文件名)
+fn split_path(path: &str) -> (&str, &str) {
+    let path = path.trim_end_matches('/');
+    match path.rfind('/') {
+        Some(0) => ("/", &path[1..]),
+        Some(idx) => (&path[..idx], &path[idx + 1..]),
+        None => (".", path),
+    }
+}
+
+/// 将相对于cwd的路径规范化为不含"."与".."的绝对路径
+pub fn normalize_path(cwd: &str, path: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    let full_path = if path.starts_with('/') {
+        String::from(path)
+    } else {
+        format!("{}/{}", cwd, path)
+    };
+    for name in full_path.split('/') {
+        match name {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            _ => components.push(name),
+        }
+    }
+    let mut normalized = String::new();
+    for name in components {
+        normalized.push('/');
+        normalized.push_str(name);
+    }
+    if normalized.is_empty() {
+        normalized.push('/');
+    }
+    normalized
+}
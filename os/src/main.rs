@@ -1,9 +1,12 @@
 //! os/src/main.rs
 //! The main source code
 #![feature(panic_info_message)] //Enable feature `message()` in panic_info
+#![feature(alloc_error_handler)] //Enable feature `alloc_error_handler` for heap allocator
 #![no_std] //Delete std-lib, use rust-core-lib
 #![no_main] //Remove main() func
 
+extern crate alloc;
+
 use core::arch::global_asm;
 
 use log::*;
@@ -11,17 +14,23 @@ use log::*;
 use crate::console::print;
 use crate::sbi_call::shutdown;
 
-mod batch;
+mod config;
 mod console;
+mod drivers;
+mod fs;
 mod kernel_log;
 mod lang_items;
+mod mm;
 mod sbi_call;
 mod sync;
 mod syscall;
+mod task;
+mod timer;
 mod trap;
 
 global_asm!(include_str!("entry.asm"));
-global_asm!(include_str!("link_app.S")); //将App链入内核
+#[cfg(any(feature = "board_k210", feature = "ramdisk"))]
+global_asm!(include_str!("link_fs.S")); //将磁盘镜像链入内核
 
 #[no_mangle] //disable mangle of func name 'rust_main'
 pub fn rust_main() -> ! {
@@ -40,12 +49,19 @@ pub fn rust_main() -> ! {
     // 用于调试的信息
     trace!("[Test] TRACE log level"); // 用于调试的详细信息，会追踪到每个步骤
 
-    // 调用AppManager
+    info!("Init memory management.");
+    mm::init();
     info!("Init trap handler.");
     trap::init();
-    info!("Init batch system.");
-    batch::init();
-    batch::run_next_app();
+    info!("Init block device.");
+    drivers::block::init();
+    fs::init();
+    fs::list_apps();
+    task::add_initproc();
+    trap::enable_timer_interrupt();
+    timer::set_next_trigger();
+    info!("Run tasks.");
+    task::run_tasks();
 
     //shutdown();
 }
@@ -0,0 +1,137 @@
+//! os/src/mm/asid.rs <br>
+//! 地址空间标识符（ASID）分配器 <br>
+//! 为每个应用地址空间分配独立的ASID，切换地址空间时只需写satp而无需刷新整个TLB。
+//! 分配采用世代（generation）机制：ASID耗尽时进入新世代并刷新一次TLB，
+//! 之后每个地址空间在下一次被激活时重新分配ASID。
+
+use core::arch::asm;
+
+use lazy_static::lazy_static;
+use log::*;
+use riscv::register::satp;
+
+use crate::sync::UPSafeCell;
+
+/// satp中ASID字段的偏移
+const SATP_ASID_SHIFT: usize = 44;
+/// satp中ASID字段的掩码（SV39下最多16位）
+const SATP_ASID_MASK: usize = 0xffff;
+/// 内核地址空间保留的ASID
+pub const KERNEL_ASID: usize = 0;
+
+/// 地址空间当前持有的ASID及其所属世代
+#[derive(Copy, Clone)]
+pub struct AsidHandle {
+    asid: usize,
+    generation: usize,
+}
+
+impl AsidHandle {
+    /// 尚未分配ASID的句柄，世代0永远不是有效世代
+    pub const fn invalid() -> Self {
+        Self {
+            asid: KERNEL_ASID,
+            generation: 0,
+        }
+    }
+
+    pub fn asid(&self) -> usize {
+        self.asid
+    }
+}
+
+struct AsidAllocator {
+    /// 硬件支持的最大ASID，为0时表示不支持ASID
+    max_asid: usize,
+    /// 当前世代，从1开始
+    generation: usize,
+    /// 当前世代中下一个可分配的ASID
+    next_asid: usize,
+}
+
+impl AsidAllocator {
+    fn new() -> Self {
+        Self {
+            max_asid: 0,
+            generation: 1,
+            next_asid: KERNEL_ASID + 1,
+        }
+    }
+
+    /// 若句柄不属于当前世代则为其重新分配ASID；ASID耗尽时进入新世代并刷新全部TLB
+    fn refresh(&mut self, handle: &mut AsidHandle) {
+        if handle.generation == self.generation {
+            return;
+        }
+        if self.next_asid > self.max_asid {
+            trace!("ASID exhausted, start generation {}", self.generation + 1);
+            self.generation += 1;
+            self.next_asid = KERNEL_ASID + 1;
+            unsafe {
+                asm!("sfence.vma");
+            }
+        }
+        handle.asid = self.next_asid;
+        handle.generation = self.generation;
+        self.next_asid += 1;
+    }
+}
+
+lazy_static! {
+    static ref ASID_ALLOCATOR: UPSafeCell<AsidAllocator> =
+        unsafe { UPSafeCell::new(AsidAllocator::new()) };
+}
+
+/// 探测硬件支持的ASID位宽：向satp的ASID字段写入全1，读回的值即为可用位 <br>
+/// 应在启用内核地址空间之后调用
+pub fn init_asid_allocator() {
+    let old = satp::read().bits();
+    let max_asid = if cfg!(feature = "no_asid") {
+        0
+    } else {
+        unsafe {
+            satp::write(old | (SATP_ASID_MASK << SATP_ASID_SHIFT));
+            let max_asid = (satp::read().bits() >> SATP_ASID_SHIFT) & SATP_ASID_MASK;
+            satp::write(old);
+            max_asid
+        }
+    };
+    ASID_ALLOCATOR.exclusive_access().max_asid = max_asid;
+    if max_asid == 0 {
+        info!("ASID is unavailable, TLB will be flushed on every address space switch");
+    } else {
+        info!("ASID is available, max ASID = {:#x}", max_asid);
+    }
+}
+
+/// 是否启用了ASID
+pub fn asid_enabled() -> bool {
+    ASID_ALLOCATOR.exclusive_access().max_asid != 0
+}
+
+/// 确保句柄持有当前世代的有效ASID
+pub fn refresh_asid(handle: &mut AsidHandle) {
+    let mut allocator = ASID_ALLOCATOR.exclusive_access();
+    if allocator.max_asid != 0 {
+        allocator.refresh(handle);
+    }
+}
+
+/// 将ASID写入satp值的对应字段
+pub fn satp_with_asid(satp: usize, asid: usize) -> usize {
+    (satp & !(SATP_ASID_MASK << SATP_ASID_SHIFT)) | ((asid & SATP_ASID_MASK) << SATP_ASID_SHIFT)
+}
+
+/// 刷新指定ASID的TLB项；vpn为None时刷新该ASID的全部TLB项 <br>
+/// 不支持ASID时只能刷新全部TLB
+pub fn flush_tlb(asid: usize, va: Option<usize>) {
+    let asid = if asid_enabled() { asid } else { KERNEL_ASID };
+    unsafe {
+        match (asid, va) {
+            (KERNEL_ASID, None) => asm!("sfence.vma"),
+            (KERNEL_ASID, Some(va)) => asm!("sfence.vma {}, zero", in(reg) va),
+            (asid, None) => asm!("sfence.vma zero, {}", in(reg) asid),
+            (asid, Some(va)) => asm!("sfence.vma {}, {}", in(reg) va, in(reg) asid),
+        }
+    }
+}
@@ -0,0 +1,165 @@
+//! os/src/mm/frame_allocator.rs <br>
+//! 物理页帧分配器
+
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+
+use lazy_static::lazy_static;
+use log::*;
+
+use crate::config::MEMORY_END;
+use crate::sync::UPSafeCell;
+
+use super::{PhysAddr, PhysPageNum};
+
+/// 物理页帧的RAII封装，析构时自动回收对应的物理页帧
+pub struct FrameTracker {
+    pub ppn: PhysPageNum,
+}
+
+impl FrameTracker {
+    /// 创建时将物理页帧清零
+    pub fn new(ppn: PhysPageNum) -> Self {
+        let bytes_array = ppn.get_bytes_array();
+        for i in bytes_array {
+            *i = 0;
+        }
+        Self { ppn }
+    }
+}
+
+impl Debug for FrameTracker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_fmt(format_args!("FrameTracker:PPN={:#x}", self.ppn.0))
+    }
+}
+
+impl Drop for FrameTracker {
+    fn drop(&mut self) {
+        frame_dealloc(self.ppn);
+    }
+}
+
+trait FrameAllocator {
+    fn new() -> Self;
+    fn alloc(&mut self) -> Option<PhysPageNum>;
+    fn alloc_contiguous(&mut self, count: usize) -> Option<PhysPageNum>;
+    fn dealloc(&mut self, ppn: PhysPageNum);
+    /// 返回(可分配的页帧总数, 空闲页帧数)
+    fn stats(&self) -> (usize, usize);
+}
+
+/// 栈式物理页帧分配器
+pub struct StackFrameAllocator {
+    /// 可分配区间的起始物理页号
+    start: usize,
+    /// 尚未分配过的物理页号区间[current, end)
+    current: usize,
+    end: usize,
+    /// 已回收的物理页号
+    recycled: Vec<usize>,
+}
+
+impl StackFrameAllocator {
+    pub fn init(&mut self, l: PhysPageNum, r: PhysPageNum) {
+        self.start = l.0;
+        self.current = l.0;
+        self.end = r.0;
+        debug!("Frame allocator: [{:#x}, {:#x})", l.0, r.0);
+    }
+}
+
+impl FrameAllocator for StackFrameAllocator {
+    fn new() -> Self {
+        Self {
+            start: 0,
+            current: 0,
+            end: 0,
+            recycled: Vec::new(),
+        }
+    }
+
+    fn alloc(&mut self) -> Option<PhysPageNum> {
+        if let Some(ppn) = self.recycled.pop() {
+            Some(ppn.into())
+        } else if self.current == self.end {
+            None
+        } else {
+            self.current += 1;
+            Some((self.current - 1).into())
+        }
+    }
+
+    /// 分配count个物理地址连续的页帧，返回首个页帧的页号，只从尚未分配过的区间中分配
+    fn alloc_contiguous(&mut self, count: usize) -> Option<PhysPageNum> {
+        if self.current + count > self.end {
+            None
+        } else {
+            self.current += count;
+            Some((self.current - count).into())
+        }
+    }
+
+    fn dealloc(&mut self, ppn: PhysPageNum) {
+        let ppn = ppn.0;
+        // 检查该页帧是否已分配且未被回收
+        if ppn >= self.current || self.recycled.iter().any(|&v| v == ppn) {
+            panic!("Frame ppn={:#x} has not been allocated!", ppn);
+        }
+        self.recycled.push(ppn);
+    }
+
+    fn stats(&self) -> (usize, usize) {
+        (
+            self.end - self.start,
+            self.end - self.current + self.recycled.len(),
+        )
+    }
+}
+
+type FrameAllocatorImpl = StackFrameAllocator;
+
+lazy_static! {
+    /// 全局物理页帧分配器
+    pub static ref FRAME_ALLOCATOR: UPSafeCell<FrameAllocatorImpl> =
+        unsafe { UPSafeCell::new(FrameAllocatorImpl::new()) };
+}
+
+/// 初始化物理页帧分配器，可分配区间为内核结束处至物理内存结束处
+pub fn init_frame_allocator() {
+    extern "C" {
+        fn ekernel();
+    }
+    FRAME_ALLOCATOR.exclusive_access().init(
+        PhysAddr::from(ekernel as usize).ceil(),
+        PhysAddr::from(MEMORY_END).floor(),
+    );
+}
+
+/// 分配一个物理页帧
+pub fn frame_alloc() -> Option<FrameTracker> {
+    FRAME_ALLOCATOR
+        .exclusive_access()
+        .alloc()
+        .map(FrameTracker::new)
+}
+
+/// 分配count个物理地址连续的页帧（如用于设备DMA）
+pub fn frame_alloc_contiguous(count: usize) -> Option<Vec<FrameTracker>> {
+    let start = FRAME_ALLOCATOR.exclusive_access().alloc_contiguous(count)?;
+    Some(
+        (start.0..start.0 + count)
+            .map(|ppn| FrameTracker::new(ppn.into()))
+            .collect(),
+    )
+}
+
+/// 返回(可分配的物理页帧总数, 空闲页帧数)
+pub fn frame_stats() -> (usize, usize) {
+    FRAME_ALLOCATOR.exclusive_access().stats()
+}
+
+/// 回收一个物理页帧
+fn frame_dealloc(ppn: PhysPageNum) {
+    FRAME_ALLOCATOR.exclusive_access().dealloc(ppn);
+}
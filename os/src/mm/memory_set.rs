@@ -0,0 +1,824 @@
+//! os/src/mm/memory_set.rs <br>
+//! 地址空间：由页表与若干逻辑段组成
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::arch::asm;
+
+use bitflags::*;
+use lazy_static::lazy_static;
+use log::*;
+use riscv::register::satp;
+
+use crate::config::{MEMORY_END, MMAP_BASE, MMIO, PAGE_SIZE, TRAMPOLINE, USER_SPACE_END};
+use crate::fs::Inode;
+use crate::sync::UPSafeCell;
+
+use super::asid::{flush_tlb, refresh_asid, satp_with_asid, AsidHandle};
+use super::page_cache::{get_cached_page, CachedPage};
+use super::page_table::pages_at_level;
+use super::{frame_alloc, FrameTracker, PageTable, PageTableEntry, PTEFlags};
+use super::{PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum, VPNRange};
+
+extern "C" {
+    fn stext();
+    fn etext();
+    fn srodata();
+    fn erodata();
+    fn sdata();
+    fn edata();
+    fn sbss_with_stack();
+    fn end_bss();
+    fn ekernel();
+    fn strampoline();
+    fn boot_stack_guard();
+    fn boot_stack_lower_bound();
+}
+
+lazy_static! {
+    /// 内核地址空间
+    pub static ref KERNEL_SPACE: Arc<UPSafeCell<MemorySet>> =
+        Arc::new(unsafe { UPSafeCell::new(MemorySet::new_kernel()) });
+}
+
+/// 地址空间
+pub struct MemorySet {
+    page_table: PageTable,
+    areas: Vec<MapArea>,
+    /// 该地址空间的ASID，内核地址空间始终使用KERNEL_ASID
+    asid: AsidHandle,
+}
+
+impl MemorySet {
+    pub fn new_bare() -> Self {
+        Self {
+            page_table: PageTable::new(),
+            areas: Vec::new(),
+            asid: AsidHandle::invalid(),
+        }
+    }
+
+    /// 构造写入satp的值（含ASID）
+    pub fn token(&self) -> usize {
+        satp_with_asid(self.page_table.token(), self.asid.asid())
+    }
+
+    /// 即将切换至该应用地址空间时调用：确保持有当前世代的ASID，并返回写入satp的值
+    pub fn user_token(&mut self) -> usize {
+        refresh_asid(&mut self.asid);
+        self.token()
+    }
+
+    /// 插入一个以物理页帧为后备的逻辑段，调用者需保证不与已有逻辑段重叠
+    pub fn insert_framed_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+    ) {
+        self.push(
+            MapArea::new(start_va, end_va, MapType::Framed, permission),
+            None,
+        );
+        // 该地址空间可能正在使用，需刷新其TLB项
+        flush_tlb(self.asid.asid(), None);
+    }
+
+    /// 移除以start_vpn起始的逻辑段并解除其映射
+    pub fn remove_area_with_start_vpn(&mut self, start_vpn: VirtPageNum) {
+        if let Some((idx, area)) = self
+            .areas
+            .iter_mut()
+            .enumerate()
+            .find(|(_, area)| area.vpn_range.get_start() == start_vpn)
+        {
+            area.unmap(&mut self.page_table);
+            self.areas.remove(idx);
+        }
+        flush_tlb(self.asid.asid(), None);
+    }
+
+    /// 在hint处或MMAP_BASE之上寻找长为len字节、不与已有逻辑段重叠的区间，返回其起始地址 <br>
+    /// hint低于MMAP_BASE（该区域留给各线程的用户栈）或不可用时从MMAP_BASE起按首次适配选取
+    pub fn find_free_area(&self, hint: usize, len: usize) -> Option<usize> {
+        let is_free = |start: usize| {
+            start % PAGE_SIZE == 0
+                && start
+                    .checked_add(len)
+                    .is_some_and(|end| end <= USER_SPACE_END)
+                && self.areas.iter().all(|area| {
+                    let (area_start, area_end) = area.va_range();
+                    start + len <= area_start || area_end <= start
+                })
+        };
+        if hint >= MMAP_BASE && is_free(hint) {
+            return Some(hint);
+        }
+        let mut candidates: Vec<usize> = self
+            .areas
+            .iter()
+            .map(|area| area.va_range().1)
+            .filter(|&end| end > MMAP_BASE)
+            .collect();
+        candidates.push(MMAP_BASE);
+        candidates.sort_unstable();
+        candidates.into_iter().find(|&start| is_free(start))
+    }
+
+    /// 插入mmap逻辑段，file为None时为匿名映射；各页在首次访问时才经缺页异常映射
+    pub fn insert_mmap_area(
+        &mut self,
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        permission: MapPermission,
+        file: Option<MmapFile>,
+    ) {
+        let mut map_area = MapArea::new(start_va, end_va, MapType::Lazy, permission);
+        map_area.file = file;
+        self.areas.push(map_area);
+    }
+
+    /// 解除[start_va, end_va)中的mmap映射，部分位于区间中的逻辑段在区间边界处切开 <br>
+    /// 区间与非mmap逻辑段重叠时返回false
+    pub fn munmap(&mut self, start_va: VirtAddr, end_va: VirtAddr) -> bool {
+        let start_vpn = start_va.floor();
+        let end_vpn = end_va.ceil();
+        let overlaps = |area: &MapArea| {
+            area.vpn_range.get_start() < end_vpn && start_vpn < area.vpn_range.get_end()
+        };
+        if self
+            .areas
+            .iter()
+            .any(|area| area.map_type != MapType::Lazy && overlaps(area))
+        {
+            return false;
+        }
+        for mut area in core::mem::take(&mut self.areas) {
+            if area.map_type != MapType::Lazy || !overlaps(&area) {
+                self.areas.push(area);
+                continue;
+            }
+            if area.vpn_range.get_start() < start_vpn {
+                let rest = area.split_off(start_vpn);
+                self.areas.push(area);
+                area = rest;
+            }
+            if end_vpn < area.vpn_range.get_end() {
+                let rest = area.split_off(end_vpn);
+                self.areas.push(rest);
+            }
+            // 其他地址空间可能仍映射着这些页，修改在此时就应写回文件
+            for page in area.file_pages.values() {
+                page.writeback();
+            }
+            area.unmap(&mut self.page_table);
+        }
+        flush_tlb(self.asid.asid(), None);
+        true
+    }
+
+    /// 处理应用对va的access（R、W或X之一）访问引发的缺页异常，返回是否已处理 <br>
+    /// 页已映射且权限足够时直接返回true；否则只有mmap逻辑段中的页可以按需映射
+    pub fn handle_page_fault(&mut self, va: VirtAddr, access: MapPermission) -> bool {
+        let vpn = va.floor();
+        let Some(area) = self
+            .areas
+            .iter_mut()
+            .find(|area| area.vpn_range.get_start() <= vpn && vpn < area.vpn_range.get_end())
+        else {
+            return false;
+        };
+        if !area.map_perm.contains(access | MapPermission::U) {
+            return false;
+        }
+        if let Some(pte) = self.page_table.translate(vpn) {
+            let flags = PTEFlags::from_bits(access.bits).unwrap();
+            if pte.flags().contains(flags) {
+                return true;
+            }
+        }
+        if !area.handle_fault(&mut self.page_table, vpn, access.contains(MapPermission::W)) {
+            return false;
+        }
+        flush_tlb(self.asid.asid(), Some(va.0));
+        true
+    }
+
+    /// 确保应用地址va所在的页已映射且允许U模式以access权限访问，返回其物理页号；地址非法时返回None
+    fn user_page(&mut self, va: usize, access: MapPermission) -> Option<PhysPageNum> {
+        if va >= USER_SPACE_END || !self.handle_page_fault(va.into(), access) {
+            return None;
+        }
+        let flags = PTEFlags::from_bits((access | MapPermission::U).bits).unwrap();
+        self.page_table
+            .translate(VirtAddr::from(va).floor())
+            .filter(|pte| pte.flags().contains(flags))
+            .map(|pte| pte.ppn())
+    }
+
+    /// 将应用地址空间中的缓冲区[ptr, ptr + len)转换为内核可直接访问的若干切片（缓冲区可能跨越多个物理页），
+    /// 各页先按access权限经缺页处理映射；缓冲区中有非法地址时返回None
+    pub fn translated_byte_buffer(
+        &mut self,
+        ptr: *const u8,
+        len: usize,
+        access: MapPermission,
+    ) -> Option<Vec<&'static mut [u8]>> {
+        let mut start = ptr as usize;
+        let end = start
+            .checked_add(len)
+            .filter(|&end| end <= USER_SPACE_END)?;
+        let mut v = Vec::new();
+        while start < end {
+            let ppn = self.user_page(start, access)?;
+            let offset = start % PAGE_SIZE;
+            let page_end = (start - offset + PAGE_SIZE).min(end);
+            v.push(&mut ppn.get_bytes_array()[offset..offset + (page_end - start)]);
+            start = page_end;
+        }
+        Some(v)
+    }
+
+    /// 从应用地址空间中读取以'\0'结尾的字符串，字符串中有不可读的地址时返回None
+    pub fn translated_str(&mut self, ptr: *const u8) -> Option<String> {
+        let mut string = String::new();
+        let mut va = ptr as usize;
+        let mut page = self.user_page(va, MapPermission::R)?;
+        loop {
+            let ch = page.get_bytes_array()[va % PAGE_SIZE];
+            if ch == 0 {
+                break;
+            }
+            string.push(ch as char);
+            va += 1;
+            if va % PAGE_SIZE == 0 {
+                page = self.user_page(va, MapPermission::R)?;
+            }
+        }
+        Some(string)
+    }
+
+    /// 获取应用地址空间中对象的物理地址，要求对象按其类型对齐（因而不跨越页边界）且具备access权限
+    fn translated_object<T>(&mut self, ptr: usize, access: MapPermission) -> Option<PhysAddr> {
+        if ptr % core::mem::align_of::<T>() != 0
+            || ptr % PAGE_SIZE + core::mem::size_of::<T>() > PAGE_SIZE
+        {
+            return None;
+        }
+        let ppn = self.user_page(ptr, access)?;
+        Some(PhysAddr::from(PhysAddr::from(ppn).0 + ptr % PAGE_SIZE))
+    }
+
+    /// 获取应用地址空间中某个对象的引用，地址非法、未对齐或不可读时返回None
+    pub fn translated_ref<T>(&mut self, ptr: *const T) -> Option<&'static T> {
+        self.translated_object::<T>(ptr as usize, MapPermission::R)
+            .map(|pa| pa.get_ref())
+    }
+
+    /// 获取应用地址空间中某个对象的可变引用，地址非法、未对齐或不可写时返回None <br>
+    /// 写时复制的页在此之前即被复制，内核的写入不会影响共享该页的其他地址空间
+    pub fn translated_refmut<T>(&mut self, ptr: *mut T) -> Option<&'static mut T> {
+        self.translated_object::<T>(ptr as usize, MapPermission::W)
+            .map(|pa| pa.get_mut())
+    }
+
+    /// 映射逻辑段并将其加入地址空间，可同时向其中写入初始数据
+    fn push(&mut self, mut map_area: MapArea, data: Option<&[u8]>) {
+        map_area.map(&mut self.page_table);
+        if let Some(data) = data {
+            map_area.copy_data(&mut self.page_table, data);
+        }
+        self.areas.push(map_area);
+    }
+
+    /// 映射跳板页，跳板页不属于任何逻辑段
+    fn map_trampoline(&mut self) {
+        self.page_table.map(
+            VirtAddr::from(TRAMPOLINE).into(),
+            PhysAddr::from(strampoline as usize).into(),
+            PTEFlags::R | PTEFlags::X,
+        );
+    }
+
+    /// 构造内核地址空间
+    pub fn new_kernel() -> Self {
+        let mut memory_set = Self::new_bare();
+        memory_set.map_trampoline();
+        // 按最小权限恒等映射内核各段：代码段R-X，只读数据段R--，数据段与bss段RW-
+        debug!(".text [{:#x}, {:#x})", stext as usize, etext as usize);
+        debug!(".rodata [{:#x}, {:#x})", srodata as usize, erodata as usize);
+        debug!(".data [{:#x}, {:#x})", sdata as usize, edata as usize);
+        debug!(
+            ".bss [{:#x}, {:#x}), boot stack guard page at {:#x}",
+            sbss_with_stack as usize, end_bss as usize, boot_stack_guard as usize
+        );
+        memory_set.push(
+            MapArea::new(
+                (stext as usize).into(),
+                (etext as usize).into(),
+                MapType::Identical,
+                MapPermission::R | MapPermission::X,
+            ),
+            None,
+        );
+        memory_set.push(
+            MapArea::new(
+                (srodata as usize).into(),
+                (erodata as usize).into(),
+                MapType::Identical,
+                MapPermission::R,
+            ),
+            None,
+        );
+        memory_set.push(
+            MapArea::new(
+                (sdata as usize).into(),
+                (edata as usize).into(),
+                MapType::Identical,
+                MapPermission::R | MapPermission::W,
+            ),
+            None,
+        );
+        // bss段中启动栈下方的保护页不映射
+        memory_set.push(
+            MapArea::new(
+                (sbss_with_stack as usize).into(),
+                (boot_stack_guard as usize).into(),
+                MapType::Identical,
+                MapPermission::R | MapPermission::W,
+            ),
+            None,
+        );
+        memory_set.push(
+            MapArea::new(
+                (boot_stack_lower_bound as usize).into(),
+                (end_bss as usize).into(),
+                MapType::Identical,
+                MapPermission::R | MapPermission::W,
+            ),
+            None,
+        );
+        // 恒等映射剩余的全部物理内存，尽可能使用大页
+        info!(
+            "Mapping physical memory [{:#x}, {:#x})",
+            ekernel as usize, MEMORY_END
+        );
+        memory_set.push(
+            MapArea::new(
+                (ekernel as usize).into(),
+                MEMORY_END.into(),
+                MapType::Linear,
+                MapPermission::R | MapPermission::W,
+            ),
+            None,
+        );
+        // 恒等映射设备MMIO区间
+        for &(start, len) in MMIO {
+            debug!("Mapping MMIO [{:#x}, {:#x})", start, start + len);
+            memory_set.push(
+                MapArea::new(
+                    start.into(),
+                    (start + len).into(),
+                    MapType::Identical,
+                    MapPermission::R | MapPermission::W,
+                ),
+                None,
+            );
+        }
+        memory_set
+    }
+
+    /// 解析ELF构造应用地址空间，返回(地址空间, 用户栈区域的起始地址, 入口地址)
+    pub fn from_elf(elf_data: &[u8]) -> (Self, usize, usize) {
+        let mut memory_set = Self::new_bare();
+        memory_set.map_trampoline();
+        // 按程序头映射各个段
+        let elf = xmas_elf::ElfFile::new(elf_data).unwrap();
+        let elf_header = elf.header;
+        let magic = elf_header.pt1.magic;
+        assert_eq!(magic, [0x7f, 0x45, 0x4c, 0x46], "invalid elf!");
+        let ph_count = elf_header.pt2.ph_count();
+        let mut max_end_vpn = VirtPageNum(0);
+        for i in 0..ph_count {
+            let ph = elf.program_header(i).unwrap();
+            if ph.get_type().unwrap() == xmas_elf::program::Type::Load {
+                let start_va: VirtAddr = (ph.virtual_addr() as usize).into();
+                let end_va: VirtAddr = ((ph.virtual_addr() + ph.mem_size()) as usize).into();
+                let mut map_perm = MapPermission::U;
+                let ph_flags = ph.flags();
+                if ph_flags.is_read() {
+                    map_perm |= MapPermission::R;
+                }
+                if ph_flags.is_write() {
+                    map_perm |= MapPermission::W;
+                }
+                if ph_flags.is_execute() {
+                    map_perm |= MapPermission::X;
+                }
+                let map_area = MapArea::new(start_va, end_va, MapType::Framed, map_perm);
+                max_end_vpn = map_area.vpn_range.get_end();
+                memory_set.push(
+                    map_area,
+                    Some(&elf.input[ph.offset() as usize..(ph.offset() + ph.file_size()) as usize]),
+                );
+            }
+        }
+        // 各线程的用户栈位于程序最高段之上，二者之间隔一个不映射的保护页
+        // 用户栈与TrapContext页在创建线程时才映射
+        let max_end_va: VirtAddr = max_end_vpn.into();
+        let ustack_base = usize::from(max_end_va) + PAGE_SIZE;
+        (
+            memory_set,
+            ustack_base,
+            elf.header.pt2.entry_point() as usize,
+        )
+    }
+
+    /// 复制一个应用地址空间（用于fork），新地址空间持有各页的副本并在首次激活时分配ASID
+    pub fn from_existed_user(user_space: &MemorySet) -> MemorySet {
+        let mut memory_set = Self::new_bare();
+        memory_set.map_trampoline();
+        for area in user_space.areas.iter() {
+            let mut new_area = MapArea::from_another(area);
+            if area.map_type == MapType::Lazy {
+                new_area.copy_lazy_pages(area, &user_space.page_table, &mut memory_set.page_table);
+                memory_set.areas.push(new_area);
+                continue;
+            }
+            memory_set.push(new_area, None);
+            // 逐页复制数据
+            for vpn in area.vpn_range {
+                let src_ppn = user_space.translate(vpn).unwrap().ppn();
+                let dst_ppn = memory_set.translate(vpn).unwrap().ppn();
+                dst_ppn
+                    .get_bytes_array()
+                    .copy_from_slice(src_ppn.get_bytes_array());
+            }
+        }
+        memory_set
+    }
+
+    /// 回收所有逻辑段占用的物理页帧，页表本身随地址空间一同释放
+    pub fn recycle_data_pages(&mut self) {
+        self.areas.clear();
+    }
+
+    /// 切换至该地址空间并刷新全部TLB，仅在启用内核地址空间时使用
+    pub fn activate(&self) {
+        let satp = self.token();
+        unsafe {
+            satp::write(satp);
+            asm!("sfence.vma");
+        }
+    }
+
+    pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
+        self.page_table.translate(vpn)
+    }
+
+    /// 各逻辑段的(起始地址, 结束地址, 访问权限, 占用的物理页帧数)
+    pub fn areas_info(&self) -> Vec<(usize, usize, MapPermission, usize)> {
+        self.areas
+            .iter()
+            .map(|area| {
+                (
+                    VirtAddr::from(area.vpn_range.get_start()).0,
+                    VirtAddr::from(area.vpn_range.get_end()).0,
+                    area.map_perm,
+                    area.data_frames.len() + area.file_pages.len(),
+                )
+            })
+            .collect()
+    }
+}
+
+/// mmap映射到逻辑段的文件区间
+#[derive(Clone)]
+pub struct MmapFile {
+    /// 文件所在设备的编号，与索引节点编号一同标识文件页缓存
+    pub dev: u64,
+    pub inode: Arc<dyn Inode>,
+    /// 逻辑段起始处对应的文件偏移，按页对齐
+    pub offset: usize,
+    /// MAP_SHARED：各映射共享文件页，修改写回文件；否则为MAP_PRIVATE，写入时复制
+    pub shared: bool,
+}
+
+/// 逻辑段：一段连续且映射方式、权限相同的虚拟地址区间
+pub struct MapArea {
+    vpn_range: VPNRange,
+    data_frames: BTreeMap<VirtPageNum, FrameTracker>,
+    /// 映射至文件页缓存的页，仅用于映射文件的Lazy逻辑段
+    file_pages: BTreeMap<VirtPageNum, Arc<CachedPage>>,
+    map_type: MapType,
+    map_perm: MapPermission,
+    /// 映射的文件，仅用于Lazy逻辑段，None表示匿名映射
+    file: Option<MmapFile>,
+}
+
+impl MapArea {
+    pub fn new(
+        start_va: VirtAddr,
+        end_va: VirtAddr,
+        map_type: MapType,
+        map_perm: MapPermission,
+    ) -> Self {
+        let start_vpn: VirtPageNum = start_va.floor();
+        let end_vpn: VirtPageNum = end_va.ceil();
+        Self {
+            vpn_range: VPNRange::new(start_vpn, end_vpn),
+            data_frames: BTreeMap::new(),
+            file_pages: BTreeMap::new(),
+            map_type,
+            map_perm,
+            file: None,
+        }
+    }
+
+    /// 复制另一个逻辑段的范围与属性，不复制映射与数据
+    pub fn from_another(another: &MapArea) -> Self {
+        Self {
+            vpn_range: VPNRange::new(another.vpn_range.get_start(), another.vpn_range.get_end()),
+            data_frames: BTreeMap::new(),
+            file_pages: BTreeMap::new(),
+            map_type: another.map_type,
+            map_perm: another.map_perm,
+            file: another.file.clone(),
+        }
+    }
+
+    /// 逻辑段的虚拟地址区间[起始地址, 结束地址)
+    fn va_range(&self) -> (usize, usize) {
+        (
+            VirtAddr::from(self.vpn_range.get_start()).0,
+            VirtAddr::from(self.vpn_range.get_end()).0,
+        )
+    }
+
+    /// 在vpn处将逻辑段一分为二，自身保留[起始页, vpn)，返回[vpn, 结束页)
+    fn split_off(&mut self, vpn: VirtPageNum) -> Self {
+        let start = self.vpn_range.get_start();
+        let end = self.vpn_range.get_end();
+        self.vpn_range = VPNRange::new(start, vpn);
+        let file = self.file.clone().map(|mut file| {
+            file.offset += (vpn.0 - start.0) * PAGE_SIZE;
+            file
+        });
+        Self {
+            vpn_range: VPNRange::new(vpn, end),
+            data_frames: self.data_frames.split_off(&vpn),
+            file_pages: self.file_pages.split_off(&vpn),
+            map_type: self.map_type,
+            map_perm: self.map_perm,
+            file,
+        }
+    }
+
+    /// 复制另一个Lazy逻辑段中已映射的页（用于fork）：文件缓存页与之共享，其余页逐页复制
+    fn copy_lazy_pages(&mut self, another: &MapArea, src: &PageTable, dst: &mut PageTable) {
+        for (vpn, page) in another.file_pages.iter() {
+            // 保持原页表项的权限，私有映射中的只读文件页在写入时各自复制
+            dst.map(*vpn, page.ppn(), src.translate(*vpn).unwrap().flags());
+            self.file_pages.insert(*vpn, page.clone());
+        }
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        for (vpn, frame) in another.data_frames.iter() {
+            let new_frame = frame_alloc().unwrap();
+            new_frame
+                .ppn
+                .get_bytes_array()
+                .copy_from_slice(frame.ppn.get_bytes_array());
+            dst.map(*vpn, new_frame.ppn, pte_flags);
+            self.data_frames.insert(*vpn, new_frame);
+        }
+    }
+
+    /// 处理Lazy逻辑段中vpn处的缺页，write表示是否为写访问，物理内存不足时返回false <br>
+    /// 匿名映射分配清零的页帧；文件映射读取时映射文件页缓存中的页，不含写权限，
+    /// 写入时共享映射标记该页为脏并加上写权限，私有映射则复制出独立的页帧
+    fn handle_fault(&mut self, page_table: &mut PageTable, vpn: VirtPageNum, write: bool) -> bool {
+        if self.map_type != MapType::Lazy {
+            return false;
+        }
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        let Some(file) = self.file.as_ref() else {
+            let Some(frame) = frame_alloc() else {
+                return false;
+            };
+            page_table.map(vpn, frame.ppn, pte_flags);
+            self.data_frames.insert(vpn, frame);
+            return true;
+        };
+        let page = match self.file_pages.get(&vpn) {
+            // 页已以只读方式映射，此时必为写访问
+            Some(page) => {
+                page_table.unmap(vpn);
+                page.clone()
+            }
+            None => {
+                let page_index = file.offset / PAGE_SIZE + (vpn.0 - self.vpn_range.get_start().0);
+                let Some(page) = get_cached_page(file.dev, &file.inode, page_index) else {
+                    return false;
+                };
+                page
+            }
+        };
+        if !write {
+            page_table.map(vpn, page.ppn(), pte_flags - PTEFlags::W);
+            self.file_pages.insert(vpn, page);
+        } else if file.shared {
+            page.mark_dirty();
+            page_table.map(vpn, page.ppn(), pte_flags);
+            self.file_pages.insert(vpn, page);
+        } else {
+            self.file_pages.remove(&vpn);
+            let Some(frame) = frame_alloc() else {
+                return false;
+            };
+            frame
+                .ppn
+                .get_bytes_array()
+                .copy_from_slice(page.ppn().get_bytes_array());
+            page_table.map(vpn, frame.ppn, pte_flags);
+            self.data_frames.insert(vpn, frame);
+        }
+        true
+    }
+
+    pub fn map_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        let ppn: PhysPageNum;
+        match self.map_type {
+            MapType::Identical | MapType::Linear => {
+                ppn = PhysPageNum(vpn.0);
+            }
+            MapType::Framed => {
+                let frame = frame_alloc().unwrap();
+                ppn = frame.ppn;
+                self.data_frames.insert(vpn, frame);
+            }
+            MapType::Lazy => panic!("vpn {:?} in a lazy area is mapped on page fault", vpn),
+        }
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        page_table.map(vpn, ppn, pte_flags);
+    }
+
+    pub fn unmap_one(&mut self, page_table: &mut PageTable, vpn: VirtPageNum) {
+        match self.map_type {
+            MapType::Framed => {
+                self.data_frames.remove(&vpn);
+            }
+            MapType::Lazy => {
+                // 尚未访问过的页没有映射
+                if self.data_frames.remove(&vpn).is_none() && self.file_pages.remove(&vpn).is_none()
+                {
+                    return;
+                }
+            }
+            MapType::Identical | MapType::Linear => {}
+        }
+        page_table.unmap(vpn);
+    }
+
+    pub fn map(&mut self, page_table: &mut PageTable) {
+        if self.map_type == MapType::Lazy {
+            return;
+        }
+        if self.map_type == MapType::Linear {
+            self.map_linear(page_table);
+            return;
+        }
+        for vpn in self.vpn_range {
+            self.map_one(page_table, vpn);
+        }
+    }
+
+    pub fn unmap(&mut self, page_table: &mut PageTable) {
+        if self.map_type == MapType::Linear {
+            self.for_each_linear_page(|vpn, level| page_table.unmap_huge(vpn, level));
+            return;
+        }
+        for vpn in self.vpn_range {
+            self.unmap_one(page_table, vpn);
+        }
+    }
+
+    /// 恒等映射整个逻辑段，对齐且完整的区间使用1GiB或2MiB大页，其余部分使用4KiB页
+    fn map_linear(&mut self, page_table: &mut PageTable) {
+        let pte_flags = PTEFlags::from_bits(self.map_perm.bits).unwrap();
+        let mut count = [0usize; 3];
+        self.for_each_linear_page(|vpn, level| {
+            page_table.map_huge(vpn, PhysPageNum(vpn.0), pte_flags, level);
+            count[level] += 1;
+        });
+        debug!(
+            "Linear mapping [{:#x}, {:#x}): {} 1GiB pages, {} 2MiB pages, {} 4KiB pages",
+            VirtAddr::from(self.vpn_range.get_start()).0,
+            VirtAddr::from(self.vpn_range.get_end()).0,
+            count[0],
+            count[1],
+            count[2]
+        );
+    }
+
+    /// 将逻辑段划分为尽可能大的页，依次以(起始虚拟页号, 页表级别)调用f
+    fn for_each_linear_page(&self, mut f: impl FnMut(VirtPageNum, usize)) {
+        let end = self.vpn_range.get_end().0;
+        let mut vpn = self.vpn_range.get_start().0;
+        while vpn < end {
+            let level = (0..3)
+                .find(|&level| {
+                    let pages = pages_at_level(level);
+                    vpn % pages == 0 && vpn + pages <= end
+                })
+                .unwrap();
+            f(VirtPageNum(vpn), level);
+            vpn += pages_at_level(level);
+        }
+    }
+
+    /// 将数据拷贝至逻辑段起始处，要求逻辑段已映射且为Framed类型
+    pub fn copy_data(&mut self, page_table: &mut PageTable, data: &[u8]) {
+        assert_eq!(self.map_type, MapType::Framed);
+        let mut start: usize = 0;
+        let mut current_vpn = self.vpn_range.get_start();
+        let len = data.len();
+        loop {
+            let src = &data[start..len.min(start + PAGE_SIZE)];
+            let dst = &mut page_table
+                .translate(current_vpn)
+                .unwrap()
+                .ppn()
+                .get_bytes_array()[..src.len()];
+            dst.copy_from_slice(src);
+            start += PAGE_SIZE;
+            if start >= len {
+                break;
+            }
+            current_vpn.step();
+        }
+    }
+}
+
+/// 映射方式
+#[derive(Copy, Clone, PartialEq, Debug)]
+pub enum MapType {
+    /// 恒等映射
+    Identical,
+    /// 使用大页的恒等映射，用于内核对物理内存的线性映射
+    Linear,
+    /// 为每个虚拟页分配新的物理页帧
+    Framed,
+    /// 首次访问时才在缺页异常中映射，用于mmap
+    Lazy,
+}
+
+bitflags! {
+    /// 逻辑段的访问权限，与页表项中对应的标志位一致
+    pub struct MapPermission: u8 {
+        const R = 1 << 1;
+        const W = 1 << 2;
+        const X = 1 << 3;
+        const U = 1 << 4;
+    }
+}
+
+/// 检查内核地址空间中各段的权限是否符合预期
+pub fn remap_test() {
+    let kernel_space = KERNEL_SPACE.exclusive_access();
+    let mid_text: VirtAddr = ((stext as usize + etext as usize) / 2).into();
+    let mid_rodata: VirtAddr = ((srodata as usize + erodata as usize) / 2).into();
+    let mid_data: VirtAddr = ((sdata as usize + edata as usize) / 2).into();
+    assert!(!kernel_space
+        .page_table
+        .translate(mid_text.floor())
+        .unwrap()
+        .writable());
+    assert!(!kernel_space
+        .page_table
+        .translate(mid_rodata.floor())
+        .unwrap()
+        .writable());
+    assert!(!kernel_space
+        .page_table
+        .translate(mid_rodata.floor())
+        .unwrap()
+        .executable());
+    assert!(!kernel_space
+        .page_table
+        .translate(mid_data.floor())
+        .unwrap()
+        .executable());
+    assert!(kernel_space
+        .page_table
+        .translate(VirtAddr::from(boot_stack_guard as usize).floor())
+        .map_or(true, |pte| !pte.is_valid()));
+    // 线性映射区中的大页应正确转换到对应的物理地址
+    let last_page: VirtAddr = (MEMORY_END - PAGE_SIZE + 0x123).into();
+    assert_eq!(
+        kernel_space.page_table.translate_va(last_page),
+        Some(PhysAddr::from(MEMORY_END - PAGE_SIZE + 0x123))
+    );
+    info!("Kernel remap test passed!");
+}
@@ -0,0 +1,27 @@
+//! os/src/mm/mod.rs <br>
+//! 内存管理模块：物理页帧分配、内核堆、SV39页表、地址空间与mmap的文件页缓存
+
+pub use address::{PhysAddr, PhysPageNum, StepByOne, VirtAddr, VirtPageNum, VPNRange};
+pub use asid::asid_enabled;
+pub use frame_allocator::{frame_alloc, frame_alloc_contiguous, frame_stats, FrameTracker};
+pub use heap_allocator::heap_stats;
+pub use memory_set::{KERNEL_SPACE, MapPermission, MemorySet, MmapFile};
+pub use page_cache::{reload_file_pages, writeback_all_pages, writeback_file_pages};
+pub use page_table::{PageTable, PageTableEntry, PTEFlags, UserBuffer};
+
+mod address;
+mod asid;
+mod frame_allocator;
+mod heap_allocator;
+mod memory_set;
+mod page_cache;
+mod page_table;
+
+/// 初始化内存管理：内核堆、物理页帧分配器，并启用内核地址空间
+pub fn init() {
+    heap_allocator::init_heap();
+    frame_allocator::init_frame_allocator();
+    KERNEL_SPACE.exclusive_access().activate();
+    asid::init_asid_allocator();
+    memory_set::remap_test();
+}
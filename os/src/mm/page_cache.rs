@@ -0,0 +1,137 @@
+//! os/src/mm/page_cache.rs <br>
+//! 文件页缓存：mmap映射的同一文件页在所有映射之间共享同一物理页帧 <br>
+//! 缓存只持有弱引用，最后一个映射解除时缓存页被释放；修改过的页在munmap、sync/fsync与释放时经块缓存写回文件 <br>
+//! read/write系统调用不经过页缓存，而是在访问文件前写回该文件修改过的缓存页，写入文件后重新读入受影响的缓存页
+
+use alloc::collections::BTreeMap;
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+
+use crate::config::PAGE_SIZE;
+use crate::fs::Inode;
+use crate::sync::UPSafeCell;
+
+use super::{frame_alloc, FrameTracker, PhysPageNum};
+
+/// 缓存页的键：(设备编号, 索引节点编号, 文件页号)
+type PageKey = (u64, u64, usize);
+
+lazy_static! {
+    /// 全局文件页缓存
+    static ref PAGE_CACHE: UPSafeCell<BTreeMap<PageKey, Weak<CachedPage>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+/// 缓存中的一个文件页
+pub struct CachedPage {
+    frame: FrameTracker,
+    inode: Arc<dyn Inode>,
+    /// 页在文件中的序号
+    page_index: usize,
+    /// 是否经共享映射写入过；此后该页保持可写映射，之后的修改无从察觉，因此每次写回都写出整页
+    dirty: UPSafeCell<bool>,
+}
+
+impl CachedPage {
+    pub fn ppn(&self) -> PhysPageNum {
+        self.frame.ppn
+    }
+
+    /// 标记该页已被修改
+    pub fn mark_dirty(&self) {
+        *self.dirty.exclusive_access() = true;
+    }
+
+    /// 若该页被修改过，经块缓存将其写回文件
+    pub fn writeback(&self) {
+        if !*self.dirty.exclusive_access() {
+            return;
+        }
+        // 文件末尾之外的部分不写回，也不因此扩大文件
+        let offset = self.page_index * PAGE_SIZE;
+        let size = self.inode.size();
+        if offset < size {
+            let len = PAGE_SIZE.min(size - offset);
+            self.inode
+                .write_at(offset, &self.frame.ppn.get_bytes_array()[..len]);
+        }
+    }
+}
+
+impl Drop for CachedPage {
+    fn drop(&mut self) {
+        self.writeback();
+    }
+}
+
+/// 文件中第[start, end)页仍被映射的缓存页，先从缓存中取出，以免写回文件时持有缓存的借用
+fn file_pages(dev: u64, ino: u64, start: usize, end: usize) -> Vec<Arc<CachedPage>> {
+    if start >= end {
+        return Vec::new();
+    }
+    PAGE_CACHE
+        .exclusive_access()
+        .range((dev, ino, start)..(dev, ino, end))
+        .filter_map(|(_, page)| page.upgrade())
+        .collect()
+}
+
+/// 将文件中所有修改过的缓存页写回文件
+pub fn writeback_file_pages(dev: u64, ino: u64) {
+    for page in file_pages(dev, ino, 0, usize::MAX) {
+        page.writeback();
+    }
+}
+
+/// 将所有修改过的缓存页写回各自的文件
+pub fn writeback_all_pages() {
+    let pages: Vec<Arc<CachedPage>> = PAGE_CACHE
+        .exclusive_access()
+        .values()
+        .filter_map(Weak::upgrade)
+        .collect();
+    for page in pages {
+        page.writeback();
+    }
+}
+
+/// 文件的[offset, offset + len)被直接写入后，从文件重新读入与之重叠的缓存页，使各映射看到写入的数据 <br>
+/// 调用者需在写入前写回该文件修改过的缓存页，否则其中的修改将丢失
+pub fn reload_file_pages(dev: u64, inode: &Arc<dyn Inode>, offset: usize, len: usize) {
+    let start = offset / PAGE_SIZE;
+    let end = (offset + len).div_ceil(PAGE_SIZE);
+    for page in file_pages(dev, inode.ino(), start, end) {
+        inode.read_at(page.page_index * PAGE_SIZE, page.ppn().get_bytes_array());
+    }
+}
+
+/// 获取文件第page_index页的缓存页，不在缓存中时从文件读入，物理内存不足时返回None
+pub fn get_cached_page(
+    dev: u64,
+    inode: &Arc<dyn Inode>,
+    page_index: usize,
+) -> Option<Arc<CachedPage>> {
+    let key = (dev, inode.ino(), page_index);
+    if let Some(page) = PAGE_CACHE
+        .exclusive_access()
+        .get(&key)
+        .and_then(|page| page.upgrade())
+    {
+        return Some(page);
+    }
+    // 新分配的页帧已清零，文件末尾之后的部分保持为0
+    let frame = frame_alloc()?;
+    inode.read_at(page_index * PAGE_SIZE, frame.ppn.get_bytes_array());
+    let page = Arc::new(CachedPage {
+        frame,
+        inode: inode.clone(),
+        page_index,
+        dirty: unsafe { UPSafeCell::new(false) },
+    });
+    let mut cache = PAGE_CACHE.exclusive_access();
+    cache.retain(|_, page| page.strong_count() > 0);
+    cache.insert(key, Arc::downgrade(&page));
+    Some(page)
+}
@@ -0,0 +1,254 @@
+//! os/src/mm/page_table.rs <br>
+//! SV39多级页表
+
+use alloc::vec;
+use alloc::vec::Vec;
+
+use bitflags::*;
+
+use super::{frame_alloc, FrameTracker, PhysAddr, PhysPageNum, VirtAddr, VirtPageNum};
+
+bitflags! {
+    /// 页表项标志位
+    pub struct PTEFlags: u8 {
+        /// 有效位
+        const V = 1 << 0;
+        /// 可读
+        const R = 1 << 1;
+        /// 可写
+        const W = 1 << 2;
+        /// 可执行
+        const X = 1 << 3;
+        /// U模式可访问
+        const U = 1 << 4;
+        /// 全局映射
+        const G = 1 << 5;
+        /// 已访问
+        const A = 1 << 6;
+        /// 已修改
+        const D = 1 << 7;
+    }
+}
+
+/// 页表项
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct PageTableEntry {
+    pub bits: usize,
+}
+
+impl PageTableEntry {
+    pub fn new(ppn: PhysPageNum, flags: PTEFlags) -> Self {
+        PageTableEntry {
+            bits: ppn.0 << 10 | flags.bits as usize,
+        }
+    }
+
+    pub fn empty() -> Self {
+        PageTableEntry { bits: 0 }
+    }
+
+    pub fn ppn(&self) -> PhysPageNum {
+        (self.bits >> 10 & ((1usize << 44) - 1)).into()
+    }
+
+    pub fn flags(&self) -> PTEFlags {
+        PTEFlags::from_bits(self.bits as u8).unwrap()
+    }
+
+    pub fn is_valid(&self) -> bool {
+        (self.flags() & PTEFlags::V) != PTEFlags::empty()
+    }
+
+    pub fn readable(&self) -> bool {
+        (self.flags() & PTEFlags::R) != PTEFlags::empty()
+    }
+
+    pub fn writable(&self) -> bool {
+        (self.flags() & PTEFlags::W) != PTEFlags::empty()
+    }
+
+    pub fn executable(&self) -> bool {
+        (self.flags() & PTEFlags::X) != PTEFlags::empty()
+    }
+
+    /// 是否为叶节点：有效且R/W/X中至少一位被置位，否则指向下一级页表
+    pub fn is_leaf(&self) -> bool {
+        self.is_valid() && (self.flags() & (PTEFlags::R | PTEFlags::W | PTEFlags::X)) != PTEFlags::empty()
+    }
+}
+
+/// 第level级页表项所映射的4KiB页数量：0级为1GiB，1级为2MiB，2级为4KiB
+pub fn pages_at_level(level: usize) -> usize {
+    1 << (9 * (2 - level))
+}
+
+/// 页表，持有根页表及所有中间页表所在的物理页帧
+pub struct PageTable {
+    root_ppn: PhysPageNum,
+    frames: Vec<FrameTracker>,
+}
+
+impl PageTable {
+    pub fn new() -> Self {
+        let frame = frame_alloc().unwrap();
+        PageTable {
+            root_ppn: frame.ppn,
+            frames: vec![frame],
+        }
+    }
+
+    /// 查找vpn在第level级页表中对应的页表项，中间页表不存在时创建 <br>
+    /// level为0时对应1GiB大页，为1时对应2MiB大页，为2时对应4KiB页
+    fn find_pte_create(&mut self, vpn: VirtPageNum, level: usize) -> Option<&mut PageTableEntry> {
+        let idxs = vpn.indexes();
+        let mut ppn = self.root_ppn;
+        let mut result: Option<&mut PageTableEntry> = None;
+        for (i, idx) in idxs.iter().enumerate() {
+            let pte = &mut ppn.get_pte_array()[*idx];
+            if i == level {
+                result = Some(pte);
+                break;
+            }
+            if !pte.is_valid() {
+                let frame = frame_alloc().unwrap();
+                *pte = PageTableEntry::new(frame.ppn, PTEFlags::V);
+                self.frames.push(frame);
+            }
+            assert!(!pte.is_leaf(), "vpn {:?} is covered by a huge page", vpn);
+            ppn = pte.ppn();
+        }
+        result
+    }
+
+    /// 查找vpn对应的页表项及其所在级别，遇到大页时提前返回，中间页表不存在时返回None
+    fn find_pte(&self, vpn: VirtPageNum) -> Option<(&mut PageTableEntry, usize)> {
+        let idxs = vpn.indexes();
+        let mut ppn = self.root_ppn;
+        let mut result: Option<(&mut PageTableEntry, usize)> = None;
+        for (i, idx) in idxs.iter().enumerate() {
+            let pte = &mut ppn.get_pte_array()[*idx];
+            if i == 2 || pte.is_leaf() {
+                result = Some((pte, i));
+                break;
+            }
+            if !pte.is_valid() {
+                return None;
+            }
+            ppn = pte.ppn();
+        }
+        result
+    }
+
+    /// 建立vpn到ppn的映射
+    pub fn map(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags) {
+        self.map_huge(vpn, ppn, flags, 2);
+    }
+
+    /// 在第level级页表中建立vpn到ppn的映射，vpn与ppn均需按对应的页大小对齐
+    pub fn map_huge(&mut self, vpn: VirtPageNum, ppn: PhysPageNum, flags: PTEFlags, level: usize) {
+        let pages = pages_at_level(level);
+        assert!(
+            vpn.0 % pages == 0 && ppn.0 % pages == 0,
+            "vpn {:?} or ppn {:?} is not aligned to level {}",
+            vpn,
+            ppn,
+            level
+        );
+        let pte = self.find_pte_create(vpn, level).unwrap();
+        assert!(!pte.is_valid(), "vpn {:?} is mapped before mapping", vpn);
+        *pte = PageTableEntry::new(ppn, flags | PTEFlags::V);
+    }
+
+    /// 解除vpn的映射
+    pub fn unmap(&mut self, vpn: VirtPageNum) {
+        self.unmap_huge(vpn, 2);
+    }
+
+    /// 解除第level级页表中vpn的映射
+    pub fn unmap_huge(&mut self, vpn: VirtPageNum, level: usize) {
+        let (pte, pte_level) = self.find_pte(vpn).unwrap();
+        assert!(pte.is_valid(), "vpn {:?} is invalid before unmapping", vpn);
+        assert_eq!(pte_level, level, "vpn {:?} is not mapped at level {}", vpn, level);
+        *pte = PageTableEntry::empty();
+    }
+
+    /// 查询vpn对应的有效页表项，未映射时返回None；若vpn位于大页中，返回的页表项的物理页号已加上页内偏移
+    pub fn translate(&self, vpn: VirtPageNum) -> Option<PageTableEntry> {
+        let (pte, level) = self.find_pte(vpn).filter(|(pte, _)| pte.is_valid())?;
+        let offset = vpn.0 & (pages_at_level(level) - 1);
+        Some(PageTableEntry::new(
+            PhysPageNum(pte.ppn().0 + offset),
+            pte.flags(),
+        ))
+    }
+
+    /// 将虚拟地址转换为物理地址
+    pub fn translate_va(&self, va: VirtAddr) -> Option<PhysAddr> {
+        self.translate(va.floor()).map(|pte| {
+            let aligned_pa: PhysAddr = pte.ppn().into();
+            (aligned_pa.0 + va.page_offset()).into()
+        })
+    }
+
+    /// 构造写入satp的值，MODE字段为8表示启用SV39
+    pub fn token(&self) -> usize {
+        8usize << 60 | self.root_ppn.0
+    }
+}
+
+/// 应用地址空间中的缓冲区，由若干段内核可直接访问的切片组成
+pub struct UserBuffer {
+    pub buffers: Vec<&'static mut [u8]>,
+}
+
+impl UserBuffer {
+    pub fn new(buffers: Vec<&'static mut [u8]>) -> Self {
+        Self { buffers }
+    }
+
+    /// 缓冲区的总长度
+    pub fn len(&self) -> usize {
+        self.buffers.iter().map(|b| b.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl IntoIterator for UserBuffer {
+    type Item = *mut u8;
+    type IntoIter = UserBufferIterator;
+    fn into_iter(self) -> Self::IntoIter {
+        UserBufferIterator {
+            buffers: self.buffers,
+            current_buffer: 0,
+            current_idx: 0,
+        }
+    }
+}
+
+/// 逐字节遍历UserBuffer
+pub struct UserBufferIterator {
+    buffers: Vec<&'static mut [u8]>,
+    current_buffer: usize,
+    current_idx: usize,
+}
+
+impl Iterator for UserBufferIterator {
+    type Item = *mut u8;
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.current_buffer < self.buffers.len() {
+            let buffer = &mut self.buffers[self.current_buffer];
+            if self.current_idx < buffer.len() {
+                let r = &mut buffer[self.current_idx] as *mut u8;
+                self.current_idx += 1;
+                return Some(r);
+            }
+            self.current_buffer += 1;
+            self.current_idx = 0;
+        }
+        None
+    }
+}
@@ -2,13 +2,14 @@
 //! The rust_sbi service impl
 
 /* sbi_call()          Func    call sbi service
+ * set_timer()         Func    set the next timer interrupt
  * console_putchar()   Func    put a char into console
+ * console_getchar()   Func    get a char from console
  * shutdown()          Func    shutdown the machine gracefully
  */
 
 use core::arch::asm;
 
-#[allow(unused)]
 const SBI_SET_TIMER: usize = 0;
 const SBI_CONSOLE_PUTCHAR: usize = 1;
 const SBI_CONSOLE_GETCHAR: usize = 2;
@@ -39,6 +40,13 @@ fn sbi_call(eid: usize, fid: usize, arg0: usize, arg1: usize, arg2: usize) -> us
     ret
 }
 
+/// set the next timer interrupt
+/// # args
+/// * `timer` - the value of `mtime` when the interrupt should fire
+pub fn set_timer(timer: usize) {
+    sbi_call(SBI_SET_TIMER, 0, timer, 0, 0);
+}
+
 /// put a char into console
 /// # args
 /// * `c` - char
@@ -46,6 +54,13 @@ pub fn console_putchar(c: usize) {
     sbi_call(SBI_CONSOLE_PUTCHAR, 0, c, 0, 0);
 }
 
+/// get a char from console
+/// # return
+/// * the char, or `usize::MAX` (-1) if there is no input
+pub fn console_getchar() -> usize {
+    sbi_call(SBI_CONSOLE_GETCHAR, 0, 0, 0, 0)
+}
+
 /// shutdown the machine gracefully
 pub fn shutdown() -> ! {
     sbi_call(SRST_EXTENSION, SBI_SHUTDOWN, 0, 0, 0);
@@ -0,0 +1,34 @@
+//! os/src/sync/condvar.rs <br>
+//! 条件变量：与互斥锁配合使用，等待前释放互斥锁，被唤醒后重新获取
+
+use alloc::sync::Arc;
+
+use crate::task::block_current_and_run_next;
+
+use super::wait_queue::WaitQueue;
+use super::{Mutex, UPSafeCell};
+
+pub struct Condvar {
+    wait_queue: UPSafeCell<WaitQueue>,
+}
+
+impl Condvar {
+    pub fn new() -> Self {
+        Self {
+            wait_queue: unsafe { UPSafeCell::new(WaitQueue::new()) },
+        }
+    }
+
+    /// 唤醒一个等待的任务，没有任务等待时信号丢失
+    pub fn signal(&self) {
+        self.wait_queue.exclusive_access().wake_one();
+    }
+
+    /// 阻塞直至被唤醒，随后重新获取mutex <br>
+    /// 调用者需已释放mutex，且释放后未切换任务，以免错过其间发出的信号
+    pub fn wait(&self, mutex: Arc<dyn Mutex>) {
+        self.wait_queue.exclusive_access().push_current();
+        block_current_and_run_next();
+        mutex.lock();
+    }
+}
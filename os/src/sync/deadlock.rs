@@ -0,0 +1,125 @@
+//! os/src/sync/deadlock.rs <br>
+//! 死锁检测：记录进程中各线程对互斥锁与信号量的占有量与需求量，以银行家算法的安全性检查判断请求是否可能导致死锁 <br>
+//! 互斥锁视为数量为1的资源，信号量视为数量为其计数的资源；等待中的线程即对所等资源有一个单位的需求
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+/// 可被线程占有的资源
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Resource {
+    Mutex(usize),
+    Semaphore(usize),
+}
+
+/// 单个线程的占有量与需求量
+#[derive(Default)]
+struct ThreadResources {
+    allocation: BTreeMap<Resource, usize>,
+    need: BTreeMap<Resource, usize>,
+}
+
+/// 进程的死锁检测器，未开启检测时仍记录资源状态，以便随时开启
+pub struct DeadlockDetector {
+    enabled: bool,
+    /// 各资源的可用量
+    available: BTreeMap<Resource, usize>,
+    /// 以tid为键的各线程资源状态
+    threads: BTreeMap<usize, ThreadResources>,
+}
+
+impl DeadlockDetector {
+    pub fn new() -> Self {
+        Self {
+            enabled: false,
+            available: BTreeMap::new(),
+            threads: BTreeMap::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+    }
+
+    /// 登记新创建的资源及其数量
+    pub fn add_resource(&mut self, resource: Resource, count: usize) {
+        self.available.insert(resource, count);
+    }
+
+    /// 线程tid请求一个单位的资源；开启检测且满足请求后系统可能进入不安全状态时撤销请求并返回false
+    pub fn request(&mut self, tid: usize, resource: Resource) -> bool {
+        *self
+            .threads
+            .entry(tid)
+            .or_default()
+            .need
+            .entry(resource)
+            .or_insert(0) += 1;
+        if self.enabled && !self.is_safe() {
+            self.cancel(tid, resource);
+            return false;
+        }
+        true
+    }
+
+    /// 线程tid获得了一个单位的资源，其对该资源的需求随之满足
+    pub fn acquire(&mut self, tid: usize, resource: Resource) {
+        self.cancel(tid, resource);
+        *self
+            .threads
+            .entry(tid)
+            .or_default()
+            .allocation
+            .entry(resource)
+            .or_insert(0) += 1;
+        let available = self.available.entry(resource).or_insert(0);
+        *available = available.saturating_sub(1);
+    }
+
+    /// 线程tid释放了一个单位的资源；信号量可由未占有它的线程释放
+    pub fn release(&mut self, tid: usize, resource: Resource) {
+        if let Some(count) = self
+            .threads
+            .get_mut(&tid)
+            .and_then(|thread| thread.allocation.get_mut(&resource))
+        {
+            *count = count.saturating_sub(1);
+        }
+        *self.available.entry(resource).or_insert(0) += 1;
+    }
+
+    /// 线程退出：其需求随之消失，其占有的资源不会再被释放
+    pub fn remove_thread(&mut self, tid: usize) {
+        self.threads.remove(&tid);
+    }
+
+    /// 撤销线程tid对资源的一个单位的需求
+    fn cancel(&mut self, tid: usize, resource: Resource) {
+        if let Some(count) = self
+            .threads
+            .get_mut(&tid)
+            .and_then(|thread| thread.need.get_mut(&resource))
+        {
+            *count = count.saturating_sub(1);
+        }
+    }
+
+    /// 安全性检查：反复找出需求能被当前可用资源满足的线程，假设其运行结束并归还占有的资源，
+    /// 所有线程都能结束时状态安全
+    fn is_safe(&self) -> bool {
+        let mut work = self.available.clone();
+        let mut unfinished: Vec<&ThreadResources> = self.threads.values().collect();
+        while let Some(i) = unfinished.iter().position(|thread| {
+            thread
+                .need
+                .iter()
+                .all(|(resource, need)| work.get(resource).copied().unwrap_or(0) >= *need)
+        }) {
+            let thread = unfinished.swap_remove(i);
+            for (resource, count) in thread.allocation.iter() {
+                *work.entry(*resource).or_insert(0) += count;
+            }
+        }
+        unfinished.is_empty()
+    }
+}
@@ -1,4 +1,16 @@
+//! os/src/sync/mod.rs <br>
+//! 同步原语：单核环境下的内核引用单元，以及供用户线程使用的互斥锁、信号量与条件变量及其死锁检测
+
+pub use condvar::Condvar;
+pub use deadlock::{DeadlockDetector, Resource};
+pub use mutex::{Mutex, MutexBlocking, MutexSpin};
+pub use semaphore::Semaphore;
 pub use ups_cell::UPSafeCell;
+pub use wait_queue::WaitQueue;
 
+mod condvar;
+mod deadlock;
+mod mutex;
+mod semaphore;
 mod ups_cell;
-
+mod wait_queue;
@@ -0,0 +1,169 @@
+//! os/src/sync/mutex.rs <br>
+//! 互斥锁：自旋锁在锁被占用时让出CPU后重试，阻塞锁将等待的任务挂入等待队列 <br>
+//! 阻塞锁支持优先级继承：持有者的有效优先级不低于任何等待者，避免高优先级线程因中等优先级线程占用CPU而无限等待
+
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::ptr;
+
+use crate::task::{
+    block_current_and_run_next, current_task, wakeup_task, yield_current_and_run_next,
+    TaskControlBlock,
+};
+
+use super::wait_queue::WaitQueue;
+use super::UPSafeCell;
+
+/// 互斥锁
+pub trait Mutex: Sync + Send {
+    /// 获取锁，锁被占用时等待
+    fn lock(&self);
+    /// 释放锁，锁不被当前任务持有时返回false
+    fn unlock(&self) -> bool;
+}
+
+/// owner是否为当前任务
+fn owned_by_current(owner: &Option<Weak<TaskControlBlock>>) -> bool {
+    let current = current_task().unwrap();
+    owner
+        .as_ref()
+        .is_some_and(|owner| ptr::eq(owner.as_ptr(), Arc::as_ptr(&current)))
+}
+
+/// 自旋锁：锁被占用时让出CPU，再次被调度时重试
+pub struct MutexSpin {
+    /// 锁的持有者，为None时锁空闲
+    owner: UPSafeCell<Option<Weak<TaskControlBlock>>>,
+}
+
+impl MutexSpin {
+    pub fn new() -> Self {
+        Self {
+            owner: unsafe { UPSafeCell::new(None) },
+        }
+    }
+}
+
+impl Mutex for MutexSpin {
+    fn lock(&self) {
+        loop {
+            let mut owner = self.owner.exclusive_access();
+            if owner.is_some() {
+                drop(owner);
+                yield_current_and_run_next();
+            } else {
+                *owner = Some(Arc::downgrade(&current_task().unwrap()));
+                return;
+            }
+        }
+    }
+
+    fn unlock(&self) -> bool {
+        let mut owner = self.owner.exclusive_access();
+        if !owned_by_current(&owner) {
+            return false;
+        }
+        *owner = None;
+        true
+    }
+}
+
+/// 阻塞锁：锁被占用时阻塞，释放锁时直接将锁交给等待最久的任务 <br>
+/// 等待者将自己的有效优先级让渡给持有者，锁转手时余下的等待者改为向新持有者让渡
+pub struct MutexBlocking {
+    inner: UPSafeCell<MutexBlockingInner>,
+}
+
+struct MutexBlockingInner {
+    locked: bool,
+    /// 锁的持有者
+    owner: Option<Weak<TaskControlBlock>>,
+    wait_queue: WaitQueue,
+}
+
+impl MutexBlocking {
+    pub fn new() -> Self {
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(MutexBlockingInner {
+                    locked: false,
+                    owner: None,
+                    wait_queue: WaitQueue::new(),
+                })
+            },
+        }
+    }
+}
+
+impl Mutex for MutexBlocking {
+    fn lock(&self) {
+        let current = current_task().unwrap();
+        let mut inner = self.inner.exclusive_access();
+        if inner.locked {
+            inner.wait_queue.push_current();
+            let owner = inner.owner.as_ref().and_then(Weak::upgrade);
+            drop(inner);
+            // 向持有者让渡优先级；重复获取自身持有的锁时永远不会被唤醒，无需让渡
+            if let Some(owner) = owner.filter(|owner| !Arc::ptr_eq(owner, &current)) {
+                current.inner_exclusive_access().lock_owner = Some(Arc::downgrade(&owner));
+                owner
+                    .inner_exclusive_access()
+                    .lock_waiters
+                    .push(Arc::downgrade(&current));
+                owner.update_priority();
+            }
+            drop(current);
+            // 被唤醒时已持有锁
+            block_current_and_run_next();
+        } else {
+            inner.locked = true;
+            inner.owner = Some(Arc::downgrade(&current));
+        }
+    }
+
+    fn unlock(&self) -> bool {
+        let mut inner = self.inner.exclusive_access();
+        if !inner.locked || !owned_by_current(&inner.owner) {
+            return false;
+        }
+        let owner = inner.owner.take().and_then(|owner| owner.upgrade());
+        // 有任务等待时锁保持占用，由被唤醒的任务持有
+        let next = inner.wait_queue.pop();
+        let waiters: Vec<_> = inner.wait_queue.tasks().collect();
+        match next.as_ref() {
+            Some(next) => inner.owner = Some(Arc::downgrade(next)),
+            None => inner.locked = false,
+        }
+        drop(inner);
+        // 该锁的等待者不再向原持有者让渡优先级
+        if let Some(owner) = owner.as_ref() {
+            owner
+                .inner_exclusive_access()
+                .lock_waiters
+                .retain(|waiter| {
+                    waiter.upgrade().is_some_and(|waiter| {
+                        !waiters
+                            .iter()
+                            .chain(next.iter())
+                            .any(|other| Arc::ptr_eq(&waiter, other))
+                    })
+                });
+        }
+        if let Some(next) = next {
+            let mut next_inner = next.inner_exclusive_access();
+            next_inner.lock_owner = None;
+            for waiter in waiters.iter() {
+                waiter.inner_exclusive_access().lock_owner = Some(Arc::downgrade(&next));
+                next_inner.lock_waiters.push(Arc::downgrade(waiter));
+            }
+            drop(next_inner);
+            next.update_priority();
+            wakeup_task(next);
+        }
+        // 恢复原持有者的优先级
+        if let Some(owner) = owner {
+            owner.update_priority();
+        }
+        true
+    }
+}
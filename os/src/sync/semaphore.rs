@@ -0,0 +1,50 @@
+//! os/src/sync/semaphore.rs <br>
+//! 信号量：计数为负时其绝对值即等待队列中的任务数
+
+use crate::task::block_current_and_run_next;
+
+use super::wait_queue::WaitQueue;
+use super::UPSafeCell;
+
+pub struct Semaphore {
+    inner: UPSafeCell<SemaphoreInner>,
+}
+
+struct SemaphoreInner {
+    count: isize,
+    wait_queue: WaitQueue,
+}
+
+impl Semaphore {
+    /// 创建初始计数为res_count的信号量
+    pub fn new(res_count: usize) -> Self {
+        Self {
+            inner: unsafe {
+                UPSafeCell::new(SemaphoreInner {
+                    count: res_count as isize,
+                    wait_queue: WaitQueue::new(),
+                })
+            },
+        }
+    }
+
+    /// V操作：计数加一，有任务等待时唤醒其中等待最久的一个
+    pub fn up(&self) {
+        let mut inner = self.inner.exclusive_access();
+        inner.count += 1;
+        if inner.count <= 0 {
+            inner.wait_queue.wake_one();
+        }
+    }
+
+    /// P操作：计数减一，计数为负时阻塞直至被up唤醒
+    pub fn down(&self) {
+        let mut inner = self.inner.exclusive_access();
+        inner.count -= 1;
+        if inner.count < 0 {
+            inner.wait_queue.push_current();
+            drop(inner);
+            block_current_and_run_next();
+        }
+    }
+}
@@ -0,0 +1,55 @@
+//! os/src/sync/wait_queue.rs <br>
+//! 等待队列：按先进先出的顺序保存阻塞在某个同步对象上的任务 <br>
+//! 队列只持有任务的弱引用，进程退出时被阻塞的线程随之释放，不会因仍在队列中而泄漏
+
+use alloc::collections::VecDeque;
+use alloc::sync::{Arc, Weak};
+
+use crate::task::{current_task, wakeup_task, TaskControlBlock};
+
+pub struct WaitQueue {
+    queue: VecDeque<Weak<TaskControlBlock>>,
+}
+
+impl WaitQueue {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// 将当前任务加入队尾，调用者随后需调用block_current_and_run_next
+    pub fn push_current(&mut self) {
+        self.queue
+            .push_back(Arc::downgrade(&current_task().unwrap()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    /// 取出队首仍然存活的任务，调用者负责唤醒它
+    pub fn pop(&mut self) -> Option<Arc<TaskControlBlock>> {
+        while let Some(task) = self.queue.pop_front() {
+            if let Some(task) = task.upgrade() {
+                return Some(task);
+            }
+        }
+        None
+    }
+
+    /// 唤醒队首仍然存活的任务，返回是否唤醒了任务
+    pub fn wake_one(&mut self) -> bool {
+        if let Some(task) = self.pop() {
+            wakeup_task(task);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// 队列中仍然存活的任务
+    pub fn tasks(&self) -> impl Iterator<Item = Arc<TaskControlBlock>> + '_ {
+        self.queue.iter().filter_map(Weak::upgrade)
+    }
+}
@@ -1,23 +1,378 @@
 //! os/src/syscall/file_sys.rs <br>
 //! file and file-system related syscall
 
-use log::*;
+use alloc::string::String;
+use core::mem::size_of;
 
-use crate::print;
+use crate::config::MAX_FD;
+use crate::fs::{
+    is_mount_point, lookup, lookup_parent, make_pipe, normalize_path, open_file, sync_all,
+    OpenFlags, Stat,
+};
+use crate::mm::{MapPermission, UserBuffer};
+use crate::task::current_process;
 
-const FD_STDOUT: usize = 1;
+/// 表示相对于当前工作目录解析路径的dirfd
+pub const AT_FDCWD: isize = -100;
+/// unlinkat的标志：删除目录
+const AT_REMOVEDIR: u32 = 0x200;
 
+/// 将相对于dirfd的路径path转换为规范化的绝对路径：绝对路径无需转换，dirfd为AT_FDCWD时相对于
+/// 当前工作目录，否则相对于dirfd对应目录打开时的路径；dirfd无效或不是目录时返回None
+pub fn resolve_path(dirfd: isize, path: &str) -> Option<String> {
+    if path.starts_with('/') {
+        return Some(normalize_path("/", path));
+    }
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    if dirfd == AT_FDCWD {
+        return Some(normalize_path(inner.cwd.as_str(), path));
+    }
+    let file = inner.fd_table.get(dirfd as usize)?.as_ref()?;
+    let os_inode = file.as_os_inode()?;
+    if os_inode.inode().is_dir() {
+        Some(normalize_path(os_inode.path(), path))
+    } else {
+        None
+    }
+}
+
+/// 将当前应用地址空间中的缓冲区转换为UserBuffer，write表示内核将写入该缓冲区 <br>
+/// 缓冲区中尚未映射的页（如mmap的区域）先经缺页处理映射，缓冲区非法时返回None
+fn user_buffer(buf: *const u8, len: usize, write: bool) -> Option<UserBuffer> {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let access = if write {
+        MapPermission::W
+    } else {
+        MapPermission::R
+    };
+    let buffers = inner.memory_set.translated_byte_buffer(buf, len, access)?;
+    Some(UserBuffer::new(buffers))
+}
+
+/// 从当前应用地址空间中读取以'\0'结尾的字符串，地址非法时返回None
+pub fn user_str(ptr: *const u8) -> Option<String> {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    inner.memory_set.translated_str(ptr)
+}
+
+/// 将缓冲区中的数据写入fd对应的文件，返回写入的字节数，fd无效或不可写时返回-1
 pub fn sys_write(fd: usize, buf: *const u8, len: usize) -> isize {
-    match fd {
-        FD_STDOUT => {
-            let slice = unsafe { core::slice::from_raw_parts(buf, len) };
-            let str = core::str::from_utf8(slice).unwrap();
-            print!("{}", str);
-            len as isize
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    if let Some(file) = &inner.fd_table[fd] {
+        if !file.writable() {
+            return -1;
         }
-        _ => {
-            error!("Unsupported fd {}", fd);
-            -1
+        let file = file.clone();
+        // 写文件时可能切换任务，需提前释放对进程控制块的借用
+        drop(inner);
+        match user_buffer(buf, len, false) {
+            Some(user_buf) => file.write(user_buf) as isize,
+            None => -1,
         }
+    } else {
+        -1
+    }
+}
+
+/// 从fd对应的文件读取数据至缓冲区，返回读取的字节数，fd无效或不可读时返回-1
+pub fn sys_read(fd: usize, buf: *const u8, len: usize) -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    if let Some(file) = &inner.fd_table[fd] {
+        if !file.readable() {
+            return -1;
+        }
+        let file = file.clone();
+        // 读文件时可能切换任务，需提前释放对进程控制块的借用
+        drop(inner);
+        match user_buffer(buf, len, true) {
+            Some(user_buf) => file.read(user_buf) as isize,
+            None => -1,
+        }
+    } else {
+        -1
+    }
+}
+
+/// 按flags打开相对于dirfd的路径path，返回分配的文件描述符，失败或文件描述符耗尽时返回-1
+pub fn sys_openat(dirfd: isize, path: *const u8, flags: u32) -> isize {
+    let process = current_process();
+    let Some(path) = user_str(path) else {
+        return -1;
+    };
+    let Some(flags) = OpenFlags::from_bits(flags) else {
+        return -1;
+    };
+    let Some(path) = resolve_path(dirfd, path.as_str()) else {
+        return -1;
+    };
+    let Some(inode) = open_file(path.as_str(), flags) else {
+        return -1;
+    };
+    let mut inner = process.inner_exclusive_access();
+    let Some(fd) = inner.alloc_fd() else {
+        return -1;
+    };
+    inner.fd_table[fd] = Some(inode);
+    fd as isize
+}
+
+/// 关闭文件描述符fd，fd无效时返回-1
+pub fn sys_close(fd: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return -1;
+    }
+    inner.fd_table[fd].take();
+    0
+}
+
+/// 创建管道，将读端与写端的文件描述符依次写入pipe指向的数组，文件描述符不足两个时返回-1
+pub fn sys_pipe(pipe: *mut usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    // 先检查pipe可写，以免分配了文件描述符却无法告知应用
+    let Some(read_fd_ref) = inner.memory_set.translated_refmut(pipe) else {
+        return -1;
+    };
+    let Some(write_fd_ref) = inner.memory_set.translated_refmut(pipe.wrapping_add(1)) else {
+        return -1;
+    };
+    let (pipe_read, pipe_write) = make_pipe();
+    let Some(read_fd) = inner.alloc_fd() else {
+        return -1;
+    };
+    inner.fd_table[read_fd] = Some(pipe_read);
+    let Some(write_fd) = inner.alloc_fd() else {
+        inner.fd_table[read_fd] = None;
+        return -1;
+    };
+    inner.fd_table[write_fd] = Some(pipe_write);
+    *read_fd_ref = read_fd;
+    *write_fd_ref = write_fd;
+    0
+}
+
+/// 复制文件描述符fd，返回新分配的（最小的空闲）文件描述符，fd无效或文件描述符耗尽时返回-1
+pub fn sys_dup(fd: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() || inner.fd_table[fd].is_none() {
+        return -1;
+    }
+    let Some(new_fd) = inner.alloc_fd() else {
+        return -1;
+    };
+    inner.fd_table[new_fd] = inner.fd_table[fd].clone();
+    new_fd as isize
+}
+
+/// 将文件描述符old_fd复制到new_fd，new_fd原先打开的文件将被关闭，返回new_fd <br>
+/// old_fd无效或new_fd不小于MAX_FD时返回-1
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if new_fd >= MAX_FD {
+        return -1;
+    }
+    if old_fd >= inner.fd_table.len() || inner.fd_table[old_fd].is_none() {
+        return -1;
+    }
+    if old_fd == new_fd {
+        return new_fd as isize;
+    }
+    if new_fd >= inner.fd_table.len() {
+        inner.fd_table.resize(new_fd + 1, None);
+    }
+    inner.fd_table[new_fd] = inner.fd_table[old_fd].clone();
+    new_fd as isize
+}
+
+/// 在相对于dirfd的路径path处创建目录，父目录不存在或同名文件已存在时返回-1
+pub fn sys_mkdirat(dirfd: isize, path: *const u8, _mode: u32) -> isize {
+    let Some(path) = user_str(path) else {
+        return -1;
+    };
+    let Some(path) = resolve_path(dirfd, path.as_str()) else {
+        return -1;
+    };
+    match lookup_parent(path.as_str()) {
+        Some((parent, _, name)) if parent.create_dir(name).is_some() => 0,
+        _ => -1,
+    }
+}
+
+/// 删除相对于dirfd的路径path对应的目录项，删除目录时flags须含AT_REMOVEDIR且目录须为空 <br>
+/// 挂载点不能删除；失败时返回-1
+pub fn sys_unlinkat(dirfd: isize, path: *const u8, flags: u32) -> isize {
+    let Some(path) = user_str(path) else {
+        return -1;
+    };
+    let Some(path) = resolve_path(dirfd, path.as_str()) else {
+        return -1;
+    };
+    if is_mount_point(path.as_str()) {
+        return -1;
+    }
+    let Some((parent, _, name)) = lookup_parent(path.as_str()) else {
+        return -1;
+    };
+    // 不保留目录项对应的索引节点，以免文件系统因其仍有句柄而推迟回收
+    let Some(is_dir) = parent.find(name).map(|inode| inode.is_dir()) else {
+        return -1;
+    };
+    if is_dir != (flags & AT_REMOVEDIR != 0) {
+        return -1;
+    }
+    if parent.unlink(name) {
+        0
+    } else {
+        -1
+    }
+}
+
+/// 为相对于old_dirfd的old_path创建硬链接new_path（相对于new_dirfd），old_path不能是目录，
+/// 两者须位于同一文件系统；失败时返回-1
+pub fn sys_linkat(
+    old_dirfd: isize,
+    old_path: *const u8,
+    new_dirfd: isize,
+    new_path: *const u8,
+    _flags: u32,
+) -> isize {
+    let (Some(old_path), Some(new_path)) = (user_str(old_path), user_str(new_path)) else {
+        return -1;
+    };
+    let Some(old_path) = resolve_path(old_dirfd, old_path.as_str()) else {
+        return -1;
+    };
+    let Some(new_path) = resolve_path(new_dirfd, new_path.as_str()) else {
+        return -1;
+    };
+    let Some((target, old_mount)) = lookup(old_path.as_str()) else {
+        return -1;
+    };
+    match lookup_parent(new_path.as_str()) {
+        Some((parent, new_mount, name))
+            if old_mount.dev == new_mount.dev && parent.link(name, target.as_ref()) =>
+        {
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// 切换当前工作目录，path不存在或不是目录时返回-1
+pub fn sys_chdir(path: *const u8) -> isize {
+    let Some(path) = user_str(path) else {
+        return -1;
+    };
+    let Some(path) = resolve_path(AT_FDCWD, path.as_str()) else {
+        return -1;
+    };
+    match lookup(path.as_str()) {
+        Some((inode, _)) if inode.is_dir() => {
+            let process = current_process();
+            process.inner_exclusive_access().cwd = path;
+            0
+        }
+        _ => -1,
+    }
+}
+
+/// 将当前工作目录的绝对路径（以'\0'结尾）写入buf，返回写入的字节数，buf过小时返回-1
+pub fn sys_getcwd(buf: *mut u8, len: usize) -> isize {
+    let cwd = current_process().inner_exclusive_access().cwd.clone();
+    let size = cwd.len() + 1;
+    if size > len {
+        return -1;
+    }
+    let Some(user_buf) = user_buffer(buf, size, true) else {
+        return -1;
+    };
+    for (dst, src) in user_buf
+        .into_iter()
+        .zip(cwd.as_bytes().iter().chain(core::iter::once(&0u8)))
+    {
+        unsafe {
+            *dst = *src;
+        }
+    }
+    size as isize
+}
+
+/// 读取目录fd中的目录项，按linux_dirent64的格式写入buf <br>
+/// 返回写入的字节数，已读完时返回0，fd无效、不是目录或buf过小时返回-1
+pub fn sys_getdents64(fd: usize, buf: *mut u8, len: usize) -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    let Some(Some(file)) = inner.fd_table.get(fd) else {
+        return -1;
+    };
+    let file = file.clone();
+    drop(inner);
+    match user_buffer(buf, len, true) {
+        Some(user_buf) => file.getdents(user_buf),
+        None => -1,
+    }
+}
+
+/// 将fd对应文件的元数据写入应用地址空间中的st，fd无效时返回-1
+pub fn sys_fstat(fd: usize, st: *mut u8) -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    if let Some(file) = &inner.fd_table[fd] {
+        let file = file.clone();
+        drop(inner);
+        let stat = file.stat();
+        // Stat可能跨越页边界，按字节复制
+        let Some(user_buf) = user_buffer(st, size_of::<Stat>(), true) else {
+            return -1;
+        };
+        for (dst, src) in user_buf.into_iter().zip(stat.as_bytes().iter()) {
+            unsafe {
+                *dst = *src;
+            }
+        }
+        0
+    } else {
+        -1
+    }
+}
+
+/// 将块缓存中所有的脏块写回磁盘
+pub fn sys_sync() -> isize {
+    sync_all();
+    0
+}
+
+/// 将fd对应文件的脏数据写回磁盘，fd无效时返回-1
+pub fn sys_fsync(fd: usize) -> isize {
+    let process = current_process();
+    let inner = process.inner_exclusive_access();
+    if fd >= inner.fd_table.len() {
+        return -1;
+    }
+    if let Some(file) = &inner.fd_table[fd] {
+        let file = file.clone();
+        drop(inner);
+        file.sync();
+        0
+    } else {
+        -1
     }
-}
\ No newline at end of file
+}
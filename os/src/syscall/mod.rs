@@ -1,17 +1,167 @@
 use log::*;
 
+use crate::task::current_process;
+
 mod file_sys;
 mod process;
+mod sync;
+mod thread;
 
+const SYSCALL_GETCWD: usize = 17;
+const SYSCALL_DUP: usize = 23;
+/// 对应Linux的dup3，但不支持flags参数
+const SYSCALL_DUP2: usize = 24;
+const SYSCALL_MKDIRAT: usize = 34;
+const SYSCALL_UNLINKAT: usize = 35;
+const SYSCALL_LINKAT: usize = 37;
+const SYSCALL_CHDIR: usize = 49;
+const SYSCALL_OPENAT: usize = 56;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_PIPE: usize = 59;
+const SYSCALL_GETDENTS64: usize = 61;
+const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_FSTAT: usize = 80;
+const SYSCALL_SYNC: usize = 81;
+const SYSCALL_FSYNC: usize = 82;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_FUTEX: usize = 98;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_GET_PRIORITY: usize = 141;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_ENABLE_DEADLOCK_DETECT: usize = 469;
+const SYSCALL_THREAD_CREATE: usize = 1000;
+const SYSCALL_GETTID: usize = 1001;
+const SYSCALL_WAITTID: usize = 1002;
+const SYSCALL_MUTEX_CREATE: usize = 1010;
+const SYSCALL_MUTEX_LOCK: usize = 1011;
+const SYSCALL_MUTEX_UNLOCK: usize = 1012;
+const SYSCALL_SEMAPHORE_CREATE: usize = 1020;
+const SYSCALL_SEMAPHORE_UP: usize = 1021;
+const SYSCALL_SEMAPHORE_DOWN: usize = 1022;
+const SYSCALL_CONDVAR_CREATE: usize = 1030;
+const SYSCALL_CONDVAR_SIGNAL: usize = 1031;
+const SYSCALL_CONDVAR_WAIT: usize = 1032;
+
+/// 系统调用号对应的名称，用于procfs
+pub fn syscall_name(syscall_id: usize) -> &'static str {
+    match syscall_id {
+        SYSCALL_GETCWD => "getcwd",
+        SYSCALL_DUP => "dup",
+        SYSCALL_DUP2 => "dup2",
+        SYSCALL_MKDIRAT => "mkdirat",
+        SYSCALL_UNLINKAT => "unlinkat",
+        SYSCALL_LINKAT => "linkat",
+        SYSCALL_CHDIR => "chdir",
+        SYSCALL_OPENAT => "openat",
+        SYSCALL_CLOSE => "close",
+        SYSCALL_PIPE => "pipe",
+        SYSCALL_GETDENTS64 => "getdents64",
+        SYSCALL_READ => "read",
+        SYSCALL_WRITE => "write",
+        SYSCALL_FSTAT => "fstat",
+        SYSCALL_SYNC => "sync",
+        SYSCALL_FSYNC => "fsync",
+        SYSCALL_EXIT => "exit",
+        SYSCALL_FUTEX => "futex",
+        SYSCALL_YIELD => "yield",
+        SYSCALL_SET_PRIORITY => "set_priority",
+        SYSCALL_GET_PRIORITY => "get_priority",
+        SYSCALL_GET_TIME => "get_time",
+        SYSCALL_GETPID => "getpid",
+        SYSCALL_MUNMAP => "munmap",
+        SYSCALL_FORK => "fork",
+        SYSCALL_EXEC => "exec",
+        SYSCALL_MMAP => "mmap",
+        SYSCALL_WAITPID => "waitpid",
+        SYSCALL_ENABLE_DEADLOCK_DETECT => "enable_deadlock_detect",
+        SYSCALL_THREAD_CREATE => "thread_create",
+        SYSCALL_GETTID => "gettid",
+        SYSCALL_WAITTID => "waittid",
+        SYSCALL_MUTEX_CREATE => "mutex_create",
+        SYSCALL_MUTEX_LOCK => "mutex_lock",
+        SYSCALL_MUTEX_UNLOCK => "mutex_unlock",
+        SYSCALL_SEMAPHORE_CREATE => "semaphore_create",
+        SYSCALL_SEMAPHORE_UP => "semaphore_up",
+        SYSCALL_SEMAPHORE_DOWN => "semaphore_down",
+        SYSCALL_CONDVAR_CREATE => "condvar_create",
+        SYSCALL_CONDVAR_SIGNAL => "condvar_signal",
+        SYSCALL_CONDVAR_WAIT => "condvar_wait",
+        _ => "unknown",
+    }
+}
 
-pub fn syscall(syscall_id: usize, args: [usize; 3]) -> isize {
+pub fn syscall(syscall_id: usize, args: [usize; 6]) -> isize {
+    *current_process()
+        .inner_exclusive_access()
+        .syscall_counts
+        .entry(syscall_id)
+        .or_insert(0) += 1;
     match syscall_id {
+        SYSCALL_DUP => file_sys::sys_dup(args[0]),
+        SYSCALL_DUP2 => file_sys::sys_dup2(args[0], args[1]),
+        SYSCALL_GETCWD => file_sys::sys_getcwd(args[0] as *mut u8, args[1]),
+        SYSCALL_MKDIRAT => {
+            file_sys::sys_mkdirat(args[0] as isize, args[1] as *const u8, args[2] as u32)
+        }
+        SYSCALL_UNLINKAT => {
+            file_sys::sys_unlinkat(args[0] as isize, args[1] as *const u8, args[2] as u32)
+        }
+        SYSCALL_LINKAT => file_sys::sys_linkat(
+            args[0] as isize,
+            args[1] as *const u8,
+            args[2] as isize,
+            args[3] as *const u8,
+            args[4] as u32,
+        ),
+        SYSCALL_CHDIR => file_sys::sys_chdir(args[0] as *const u8),
+        SYSCALL_OPENAT => {
+            file_sys::sys_openat(args[0] as isize, args[1] as *const u8, args[2] as u32)
+        }
+        SYSCALL_CLOSE => file_sys::sys_close(args[0]),
+        SYSCALL_PIPE => file_sys::sys_pipe(args[0] as *mut usize),
+        SYSCALL_GETDENTS64 => file_sys::sys_getdents64(args[0], args[1] as *mut u8, args[2]),
+        SYSCALL_READ => file_sys::sys_read(args[0], args[1] as *const u8, args[2]),
         SYSCALL_WRITE => file_sys::sys_write(args[0], args[1] as *const u8, args[2]),
+        SYSCALL_FSTAT => file_sys::sys_fstat(args[0], args[1] as *mut u8),
+        SYSCALL_SYNC => file_sys::sys_sync(),
+        SYSCALL_FSYNC => file_sys::sys_fsync(args[0]),
         SYSCALL_EXIT => {
             process::sys_exit(args[0] as i32);
         }
+        SYSCALL_FUTEX => sync::sys_futex(args[0], args[1], args[2]),
+        SYSCALL_YIELD => process::sys_yield(),
+        SYSCALL_SET_PRIORITY => thread::sys_set_priority(args[0] as isize),
+        SYSCALL_GET_PRIORITY => thread::sys_get_priority(),
+        SYSCALL_GET_TIME => process::sys_get_time(args[0] as *mut process::TimeVal, args[1]),
+        SYSCALL_GETPID => process::sys_getpid(),
+        SYSCALL_FORK => process::sys_fork(),
+        SYSCALL_EXEC => process::sys_exec(args[0] as *const u8, args[1] as *const usize),
+        SYSCALL_WAITPID => process::sys_waitpid(args[0] as isize, args[1] as *mut i32),
+        SYSCALL_ENABLE_DEADLOCK_DETECT => sync::sys_enable_deadlock_detect(args[0]),
+        SYSCALL_MMAP => {
+            process::sys_mmap(args[0], args[1], args[2], args[3], args[4], args[5])
+        }
+        SYSCALL_MUNMAP => process::sys_munmap(args[0], args[1]),
+        SYSCALL_THREAD_CREATE => thread::sys_thread_create(args[0], args[1]),
+        SYSCALL_GETTID => thread::sys_gettid(),
+        SYSCALL_WAITTID => thread::sys_waittid(args[0]),
+        SYSCALL_MUTEX_CREATE => sync::sys_mutex_create(args[0] == 1),
+        SYSCALL_MUTEX_LOCK => sync::sys_mutex_lock(args[0]),
+        SYSCALL_MUTEX_UNLOCK => sync::sys_mutex_unlock(args[0]),
+        SYSCALL_SEMAPHORE_CREATE => sync::sys_semaphore_create(args[0]),
+        SYSCALL_SEMAPHORE_UP => sync::sys_semaphore_up(args[0]),
+        SYSCALL_SEMAPHORE_DOWN => sync::sys_semaphore_down(args[0]),
+        SYSCALL_CONDVAR_CREATE => sync::sys_condvar_create(),
+        SYSCALL_CONDVAR_SIGNAL => sync::sys_condvar_signal(args[0]),
+        SYSCALL_CONDVAR_WAIT => sync::sys_condvar_wait(args[0], args[1]),
         _ => {
             error!("Unsupported syscall_id {}", syscall_id);
             -1
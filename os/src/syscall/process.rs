@@ -1,11 +1,292 @@
 //! os/src/syscall/process.rs <br>
 //! process related syscall
 
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::mem::size_of;
+
+use bitflags::*;
 use log::*;
 
-use crate::batch::run_next_app;
+use crate::config::{PAGE_SIZE, USER_SPACE_END, USER_STACK_SIZE};
+use crate::fs::{open_file, OpenFlags, StatMode};
+use crate::mm::{MapPermission, MmapFile};
+use crate::task::{
+    add_task, current_process, current_task, exit_current_and_run_next, register_process,
+    yield_current_and_run_next,
+};
+use crate::timer::get_time_us;
+
+use super::file_sys::{resolve_path, user_str, AT_FDCWD};
+
+/// 时间值，与用户库中的定义保持一致
+#[repr(C)]
+#[derive(Debug)]
+pub struct TimeVal {
+    pub sec: usize,
+    pub usec: usize,
+}
+
+bitflags! {
+    /// mmap的内存保护标志，取值与Linux一致
+    struct MmapProt: usize {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXEC = 1 << 2;
+    }
+}
 
+bitflags! {
+    /// mmap的映射标志，取值与Linux一致
+    struct MmapFlags: usize {
+        /// 修改对映射同一文件的其他进程可见，并写回文件
+        const SHARED = 0x01;
+        /// 写入时复制，修改仅对本进程可见
+        const PRIVATE = 0x02;
+        /// 不映射文件，各页初始为0
+        const ANONYMOUS = 0x20;
+    }
+}
+
+/// 结束当前线程，主线程退出时整个进程随之退出
 pub fn sys_exit(exit_code: i32) -> ! {
-    info!("Application exited with code {}", exit_code);
-    run_next_app()
-}
\ No newline at end of file
+    let task = current_task().unwrap();
+    let tid = task.inner_exclusive_access().res.as_ref().unwrap().tid;
+    if tid == 0 {
+        info!("Process {} exited with code {}", task.getpid(), exit_code);
+    } else {
+        info!(
+            "Thread {} of process {} exited with code {}",
+            tid,
+            task.getpid(),
+            exit_code
+        );
+    }
+    drop(task);
+    exit_current_and_run_next(exit_code);
+    panic!("Unreachable in sys_exit!");
+}
+
+/// 主动放弃CPU，切换至下一个任务
+pub fn sys_yield() -> isize {
+    yield_current_and_run_next();
+    0
+}
+
+/// 获取当前时间，写入应用地址空间中的TimeVal
+pub fn sys_get_time(ts: *mut TimeVal, _tz: usize) -> isize {
+    let us = get_time_us();
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    let Some(ts) = inner.memory_set.translated_refmut(ts) else {
+        return -1;
+    };
+    *ts = TimeVal {
+        sec: us / 1_000_000,
+        usec: us % 1_000_000,
+    };
+    0
+}
+
+/// 获取当前进程的pid
+pub fn sys_getpid() -> isize {
+    current_task().unwrap().getpid() as isize
+}
+
+/// 判断当前线程是否为主线程且进程中没有其他未退出的线程，fork与exec只能在此时调用
+fn is_single_threaded() -> bool {
+    let tid = current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .unwrap()
+        .tid;
+    tid == 0 && current_process().inner_exclusive_access().thread_count() == 1
+}
+
+/// 复制当前进程，父进程返回子进程的pid，子进程返回0；进程中还有其他线程时返回-1
+pub fn sys_fork() -> isize {
+    if !is_single_threaded() {
+        return -1;
+    }
+    let current_process = current_process();
+    let new_process = current_process.fork();
+    let new_pid = new_process.getpid();
+    register_process(&new_process);
+    // 子进程从sys_fork返回0
+    let new_task = new_process.inner_exclusive_access().get_task(0);
+    let trap_cx = new_task.inner_exclusive_access().get_trap_cx();
+    trap_cx.x[10] = 0;
+    add_task(new_task);
+    new_pid as isize
+}
+
+/// 从文件系统中加载名为path的应用并替换当前进程，args为以0结尾的参数字符串指针数组 <br>
+/// 成功时返回argc（作为新应用入口的第一个参数），找不到应用或进程中还有其他线程时返回-1
+pub fn sys_exec(path: *const u8, mut args: *const usize) -> isize {
+    if !is_single_threaded() {
+        return -1;
+    }
+    let Some(path) = user_str(path) else {
+        return -1;
+    };
+    let mut args_vec: Vec<String> = Vec::new();
+    // 压入新用户栈的argv指针数组、各参数字符串与对齐所需的空间
+    let mut stack_size = 2 * size_of::<usize>();
+    loop {
+        let Some(&arg_str_ptr) = current_process()
+            .inner_exclusive_access()
+            .memory_set
+            .translated_ref(args)
+        else {
+            return -1;
+        };
+        if arg_str_ptr == 0 {
+            break;
+        }
+        let Some(arg) = user_str(arg_str_ptr as *const u8) else {
+            return -1;
+        };
+        stack_size += size_of::<usize>() + arg.len() + 1;
+        if stack_size > USER_STACK_SIZE {
+            return -1;
+        }
+        args_vec.push(arg);
+        args = args.wrapping_add(1);
+    }
+    let app_inode = resolve_path(AT_FDCWD, path.as_str())
+        .and_then(|path| open_file(path.as_str(), OpenFlags::RDONLY))
+        .filter(|app_inode| !app_inode.inode().is_dir());
+    if let Some(app_inode) = app_inode {
+        let data = app_inode.read_all();
+        let process = current_process();
+        let argc = args_vec.len();
+        let name = path.trim_end_matches('/').rsplit('/').next().unwrap();
+        process.exec(name, data.as_slice(), args_vec);
+        argc as isize
+    } else {
+        -1
+    }
+}
+
+/// 等待pid为pid的子进程（pid为-1时为任意子进程）退出并回收，将退出码写入exit_code_ptr <br>
+/// 不存在符合条件的子进程时返回-1，子进程尚未退出时返回-2，否则返回子进程的pid
+pub fn sys_waitpid(pid: isize, exit_code_ptr: *mut i32) -> isize {
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if !inner
+        .children
+        .iter()
+        .any(|p| pid == -1 || pid as usize == p.getpid())
+    {
+        return -1;
+    }
+    let pair = inner.children.iter().enumerate().find(|(_, p)| {
+        p.inner_exclusive_access().is_zombie && (pid == -1 || pid as usize == p.getpid())
+    });
+    if let Some((idx, _)) = pair {
+        // 先检查exit_code_ptr可写，以免回收了子进程却无法告知其退出码
+        let Some(exit_code_ref) = inner.memory_set.translated_refmut(exit_code_ptr) else {
+            return -1;
+        };
+        let child = inner.children.remove(idx);
+        // 此时子进程应只被这里引用，释放后其pid、各线程的内核栈与页表一同回收
+        assert_eq!(Arc::strong_count(&child), 1);
+        let found_pid = child.getpid();
+        let exit_code = child.inner_exclusive_access().exit_code;
+        *exit_code_ref = exit_code;
+        found_pid as isize
+    } else {
+        -2
+    }
+}
+
+/// 将fd对应文件自offset起的len字节（flags含ANONYMOUS时为清零的内存）映射到应用地址空间 <br>
+/// addr仅作为提示，返回映射的起始地址；各页在首次访问时才分配或读入，失败时返回-1 <br>
+/// SHARED与PRIVATE须指定且仅指定其一，匿名映射只支持PRIVATE；offset须按页对齐
+pub fn sys_mmap(
+    addr: usize,
+    len: usize,
+    prot: usize,
+    flags: usize,
+    fd: usize,
+    offset: usize,
+) -> isize {
+    let (Some(prot), Some(flags)) = (MmapProt::from_bits(prot), MmapFlags::from_bits(flags)) else {
+        return -1;
+    };
+    let shared = flags.contains(MmapFlags::SHARED);
+    if len == 0 || offset % PAGE_SIZE != 0 || shared == flags.contains(MmapFlags::PRIVATE) {
+        return -1;
+    }
+    let Some(len) = len.checked_next_multiple_of(PAGE_SIZE) else {
+        return -1;
+    };
+    let mut permission = MapPermission::U;
+    if prot.contains(MmapProt::READ) {
+        permission |= MapPermission::R;
+    }
+    // 页表项不允许只写不读
+    if prot.contains(MmapProt::WRITE) {
+        permission |= MapPermission::R | MapPermission::W;
+    }
+    if prot.contains(MmapProt::EXEC) {
+        permission |= MapPermission::X;
+    }
+    let process = current_process();
+    let mmap_file = if flags.contains(MmapFlags::ANONYMOUS) {
+        if shared {
+            return -1;
+        }
+        None
+    } else {
+        let Some(Some(file)) = process.inner_exclusive_access().fd_table.get(fd).cloned() else {
+            return -1;
+        };
+        let Some(os_inode) = file.as_os_inode() else {
+            return -1;
+        };
+        // 只能映射普通文件；共享的可写映射要求文件以可写方式打开
+        let stat = file.stat();
+        if !stat.mode.contains(StatMode::FILE)
+            || !file.readable()
+            || (shared && prot.contains(MmapProt::WRITE) && !file.writable())
+        {
+            return -1;
+        }
+        Some(MmapFile {
+            dev: stat.dev,
+            inode: os_inode.inode(),
+            offset,
+            shared,
+        })
+    };
+    let mut inner = process.inner_exclusive_access();
+    let Some(start) = inner.memory_set.find_free_area(addr, len) else {
+        return -1;
+    };
+    inner
+        .memory_set
+        .insert_mmap_area(start.into(), (start + len).into(), permission, mmap_file);
+    start as isize
+}
+
+/// 解除[addr, addr + len)中的mmap映射，共享映射中修改过的页在解除映射时立即写回文件 <br>
+/// addr须按页对齐，区间与非mmap映射的区域重叠时返回-1
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+    if addr % PAGE_SIZE != 0 || len == 0 {
+        return -1;
+    }
+    let Some(end) = addr.checked_add(len).filter(|&end| end <= USER_SPACE_END) else {
+        return -1;
+    };
+    let process = current_process();
+    let mut inner = process.inner_exclusive_access();
+    if inner.memory_set.munmap(addr.into(), end.into()) {
+        0
+    } else {
+        -1
+    }
+}
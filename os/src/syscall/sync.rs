@@ -0,0 +1,273 @@
+//! os/src/syscall/sync.rs <br>
+//! synchronization related syscall
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use crate::sync::{Condvar, Mutex, MutexBlocking, MutexSpin, Resource, Semaphore, WaitQueue};
+use crate::task::{block_current_and_run_next, current_process, current_task};
+
+/// 开启死锁检测后，可能导致死锁的请求返回的错误码
+const DEADLOCK_ERROR: isize = -0xDEAD;
+
+/// futex操作：值未改变时等待
+const FUTEX_WAIT: usize = 0;
+/// futex操作：唤醒等待者
+const FUTEX_WAKE: usize = 1;
+
+/// 将对象放入列表中的第一个空位，返回其下标
+fn insert_object<T: ?Sized>(list: &mut Vec<Option<Arc<T>>>, object: Arc<T>) -> usize {
+    if let Some(id) = list.iter().position(|item| item.is_none()) {
+        list[id] = Some(object);
+        id
+    } else {
+        list.push(Some(object));
+        list.len() - 1
+    }
+}
+
+/// 当前线程的tid
+fn current_tid() -> usize {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .unwrap()
+        .tid
+}
+
+/// 开启（enabled为1）或关闭（enabled为0）当前进程的死锁检测，参数无效时返回-1 <br>
+/// 开启后，获取互斥锁或对信号量执行P操作前先进行安全性检查，可能导致死锁时不再等待而返回-0xDEAD
+pub fn sys_enable_deadlock_detect(enabled: usize) -> isize {
+    if enabled > 1 {
+        return -1;
+    }
+    current_process()
+        .inner_exclusive_access()
+        .deadlock_detector
+        .set_enabled(enabled == 1);
+    0
+}
+
+/// 创建互斥锁，blocking为true时为阻塞锁，否则为自旋锁，返回其编号
+pub fn sys_mutex_create(blocking: bool) -> isize {
+    let mutex: Arc<dyn Mutex> = if blocking {
+        Arc::new(MutexBlocking::new())
+    } else {
+        Arc::new(MutexSpin::new())
+    };
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let id = insert_object(&mut process_inner.mutex_list, mutex);
+    process_inner
+        .deadlock_detector
+        .add_resource(Resource::Mutex(id), 1);
+    id as isize
+}
+
+/// 获取编号为mutex_id的互斥锁，锁被占用时等待；编号无效时返回-1，可能导致死锁时返回-0xDEAD
+pub fn sys_mutex_lock(mutex_id: usize) -> isize {
+    let tid = current_tid();
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let Some(Some(mutex)) = process_inner.mutex_list.get(mutex_id).cloned() else {
+        return -1;
+    };
+    if !process_inner
+        .deadlock_detector
+        .request(tid, Resource::Mutex(mutex_id))
+    {
+        return DEADLOCK_ERROR;
+    }
+    // 获取锁时可能切换任务，需提前释放对进程控制块的引用
+    drop(process_inner);
+    drop(process);
+    mutex.lock();
+    current_process()
+        .inner_exclusive_access()
+        .deadlock_detector
+        .acquire(tid, Resource::Mutex(mutex_id));
+    0
+}
+
+/// 释放编号为mutex_id的互斥锁，编号无效或锁不被当前线程持有时返回-1
+pub fn sys_mutex_unlock(mutex_id: usize) -> isize {
+    let process = current_process();
+    let Some(Some(mutex)) = process
+        .inner_exclusive_access()
+        .mutex_list
+        .get(mutex_id)
+        .cloned()
+    else {
+        return -1;
+    };
+    if !mutex.unlock() {
+        return -1;
+    }
+    process
+        .inner_exclusive_access()
+        .deadlock_detector
+        .release(current_tid(), Resource::Mutex(mutex_id));
+    0
+}
+
+/// 创建初始计数为res_count的信号量，返回其编号
+pub fn sys_semaphore_create(res_count: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let id = insert_object(
+        &mut process_inner.semaphore_list,
+        Arc::new(Semaphore::new(res_count)),
+    );
+    process_inner
+        .deadlock_detector
+        .add_resource(Resource::Semaphore(id), res_count);
+    id as isize
+}
+
+/// 对编号为sem_id的信号量执行V操作，编号无效时返回-1
+pub fn sys_semaphore_up(sem_id: usize) -> isize {
+    let process = current_process();
+    let Some(Some(sem)) = process
+        .inner_exclusive_access()
+        .semaphore_list
+        .get(sem_id)
+        .cloned()
+    else {
+        return -1;
+    };
+    sem.up();
+    process
+        .inner_exclusive_access()
+        .deadlock_detector
+        .release(current_tid(), Resource::Semaphore(sem_id));
+    0
+}
+
+/// 对编号为sem_id的信号量执行P操作，计数不足时等待；编号无效时返回-1，可能导致死锁时返回-0xDEAD
+pub fn sys_semaphore_down(sem_id: usize) -> isize {
+    let tid = current_tid();
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let Some(Some(sem)) = process_inner.semaphore_list.get(sem_id).cloned() else {
+        return -1;
+    };
+    if !process_inner
+        .deadlock_detector
+        .request(tid, Resource::Semaphore(sem_id))
+    {
+        return DEADLOCK_ERROR;
+    }
+    drop(process_inner);
+    drop(process);
+    sem.down();
+    current_process()
+        .inner_exclusive_access()
+        .deadlock_detector
+        .acquire(tid, Resource::Semaphore(sem_id));
+    0
+}
+
+/// 创建条件变量，返回其编号
+pub fn sys_condvar_create() -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    insert_object(&mut process_inner.condvar_list, Arc::new(Condvar::new())) as isize
+}
+
+/// 唤醒一个等待编号为condvar_id的条件变量的任务，编号无效时返回-1
+pub fn sys_condvar_signal(condvar_id: usize) -> isize {
+    let process = current_process();
+    let Some(Some(condvar)) = process
+        .inner_exclusive_access()
+        .condvar_list
+        .get(condvar_id)
+        .cloned()
+    else {
+        return -1;
+    };
+    condvar.signal();
+    0
+}
+
+/// 释放编号为mutex_id的互斥锁并等待编号为condvar_id的条件变量，被唤醒后重新获取互斥锁 <br>
+/// 编号无效或互斥锁不被当前线程持有时返回-1
+pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
+    let process = current_process();
+    let process_inner = process.inner_exclusive_access();
+    let (Some(Some(condvar)), Some(Some(mutex))) = (
+        process_inner.condvar_list.get(condvar_id).cloned(),
+        process_inner.mutex_list.get(mutex_id).cloned(),
+    ) else {
+        return -1;
+    };
+    drop(process_inner);
+    if !mutex.unlock() {
+        return -1;
+    }
+    let tid = current_tid();
+    process
+        .inner_exclusive_access()
+        .deadlock_detector
+        .release(tid, Resource::Mutex(mutex_id));
+    drop(process);
+    condvar.wait(mutex);
+    current_process()
+        .inner_exclusive_access()
+        .deadlock_detector
+        .acquire(tid, Resource::Mutex(mutex_id));
+    0
+}
+
+/// 对位于用户虚拟地址uaddr的32位futex执行op操作 <br>
+/// FUTEX_WAIT：futex的值仍为val时阻塞直至被唤醒，返回0；值已改变时返回-2 <br>
+/// FUTEX_WAKE：唤醒至多val个在该futex上等待的线程，返回唤醒的线程数 <br>
+/// 操作无效、地址未按4字节对齐或不可访问时返回-1
+pub fn sys_futex(uaddr: usize, op: usize, val: usize) -> isize {
+    if uaddr % core::mem::size_of::<u32>() != 0 {
+        return -1;
+    }
+    match op {
+        FUTEX_WAIT => futex_wait(uaddr, val as u32),
+        FUTEX_WAKE => futex_wake(uaddr, val),
+        _ => -1,
+    }
+}
+
+fn futex_wait(uaddr: usize, val: u32) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let Some(&current) = process_inner.memory_set.translated_ref(uaddr as *const u32) else {
+        return -1;
+    };
+    // 比较与入队之间不会切换任务，不会错过其间的唤醒
+    if current != val {
+        return -2;
+    }
+    process_inner
+        .futex_queues
+        .entry(uaddr)
+        .or_insert_with(WaitQueue::new)
+        .push_current();
+    drop(process_inner);
+    drop(process);
+    block_current_and_run_next();
+    0
+}
+
+fn futex_wake(uaddr: usize, count: usize) -> isize {
+    let process = current_process();
+    let mut process_inner = process.inner_exclusive_access();
+    let Some(queue) = process_inner.futex_queues.get_mut(&uaddr) else {
+        return 0;
+    };
+    let mut woken = 0;
+    while woken < count && queue.wake_one() {
+        woken += 1;
+    }
+    if queue.is_empty() {
+        process_inner.futex_queues.remove(&uaddr);
+    }
+    woken as isize
+}
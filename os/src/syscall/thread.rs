@@ -0,0 +1,96 @@
+//! os/src/syscall/thread.rs <br>
+//! thread related syscall
+
+use alloc::sync::Arc;
+
+use crate::config::{MAX_PRIORITY, MAX_THREADS};
+use crate::mm::KERNEL_SPACE;
+use crate::task::{add_task, current_process, current_task, TaskControlBlock};
+use crate::trap::{trap_handler, TrapContext};
+
+/// 在当前进程中创建新线程，新线程从entry开始执行，arg作为其第一个参数，并继承当前线程自身的优先级 <br>
+/// 返回新线程的tid，进程的线程数已达上限时返回-1
+pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
+    let process = current_process();
+    if process.inner_exclusive_access().thread_count() >= MAX_THREADS {
+        return -1;
+    }
+    let task = current_task().unwrap();
+    let task_inner = task.inner_exclusive_access();
+    let ustack_base = task_inner.res.as_ref().unwrap().ustack_base;
+    let base_priority = task_inner.base_priority;
+    drop(task_inner);
+    // 新线程拥有独立的用户栈、TrapContext页与内核栈
+    let new_task = Arc::new(TaskControlBlock::new(&process, ustack_base, true));
+    let mut new_task_inner = new_task.inner_exclusive_access();
+    new_task_inner.base_priority = base_priority;
+    new_task_inner.priority = base_priority;
+    let new_task_res = new_task_inner.res.as_ref().unwrap();
+    let new_task_tid = new_task_res.tid;
+    let trap_cx = new_task_inner.get_trap_cx();
+    *trap_cx = TrapContext::app_init_context(
+        entry,
+        new_task_res.ustack_top(),
+        KERNEL_SPACE.exclusive_access().token(),
+        new_task.kernel_stack.get_top(),
+        trap_handler as usize,
+    );
+    trap_cx.x[10] = arg;
+    drop(new_task_inner);
+    let mut process_inner = process.inner_exclusive_access();
+    let tasks = &mut process_inner.tasks;
+    if tasks.len() <= new_task_tid {
+        tasks.resize(new_task_tid + 1, None);
+    }
+    // tid被复用时，原先已退出而未回收的线程随之丢弃
+    tasks[new_task_tid] = Some(Arc::clone(&new_task));
+    drop(process_inner);
+    add_task(new_task);
+    new_task_tid as isize
+}
+
+/// 获取当前线程的tid
+pub fn sys_gettid() -> isize {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .unwrap()
+        .tid as isize
+}
+
+/// 回收当前进程中已退出的线程tid并返回其退出码 <br>
+/// 线程不存在或等待自身时返回-1，线程尚未退出时返回-2
+pub fn sys_waittid(tid: usize) -> isize {
+    let task = current_task().unwrap();
+    let process = current_process();
+    if task.inner_exclusive_access().res.as_ref().unwrap().tid == tid {
+        return -1;
+    }
+    let mut process_inner = process.inner_exclusive_access();
+    let Some(Some(waited_task)) = process_inner.tasks.get(tid) else {
+        return -1;
+    };
+    let Some(exit_code) = waited_task.inner_exclusive_access().exit_code else {
+        return -2;
+    };
+    // 释放线程控制块，其内核栈随之回收
+    process_inner.tasks[tid] = None;
+    exit_code as isize
+}
+
+/// 将当前线程自身的优先级设为prio，数值越大优先级越高 <br>
+/// 成功时返回prio，prio超出[0, MAX_PRIORITY]时返回-1
+pub fn sys_set_priority(prio: isize) -> isize {
+    if !(0..=MAX_PRIORITY as isize).contains(&prio) {
+        return -1;
+    }
+    current_task().unwrap().set_base_priority(prio as usize);
+    prio
+}
+
+/// 获取当前线程的有效优先级，即自身优先级与经阻塞锁继承的优先级中的较大者
+pub fn sys_get_priority() -> isize {
+    current_task().unwrap().inner_exclusive_access().priority as isize
+}
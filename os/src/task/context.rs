@@ -0,0 +1,36 @@
+//! os/src/task/context.rs <br>
+//! 任务上下文，用于在内核中切换任务
+
+use crate::trap::trap_return;
+
+/// 任务上下文，保存__switch时需要保留的寄存器
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct TaskContext {
+    /// 返回地址
+    ra: usize,
+    /// 内核栈指针
+    sp: usize,
+    /// 被调用者保存寄存器s0-s11
+    s: [usize; 12],
+}
+
+impl TaskContext {
+    /// 空的任务上下文
+    pub fn zero_init() -> Self {
+        Self {
+            ra: 0,
+            sp: 0,
+            s: [0; 12],
+        }
+    }
+
+    /// 构造首次被调度时直接进入trap_return的任务上下文
+    pub fn goto_trap_return(kstack_ptr: usize) -> Self {
+        Self {
+            ra: trap_return as usize,
+            sp: kstack_ptr,
+            s: [0; 12],
+        }
+    }
+}
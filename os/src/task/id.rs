@@ -0,0 +1,200 @@
+//! os/src/task/id.rs <br>
+//! 标识符与线程资源的分配：进程的pid、线程的内核栈，以及线程在进程地址空间中的tid、用户栈与TrapContext页
+
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+
+use crate::config::{kernel_stack_position, trap_cx_position, user_stack_position, PAGE_SIZE};
+use crate::mm::{KERNEL_SPACE, MapPermission, PhysPageNum, VirtAddr};
+use crate::sync::UPSafeCell;
+
+use super::ProcessControlBlock;
+
+/// 栈式编号分配器，优先复用已回收的编号
+pub struct RecycleAllocator {
+    current: usize,
+    recycled: Vec<usize>,
+}
+
+impl RecycleAllocator {
+    pub fn new() -> Self {
+        RecycleAllocator {
+            current: 0,
+            recycled: Vec::new(),
+        }
+    }
+
+    pub fn alloc(&mut self) -> usize {
+        if let Some(id) = self.recycled.pop() {
+            id
+        } else {
+            self.current += 1;
+            self.current - 1
+        }
+    }
+
+    pub fn dealloc(&mut self, id: usize) {
+        assert!(id < self.current);
+        assert!(
+            !self.recycled.contains(&id),
+            "id {} has been deallocated!",
+            id
+        );
+        self.recycled.push(id);
+    }
+
+    /// 已分配且尚未回收的编号数
+    pub fn allocated(&self) -> usize {
+        self.current - self.recycled.len()
+    }
+}
+
+lazy_static! {
+    static ref PID_ALLOCATOR: UPSafeCell<RecycleAllocator> =
+        unsafe { UPSafeCell::new(RecycleAllocator::new()) };
+    static ref KSTACK_ALLOCATOR: UPSafeCell<RecycleAllocator> =
+        unsafe { UPSafeCell::new(RecycleAllocator::new()) };
+}
+
+/// pid的RAII封装，析构时自动回收
+pub struct PidHandle(pub usize);
+
+impl Drop for PidHandle {
+    fn drop(&mut self) {
+        PID_ALLOCATOR.exclusive_access().dealloc(self.0);
+    }
+}
+
+/// 分配一个pid
+pub fn pid_alloc() -> PidHandle {
+    PidHandle(PID_ALLOCATOR.exclusive_access().alloc())
+}
+
+/// 线程的内核栈，位置由其编号决定，析构时从内核地址空间中移除并回收编号
+pub struct KernelStack(usize);
+
+/// 分配一个内核栈并映射至内核地址空间
+pub fn kstack_alloc() -> KernelStack {
+    let id = KSTACK_ALLOCATOR.exclusive_access().alloc();
+    let (kernel_stack_bottom, kernel_stack_top) = kernel_stack_position(id);
+    KERNEL_SPACE.exclusive_access().insert_framed_area(
+        kernel_stack_bottom.into(),
+        kernel_stack_top.into(),
+        MapPermission::R | MapPermission::W,
+    );
+    KernelStack(id)
+}
+
+impl KernelStack {
+    /// 内核栈栈顶
+    pub fn get_top(&self) -> usize {
+        let (_, kernel_stack_top) = kernel_stack_position(self.0);
+        kernel_stack_top
+    }
+}
+
+impl Drop for KernelStack {
+    fn drop(&mut self) {
+        let (kernel_stack_bottom, _) = kernel_stack_position(self.0);
+        let kernel_stack_bottom_va: VirtAddr = kernel_stack_bottom.into();
+        KERNEL_SPACE
+            .exclusive_access()
+            .remove_area_with_start_vpn(kernel_stack_bottom_va.into());
+        KSTACK_ALLOCATOR.exclusive_access().dealloc(self.0);
+    }
+}
+
+/// 线程在所属进程中的资源：tid、用户栈与TrapContext页，析构时一并回收 <br>
+/// 析构时会访问进程控制块，调用者需保证此时未持有进程控制块的可变引用
+pub struct TaskUserRes {
+    pub tid: usize,
+    /// 进程中各线程用户栈区域的起始地址
+    pub ustack_base: usize,
+    pub process: Weak<ProcessControlBlock>,
+}
+
+impl TaskUserRes {
+    /// 在进程中分配tid；alloc_user_res为false时不映射用户栈与TrapContext页（fork时二者已随地址空间复制）
+    pub fn new(
+        process: &Arc<ProcessControlBlock>,
+        ustack_base: usize,
+        alloc_user_res: bool,
+    ) -> Self {
+        let tid = process.inner_exclusive_access().alloc_tid();
+        let task_user_res = Self {
+            tid,
+            ustack_base,
+            process: Arc::downgrade(process),
+        };
+        if alloc_user_res {
+            task_user_res.alloc_user_res();
+        }
+        task_user_res
+    }
+
+    /// 在进程地址空间中映射该线程的用户栈与TrapContext页
+    pub fn alloc_user_res(&self) {
+        let process = self.process.upgrade().unwrap();
+        let mut process_inner = process.inner_exclusive_access();
+        let (ustack_bottom, ustack_top) = user_stack_position(self.ustack_base, self.tid);
+        process_inner.memory_set.insert_framed_area(
+            ustack_bottom.into(),
+            ustack_top.into(),
+            MapPermission::R | MapPermission::W | MapPermission::U,
+        );
+        // TrapContext页，仅内核可访问
+        let trap_cx_bottom = trap_cx_position(self.tid);
+        process_inner.memory_set.insert_framed_area(
+            trap_cx_bottom.into(),
+            (trap_cx_bottom + PAGE_SIZE).into(),
+            MapPermission::R | MapPermission::W,
+        );
+    }
+
+    /// TrapContext页在应用地址空间中的位置
+    pub fn trap_cx_user_va(&self) -> usize {
+        trap_cx_position(self.tid)
+    }
+
+    /// TrapContext页的物理页号
+    pub fn trap_cx_ppn(&self) -> PhysPageNum {
+        let process = self.process.upgrade().unwrap();
+        let process_inner = process.inner_exclusive_access();
+        process_inner
+            .memory_set
+            .translate(VirtAddr::from(self.trap_cx_user_va()).into())
+            .unwrap()
+            .ppn()
+    }
+
+    /// 用户栈栈顶
+    pub fn ustack_top(&self) -> usize {
+        user_stack_position(self.ustack_base, self.tid).1
+    }
+
+    /// 判断地址是否位于该线程用户栈下方的保护页中
+    pub fn is_ustack_guard(&self, addr: usize) -> bool {
+        let (ustack_bottom, _) = user_stack_position(self.ustack_base, self.tid);
+        addr < ustack_bottom && addr >= ustack_bottom - PAGE_SIZE
+    }
+}
+
+impl Drop for TaskUserRes {
+    fn drop(&mut self) {
+        // 进程已被回收时，其地址空间与tid随之释放
+        let Some(process) = self.process.upgrade() else {
+            return;
+        };
+        let mut process_inner = process.inner_exclusive_access();
+        process_inner.dealloc_tid(self.tid);
+        let (ustack_bottom, _) = user_stack_position(self.ustack_base, self.tid);
+        process_inner
+            .memory_set
+            .remove_area_with_start_vpn(VirtAddr::from(ustack_bottom).into());
+        process_inner
+            .memory_set
+            .remove_area_with_start_vpn(VirtAddr::from(self.trap_cx_user_va()).into());
+    }
+}
@@ -0,0 +1,160 @@
+//! os/src/task/manager.rs <br>
+//! 任务管理器：维护线程的就绪队列，以及按pid查找所有存活进程的进程表
+
+use alloc::collections::{BTreeMap, VecDeque};
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+use lazy_static::lazy_static;
+
+use crate::sync::UPSafeCell;
+
+use super::{ProcessControlBlock, TaskControlBlock};
+
+/// 就绪队列中的一项
+struct ReadyTask {
+    task: Arc<TaskControlBlock>,
+    /// 任务还需让过的调度次数，为0时才参与按优先级的选择
+    skip: usize,
+}
+
+/// 任务管理器，总是调度有效优先级最高的就绪任务，优先级相同时先进先出 <br>
+/// 主动让出CPU的任务在此后的若干次调度中不参与选择，次数为它让出CPU时就绪队列中的任务数，
+/// 使当时已就绪的任务（即使优先级更低）有机会先运行；
+/// 否则以让出CPU的方式忙等低优先级任务的高优先级任务会被反复选中，使整个系统活锁
+pub struct TaskManager {
+    ready_queue: VecDeque<ReadyTask>,
+}
+
+/// 在按入队顺序排列的就绪任务中选出下一个被调度的任务，返回其下标 <br>
+/// 没有可选的任务时选出剩余让过次数最少的任务，因此队列非空时必有结果
+fn pick(tasks: &[(usize, &Arc<TaskControlBlock>)]) -> Option<usize> {
+    let priority = |task: &Arc<TaskControlBlock>| task.inner_exclusive_access().priority;
+    tasks
+        .iter()
+        .enumerate()
+        .filter(|(_, (skip, _))| *skip == 0)
+        .max_by_key(|(index, (_, task))| (priority(task), Reverse(*index)))
+        .or_else(|| tasks.iter().enumerate().min_by_key(|(_, (skip, _))| *skip))
+        .map(|(index, _)| index)
+}
+
+/// 取出tasks中下标为index的任务，其余任务的让过次数减一
+fn take(tasks: &mut Vec<(usize, &Arc<TaskControlBlock>)>, index: usize) {
+    tasks.remove(index);
+    for (skip, _) in tasks.iter_mut() {
+        *skip = skip.saturating_sub(1);
+    }
+}
+
+impl TaskManager {
+    pub fn new() -> Self {
+        Self {
+            ready_queue: VecDeque::new(),
+        }
+    }
+
+    /// 将任务加入就绪队列
+    pub fn add(&mut self, task: Arc<TaskControlBlock>) {
+        self.ready_queue.push_back(ReadyTask { task, skip: 0 });
+    }
+
+    /// 将主动让出CPU的任务加入就绪队列，它须先让过当前就绪的每个任务各一次调度
+    pub fn add_yielded(&mut self, task: Arc<TaskControlBlock>) {
+        let skip = self.ready_queue.len();
+        self.ready_queue.push_back(ReadyTask { task, skip });
+    }
+
+    /// 从就绪队列中取出一个任务
+    pub fn fetch(&mut self) -> Option<Arc<TaskControlBlock>> {
+        let index = pick(&self.snapshot())?;
+        let ready = self.ready_queue.remove(index)?;
+        for rest in self.ready_queue.iter_mut() {
+            rest.skip = rest.skip.saturating_sub(1);
+        }
+        Some(ready.task)
+    }
+
+    /// 将任务移出就绪队列
+    pub fn remove(&mut self, task: &Arc<TaskControlBlock>) {
+        self.ready_queue
+            .retain(|ready| !Arc::ptr_eq(&ready.task, task));
+    }
+
+    /// 按fetch取出的顺序排列的就绪任务（期间没有任务入队且优先级不变时）
+    pub fn ready_tasks(&self) -> Vec<Arc<TaskControlBlock>> {
+        let mut rest = self.snapshot();
+        let mut tasks = Vec::new();
+        while let Some(index) = pick(&rest) {
+            tasks.push(rest[index].1.clone());
+            take(&mut rest, index);
+        }
+        tasks
+    }
+
+    /// 按入队顺序排列的（让过次数，任务）
+    fn snapshot(&self) -> Vec<(usize, &Arc<TaskControlBlock>)> {
+        self.ready_queue
+            .iter()
+            .map(|ready| (ready.skip, &ready.task))
+            .collect()
+    }
+}
+
+lazy_static! {
+    /// 进程表，以pid为键；进程被回收后对应的项自然失效
+    static ref PROCESS_TABLE: UPSafeCell<BTreeMap<usize, Weak<ProcessControlBlock>>> =
+        unsafe { UPSafeCell::new(BTreeMap::new()) };
+}
+
+lazy_static! {
+    pub static ref TASK_MANAGER: UPSafeCell<TaskManager> =
+        unsafe { UPSafeCell::new(TaskManager::new()) };
+}
+
+/// 将任务加入就绪队列
+pub fn add_task(task: Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().add(task);
+}
+
+/// 将主动让出CPU的任务加入就绪队列
+pub fn add_yielded_task(task: Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().add_yielded(task);
+}
+
+/// 从就绪队列中取出一个任务
+pub fn fetch_task() -> Option<Arc<TaskControlBlock>> {
+    TASK_MANAGER.exclusive_access().fetch()
+}
+
+/// 将任务移出就绪队列
+pub fn remove_task(task: &Arc<TaskControlBlock>) {
+    TASK_MANAGER.exclusive_access().remove(task);
+}
+
+/// 按调度顺序排列的就绪任务
+pub fn ready_tasks() -> Vec<Arc<TaskControlBlock>> {
+    TASK_MANAGER.exclusive_access().ready_tasks()
+}
+
+/// 将新创建的进程加入进程表
+pub fn register_process(process: &Arc<ProcessControlBlock>) {
+    let mut table = PROCESS_TABLE.exclusive_access();
+    table.retain(|_, process| process.strong_count() > 0);
+    table.insert(process.getpid(), Arc::downgrade(process));
+}
+
+/// 按pid查找存活（含尚未回收）的进程
+pub fn find_process(pid: usize) -> Option<Arc<ProcessControlBlock>> {
+    PROCESS_TABLE.exclusive_access().get(&pid)?.upgrade()
+}
+
+/// 按pid排列的所有存活（含尚未回收）的进程
+pub fn all_processes() -> Vec<Arc<ProcessControlBlock>> {
+    PROCESS_TABLE
+        .exclusive_access()
+        .values()
+        .filter_map(Weak::upgrade)
+        .collect()
+}
@@ -0,0 +1,181 @@
+//! os/src/task/mod.rs <br>
+//! 进程与线程管理：每个进程拥有独立的地址空间与文件描述符表，进程通过fork/exec/waitpid创建、替换与回收 <br>
+//! 进程中的线程共享这些资源，各自拥有用户栈、TrapContext页与内核栈，是调度的基本单位
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use lazy_static::lazy_static;
+use log::*;
+
+pub use context::TaskContext;
+pub use manager::{add_task, all_processes, find_process, ready_tasks, register_process};
+use manager::{add_yielded_task, remove_task};
+pub use process::ProcessControlBlock;
+pub use processor::{
+    current_process, current_task, current_trap_cx, current_trap_cx_user_va, current_user_token,
+    run_tasks, switch_count,
+};
+use processor::{schedule, take_current_task};
+pub use task::{TaskControlBlock, TaskStatus};
+
+use crate::fs::{open_file, sync_all, OpenFlags};
+use crate::mm::MapPermission;
+use crate::sbi_call::shutdown;
+use crate::sync::DeadlockDetector;
+
+mod context;
+mod id;
+mod manager;
+mod process;
+mod processor;
+mod switch;
+#[allow(clippy::module_inception)]
+mod task;
+
+lazy_static! {
+    /// 初始进程，负责启动shell并回收孤儿进程
+    pub static ref INITPROC: Arc<ProcessControlBlock> = ProcessControlBlock::new(
+        &open_file("/initproc", OpenFlags::RDONLY)
+            .expect("initproc not found in the file system!")
+            .read_all()
+    );
+}
+
+/// 将初始进程的主线程加入就绪队列
+pub fn add_initproc() {
+    register_process(&INITPROC);
+    add_task(INITPROC.inner_exclusive_access().get_task(0));
+}
+
+/// 挂起当前任务并切换至下一个任务，用于时间片用尽时的抢占
+pub fn suspend_current_and_run_next() {
+    switch_out_current(false);
+}
+
+/// 当前任务主动让出CPU并切换至下一个任务，用于yield与各种以让出CPU的方式进行的等待 <br>
+/// 当前任务须先让过此时就绪的每个任务各一次调度，才会再按优先级参与选择
+pub fn yield_current_and_run_next() {
+    switch_out_current(true);
+}
+
+/// 将当前任务放回就绪队列并切换至下一个任务
+fn switch_out_current(yielded: bool) {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+    task_inner.task_status = TaskStatus::Ready;
+    drop(task_inner); // 释放mut引用
+    if yielded {
+        add_yielded_task(task);
+    } else {
+        add_task(task);
+    }
+    schedule(task_cx_ptr);
+}
+
+/// 阻塞当前任务并切换至下一个任务，调用者需已将其挂入某个等待队列，由wakeup_task唤醒
+pub fn block_current_and_run_next() {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let task_cx_ptr = &mut task_inner.task_cx as *mut TaskContext;
+    task_inner.task_status = TaskStatus::Blocked;
+    drop(task_inner); // 释放mut引用
+    drop(task);
+    schedule(task_cx_ptr);
+}
+
+/// 唤醒被阻塞的任务，将其加入就绪队列
+pub fn wakeup_task(task: Arc<TaskControlBlock>) {
+    task.inner_exclusive_access().task_status = TaskStatus::Ready;
+    add_task(task);
+}
+
+/// 结束当前线程并切换至下一个任务 <br>
+/// 主线程退出时整个进程随之退出：其余线程被一并结束，子进程交由初始进程收养
+pub fn exit_current_and_run_next(exit_code: i32) {
+    let task = take_current_task().unwrap();
+    let mut task_inner = task.inner_exclusive_access();
+    let process = task.process.upgrade().unwrap();
+    let tid = task_inner.res.as_ref().unwrap().tid;
+    task_inner.exit_code = Some(exit_code);
+    // 释放线程的用户栈与TrapContext页，内核栈仍在使用，在线程被回收时释放
+    task_inner.res = None;
+    drop(task_inner); // 释放mut引用
+    drop(task);
+    // 退出的线程不再等待资源
+    process
+        .inner_exclusive_access()
+        .deadlock_detector
+        .remove_thread(tid);
+    if tid == 0 {
+        let pid = process.getpid();
+        if pid == INITPROC.getpid() {
+            info!("[kernel] initproc exited with code {}, shutting down.", exit_code);
+            // 关机前将块缓存中的脏块写回磁盘
+            sync_all();
+            shutdown();
+        }
+        let mut process_inner = process.inner_exclusive_access();
+        process_inner.is_zombie = true;
+        process_inner.exit_code = exit_code;
+        // 将子进程挂到初始进程下
+        {
+            let mut initproc_inner = INITPROC.inner_exclusive_access();
+            for child in process_inner.children.iter() {
+                child.inner_exclusive_access().parent = Some(Arc::downgrade(&INITPROC));
+                initproc_inner.children.push(child.clone());
+            }
+        }
+        process_inner.children.clear();
+        // 结束其余线程：移出就绪队列并收集其资源，资源须在释放进程控制块的引用后回收
+        let mut recycle_res = Vec::new();
+        for task in process_inner.tasks.iter().flatten() {
+            remove_task(task);
+            if let Some(res) = task.inner_exclusive_access().res.take() {
+                recycle_res.push(res);
+            }
+        }
+        drop(process_inner);
+        recycle_res.clear();
+        let mut process_inner = process.inner_exclusive_access();
+        // 关闭进程打开的所有文件，释放同步对象
+        process_inner.fd_table.clear();
+        process_inner.mutex_list.clear();
+        process_inner.semaphore_list.clear();
+        process_inner.condvar_list.clear();
+        process_inner.deadlock_detector = DeadlockDetector::new();
+        process_inner.futex_queues.clear();
+        // 提前回收用户空间的物理页帧，页表、pid与主线程的内核栈在父进程回收时释放
+        process_inner.memory_set.recycle_data_pages();
+        process_inner.tasks.truncate(1);
+    }
+    drop(process);
+    // 当前任务不会再被调度，无需保存其上下文
+    let mut _unused = TaskContext::zero_init();
+    schedule(&mut _unused as *mut _);
+}
+
+/// 获取当前进程的pid
+pub fn current_pid() -> usize {
+    current_process().getpid()
+}
+
+/// 处理当前进程对addr的access访问引发的缺页异常，返回是否已处理
+pub fn handle_current_page_fault(addr: usize, access: MapPermission) -> bool {
+    current_process()
+        .inner_exclusive_access()
+        .memory_set
+        .handle_page_fault(addr.into(), access)
+}
+
+/// 判断地址是否位于当前线程用户栈的保护页中
+pub fn is_current_user_stack_guard(addr: usize) -> bool {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .unwrap()
+        .is_ustack_guard(addr)
+}
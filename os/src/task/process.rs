@@ -0,0 +1,264 @@
+//! os/src/task/process.rs <br>
+//! 进程控制块：进程是资源分配的基本单位，拥有地址空间、文件描述符表与若干线程
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::{Arc, Weak};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cell::RefMut;
+
+use crate::config::MAX_FD;
+use crate::fs::{File, Stdin, Stdout};
+use crate::mm::{KERNEL_SPACE, MemorySet};
+use crate::sync::{Condvar, DeadlockDetector, Mutex, Semaphore, UPSafeCell, WaitQueue};
+use crate::trap::{trap_handler, TrapContext};
+
+use super::id::{pid_alloc, PidHandle, RecycleAllocator};
+use super::TaskControlBlock;
+
+/// 进程控制块
+pub struct ProcessControlBlock {
+    // 创建后不再改变的字段
+    pub pid: PidHandle,
+    // 运行过程中可能改变的字段
+    inner: UPSafeCell<ProcessControlBlockInner>,
+}
+
+pub struct ProcessControlBlockInner {
+    /// 是否已退出但尚未被父进程回收
+    pub is_zombie: bool,
+    /// 应用地址空间
+    pub memory_set: MemorySet,
+    pub parent: Option<Weak<ProcessControlBlock>>,
+    pub children: Vec<Arc<ProcessControlBlock>>,
+    pub exit_code: i32,
+    /// 文件描述符表，下标即文件描述符
+    pub fd_table: Vec<Option<Arc<dyn File>>>,
+    /// 当前工作目录，为规范化的绝对路径
+    pub cwd: String,
+    /// 进程名，即最近一次exec的应用名
+    pub name: String,
+    /// 各系统调用的调用次数，以系统调用号为键
+    pub syscall_counts: BTreeMap<usize, usize>,
+    /// 进程的线程，下标即tid；已退出的线程在被waittid回收前仍保留于此
+    pub tasks: Vec<Option<Arc<TaskControlBlock>>>,
+    /// tid分配器
+    pub task_res_allocator: RecycleAllocator,
+    /// 进程创建的互斥锁、信号量与条件变量，下标即其编号
+    pub mutex_list: Vec<Option<Arc<dyn Mutex>>>,
+    pub semaphore_list: Vec<Option<Arc<Semaphore>>>,
+    pub condvar_list: Vec<Option<Arc<Condvar>>>,
+    /// 互斥锁与信号量的死锁检测，默认不开启
+    pub deadlock_detector: DeadlockDetector,
+    /// 在futex上等待的线程，以futex的用户虚拟地址为键
+    pub futex_queues: BTreeMap<usize, WaitQueue>,
+}
+
+impl ProcessControlBlockInner {
+    pub fn get_user_token(&mut self) -> usize {
+        self.memory_set.user_token()
+    }
+
+    /// 分配最小的空闲文件描述符，描述符均已达到MAX_FD时返回None
+    pub fn alloc_fd(&mut self) -> Option<usize> {
+        if let Some(fd) = (0..self.fd_table.len()).find(|fd| self.fd_table[*fd].is_none()) {
+            Some(fd)
+        } else if self.fd_table.len() < MAX_FD {
+            self.fd_table.push(None);
+            Some(self.fd_table.len() - 1)
+        } else {
+            None
+        }
+    }
+
+    pub fn alloc_tid(&mut self) -> usize {
+        self.task_res_allocator.alloc()
+    }
+
+    pub fn dealloc_tid(&mut self, tid: usize) {
+        self.task_res_allocator.dealloc(tid)
+    }
+
+    /// 尚未退出的线程数
+    pub fn thread_count(&self) -> usize {
+        self.task_res_allocator.allocated()
+    }
+
+    pub fn get_task(&self, tid: usize) -> Arc<TaskControlBlock> {
+        self.tasks[tid].as_ref().unwrap().clone()
+    }
+}
+
+impl ProcessControlBlock {
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, ProcessControlBlockInner> {
+        self.inner.exclusive_access()
+    }
+
+    pub fn getpid(&self) -> usize {
+        self.pid.0
+    }
+
+    /// 解析ELF构造只有主线程的新进程，仅用于创建初始进程
+    pub fn new(elf_data: &[u8]) -> Arc<Self> {
+        let (memory_set, ustack_base, entry_point) = MemorySet::from_elf(elf_data);
+        let process = Arc::new(Self {
+            pid: pid_alloc(),
+            inner: unsafe {
+                UPSafeCell::new(ProcessControlBlockInner {
+                    is_zombie: false,
+                    memory_set,
+                    parent: None,
+                    children: Vec::new(),
+                    exit_code: 0,
+                    fd_table: vec![
+                        // 0 -> 标准输入
+                        Some(Arc::new(Stdin)),
+                        // 1 -> 标准输出
+                        Some(Arc::new(Stdout)),
+                        // 2 -> 标准错误
+                        Some(Arc::new(Stdout)),
+                    ],
+                    cwd: String::from("/"),
+                    name: String::from("initproc"),
+                    syscall_counts: BTreeMap::new(),
+                    tasks: Vec::new(),
+                    task_res_allocator: RecycleAllocator::new(),
+                    mutex_list: Vec::new(),
+                    semaphore_list: Vec::new(),
+                    condvar_list: Vec::new(),
+                    deadlock_detector: DeadlockDetector::new(),
+                    futex_queues: BTreeMap::new(),
+                })
+            },
+        });
+        // 创建主线程并初始化其TrapContext
+        let task = Arc::new(TaskControlBlock::new(&process, ustack_base, true));
+        let task_inner = task.inner_exclusive_access();
+        let ustack_top = task_inner.res.as_ref().unwrap().ustack_top();
+        *task_inner.get_trap_cx() = TrapContext::app_init_context(
+            entry_point,
+            ustack_top,
+            KERNEL_SPACE.exclusive_access().token(),
+            task.kernel_stack.get_top(),
+            trap_handler as usize,
+        );
+        drop(task_inner);
+        process.inner_exclusive_access().tasks.push(Some(task));
+        process
+    }
+
+    /// 以新的ELF替换当前进程的地址空间，原地址空间随之释放，进程名改为name <br>
+    /// 调用者需保证进程中只剩主线程；已退出而未回收的线程被一并丢弃 <br>
+    /// 命令行参数被压入新的用户栈：栈顶之下依次是argv指针数组（以0结尾）与各参数字符串
+    pub fn exec(&self, name: &str, elf_data: &[u8], args: Vec<String>) {
+        let (memory_set, ustack_base, entry_point) = MemorySet::from_elf(elf_data);
+        let mut inner = self.inner_exclusive_access();
+        inner.memory_set = memory_set;
+        inner.name = String::from(name);
+        inner.tasks.truncate(1);
+        inner.mutex_list.clear();
+        inner.semaphore_list.clear();
+        inner.condvar_list.clear();
+        inner.deadlock_detector = DeadlockDetector::new();
+        inner.futex_queues.clear();
+        let task = inner.get_task(0);
+        drop(inner);
+        // 在新地址空间中为主线程重新映射用户栈与TrapContext页
+        let mut task_inner = task.inner_exclusive_access();
+        let res = task_inner.res.as_mut().unwrap();
+        res.ustack_base = ustack_base;
+        res.alloc_user_res();
+        let user_stack_top = res.ustack_top();
+        task_inner.trap_cx_ppn = task_inner.res.as_ref().unwrap().trap_cx_ppn();
+        // 压入argv指针数组，调用者已保证参数能放入用户栈
+        let mut inner = self.inner_exclusive_access();
+        let memory_set = &mut inner.memory_set;
+        let mut user_sp = user_stack_top;
+        user_sp -= (args.len() + 1) * core::mem::size_of::<usize>();
+        let argv_base = user_sp;
+        let mut argv: Vec<_> = (0..=args.len())
+            .map(|arg| {
+                memory_set
+                    .translated_refmut(
+                        (argv_base + arg * core::mem::size_of::<usize>()) as *mut usize,
+                    )
+                    .unwrap()
+            })
+            .collect();
+        *argv[args.len()] = 0;
+        // 压入各参数字符串
+        for (i, arg) in args.iter().enumerate() {
+            user_sp -= arg.len() + 1;
+            *argv[i] = user_sp;
+            let mut p = user_sp;
+            for c in arg.as_bytes() {
+                *memory_set.translated_refmut(p as *mut u8).unwrap() = *c;
+                p += 1;
+            }
+            *memory_set.translated_refmut(p as *mut u8).unwrap() = 0;
+        }
+        drop(inner);
+        // 保持栈指针按字长对齐
+        user_sp -= user_sp % core::mem::size_of::<usize>();
+
+        let trap_cx = task_inner.get_trap_cx();
+        *trap_cx = TrapContext::app_init_context(
+            entry_point,
+            user_sp,
+            KERNEL_SPACE.exclusive_access().token(),
+            task.kernel_stack.get_top(),
+            trap_handler as usize,
+        );
+        trap_cx.x[10] = args.len();
+        trap_cx.x[11] = argv_base;
+    }
+
+    /// 复制当前进程，子进程拥有独立的地址空间副本、pid，以及复制自主线程的唯一线程 <br>
+    /// 子进程不继承互斥锁等同步对象及死锁检测的设置；调用者需保证进程中只剩主线程
+    pub fn fork(self: &Arc<Self>) -> Arc<Self> {
+        let mut parent_inner = self.inner_exclusive_access();
+        let memory_set = MemorySet::from_existed_user(&parent_inner.memory_set);
+        // 子进程继承父进程打开的所有文件
+        let fd_table = parent_inner.fd_table.clone();
+        let child = Arc::new(Self {
+            pid: pid_alloc(),
+            inner: unsafe {
+                UPSafeCell::new(ProcessControlBlockInner {
+                    is_zombie: false,
+                    memory_set,
+                    parent: Some(Arc::downgrade(self)),
+                    children: Vec::new(),
+                    exit_code: 0,
+                    fd_table,
+                    cwd: parent_inner.cwd.clone(),
+                    name: parent_inner.name.clone(),
+                    syscall_counts: BTreeMap::new(),
+                    tasks: Vec::new(),
+                    task_res_allocator: RecycleAllocator::new(),
+                    mutex_list: Vec::new(),
+                    semaphore_list: Vec::new(),
+                    condvar_list: Vec::new(),
+                    deadlock_detector: DeadlockDetector::new(),
+                    futex_queues: BTreeMap::new(),
+                })
+            },
+        });
+        parent_inner.children.push(Arc::clone(&child));
+        let parent_task = parent_inner.get_task(0);
+        drop(parent_inner);
+        let parent_task_inner = parent_task.inner_exclusive_access();
+        let ustack_base = parent_task_inner.res.as_ref().unwrap().ustack_base;
+        let base_priority = parent_task_inner.base_priority;
+        drop(parent_task_inner);
+        // 主线程的用户栈与TrapContext页已随地址空间复制，只需修改其内核栈；优先级继承自父进程的主线程
+        let task = Arc::new(TaskControlBlock::new(&child, ustack_base, false));
+        let mut task_inner = task.inner_exclusive_access();
+        task_inner.base_priority = base_priority;
+        task_inner.priority = base_priority;
+        task_inner.get_trap_cx().kernel_sp = task.kernel_stack.get_top();
+        drop(task_inner);
+        child.inner_exclusive_access().tasks.push(Some(task));
+        child
+    }
+}
@@ -0,0 +1,130 @@
+//! os/src/task/processor.rs <br>
+//! 处理器：记录当前正在运行的任务，并在idle控制流中进行调度
+
+use alloc::sync::Arc;
+
+use lazy_static::lazy_static;
+use log::*;
+
+use crate::sync::UPSafeCell;
+use crate::trap::TrapContext;
+
+use super::manager::fetch_task;
+use super::switch::__switch;
+use super::task::TaskStatus;
+use super::{ProcessControlBlock, TaskContext, TaskControlBlock};
+
+/// 处理器状态
+pub struct Processor {
+    /// 当前正在运行的任务
+    current: Option<Arc<TaskControlBlock>>,
+    /// idle控制流的任务上下文，运行在启动栈上
+    idle_task_cx: TaskContext,
+    /// 切换到任务的总次数
+    switches: usize,
+}
+
+impl Processor {
+    pub fn new() -> Self {
+        Self {
+            current: None,
+            idle_task_cx: TaskContext::zero_init(),
+            switches: 0,
+        }
+    }
+
+    fn get_idle_task_cx_ptr(&mut self) -> *mut TaskContext {
+        &mut self.idle_task_cx as *mut _
+    }
+
+    /// 取出当前任务
+    pub fn take_current(&mut self) -> Option<Arc<TaskControlBlock>> {
+        self.current.take()
+    }
+
+    /// 获取当前任务的一个引用
+    pub fn current(&self) -> Option<Arc<TaskControlBlock>> {
+        self.current.as_ref().map(Arc::clone)
+    }
+}
+
+lazy_static! {
+    pub static ref PROCESSOR: UPSafeCell<Processor> = unsafe { UPSafeCell::new(Processor::new()) };
+}
+
+/// idle控制流：不断从就绪队列中取出任务并切换过去
+pub fn run_tasks() -> ! {
+    loop {
+        let mut processor = PROCESSOR.exclusive_access();
+        if let Some(task) = fetch_task() {
+            let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+            let mut task_inner = task.inner_exclusive_access();
+            let next_task_cx_ptr = &task_inner.task_cx as *const TaskContext;
+            task_inner.task_status = TaskStatus::Running;
+            drop(task_inner); // 释放mut引用
+            trace!("Switching to pid {}...", task.getpid());
+            processor.current = Some(task);
+            processor.switches += 1;
+            drop(processor); // 释放mut引用
+            unsafe {
+                __switch(idle_task_cx_ptr, next_task_cx_ptr);
+            }
+        }
+    }
+}
+
+/// 取出当前任务
+pub fn take_current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().take_current()
+}
+
+/// 切换到任务的总次数
+pub fn switch_count() -> usize {
+    PROCESSOR.exclusive_access().switches
+}
+
+/// 获取当前任务
+pub fn current_task() -> Option<Arc<TaskControlBlock>> {
+    PROCESSOR.exclusive_access().current()
+}
+
+/// 获取当前任务所属的进程
+pub fn current_process() -> Arc<ProcessControlBlock> {
+    current_task().unwrap().process.upgrade().unwrap()
+}
+
+/// 获取当前进程地址空间的satp
+pub fn current_user_token() -> usize {
+    let process = current_process();
+    let token = process.inner_exclusive_access().get_user_token();
+    token
+}
+
+/// 获取当前任务的TrapContext
+pub fn current_trap_cx() -> &'static mut TrapContext {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .get_trap_cx()
+}
+
+/// 获取当前任务的TrapContext在应用地址空间中的位置
+pub fn current_trap_cx_user_va() -> usize {
+    current_task()
+        .unwrap()
+        .inner_exclusive_access()
+        .res
+        .as_ref()
+        .unwrap()
+        .trap_cx_user_va()
+}
+
+/// 从当前任务切换回idle控制流，task_cx_ptr用于保存当前任务的上下文
+pub fn schedule(switched_task_cx_ptr: *mut TaskContext) {
+    let mut processor = PROCESSOR.exclusive_access();
+    let idle_task_cx_ptr = processor.get_idle_task_cx_ptr();
+    drop(processor); // 释放mut引用
+    unsafe {
+        __switch(switched_task_cx_ptr, idle_task_cx_ptr);
+    }
+}
@@ -0,0 +1,13 @@
+//! os/src/task/switch.rs <br>
+//! __switch的Rust封装
+
+use core::arch::global_asm;
+
+use super::TaskContext;
+
+global_asm!(include_str!("switch.S"));
+
+extern "C" {
+    /// 保存当前任务上下文至`current_task_cx_ptr`，并切换至`next_task_cx_ptr`所指的任务上下文
+    pub fn __switch(current_task_cx_ptr: *mut TaskContext, next_task_cx_ptr: *const TaskContext);
+}
@@ -0,0 +1,135 @@
+//! os/src/task/task.rs <br>
+//! 线程控制块：线程是调度的基本单位，同一进程的线程共享地址空间与文件描述符表
+
+use alloc::sync::{Arc, Weak};
+use alloc::vec::Vec;
+use core::cell::RefMut;
+
+use crate::config::DEFAULT_PRIORITY;
+use crate::mm::PhysPageNum;
+use crate::sync::UPSafeCell;
+use crate::trap::TrapContext;
+
+use super::id::{kstack_alloc, KernelStack, TaskUserRes};
+use super::{ProcessControlBlock, TaskContext};
+
+/// 线程状态
+#[derive(Copy, Clone, PartialEq)]
+pub enum TaskStatus {
+    /// 准备运行
+    Ready,
+    /// 正在运行
+    Running,
+    /// 在等待队列中等待被唤醒
+    Blocked,
+}
+
+/// 线程控制块
+pub struct TaskControlBlock {
+    // 创建后不再改变的字段
+    /// 所属进程
+    pub process: Weak<ProcessControlBlock>,
+    pub kernel_stack: KernelStack,
+    // 运行过程中可能改变的字段
+    inner: UPSafeCell<TaskControlBlockInner>,
+}
+
+pub struct TaskControlBlockInner {
+    /// 线程在进程中的资源，线程退出时释放
+    pub res: Option<TaskUserRes>,
+    /// TrapContext所在的物理页号，内核通过恒等映射直接访问
+    pub trap_cx_ppn: PhysPageNum,
+    pub task_cx: TaskContext,
+    pub task_status: TaskStatus,
+    /// 线程退出后为其退出码
+    pub exit_code: Option<i32>,
+    /// 线程自身的优先级
+    pub base_priority: usize,
+    /// 调度使用的有效优先级：自身优先级与等待其持有的阻塞锁的线程的有效优先级中的最大值
+    pub priority: usize,
+    /// 在等待该线程持有的阻塞锁的线程
+    pub lock_waiters: Vec<Weak<TaskControlBlock>>,
+    /// 该线程在等待的阻塞锁的持有者
+    pub lock_owner: Option<Weak<TaskControlBlock>>,
+}
+
+impl TaskControlBlockInner {
+    pub fn get_trap_cx(&self) -> &'static mut TrapContext {
+        self.trap_cx_ppn.get_mut()
+    }
+}
+
+impl TaskControlBlock {
+    pub fn inner_exclusive_access(&self) -> RefMut<'_, TaskControlBlockInner> {
+        self.inner.exclusive_access()
+    }
+
+    /// 为进程创建新线程并分配内核栈，alloc_user_res含义同TaskUserRes::new <br>
+    /// 新线程的TrapContext由调用者初始化
+    pub fn new(
+        process: &Arc<ProcessControlBlock>,
+        ustack_base: usize,
+        alloc_user_res: bool,
+    ) -> Self {
+        let res = TaskUserRes::new(process, ustack_base, alloc_user_res);
+        let trap_cx_ppn = res.trap_cx_ppn();
+        let kernel_stack = kstack_alloc();
+        let kernel_stack_top = kernel_stack.get_top();
+        Self {
+            process: Arc::downgrade(process),
+            kernel_stack,
+            inner: unsafe {
+                UPSafeCell::new(TaskControlBlockInner {
+                    res: Some(res),
+                    trap_cx_ppn,
+                    task_cx: TaskContext::goto_trap_return(kernel_stack_top),
+                    task_status: TaskStatus::Ready,
+                    exit_code: None,
+                    base_priority: DEFAULT_PRIORITY,
+                    priority: DEFAULT_PRIORITY,
+                    lock_waiters: Vec::new(),
+                    lock_owner: None,
+                })
+            },
+        }
+    }
+
+    /// 所属进程的pid
+    pub fn getpid(&self) -> usize {
+        self.process.upgrade().unwrap().getpid()
+    }
+
+    /// 设置线程自身的优先级，有效优先级随之更新
+    pub fn set_base_priority(self: &Arc<Self>, priority: usize) {
+        self.inner_exclusive_access().base_priority = priority;
+        self.update_priority();
+    }
+
+    /// 重新计算线程的有效优先级；有效优先级改变且线程正等待阻塞锁时，沿持有者的链条继续传递 <br>
+    /// 调用者需保证此时未持有链条上任何线程控制块的可变引用
+    pub fn update_priority(self: &Arc<Self>) {
+        let mut task = Arc::clone(self);
+        loop {
+            let inner = task.inner_exclusive_access();
+            let base_priority = inner.base_priority;
+            let waiters = inner.lock_waiters.clone();
+            drop(inner);
+            let priority = waiters
+                .iter()
+                .filter_map(Weak::upgrade)
+                .map(|waiter| waiter.inner_exclusive_access().priority)
+                .fold(base_priority, usize::max);
+            let mut inner = task.inner_exclusive_access();
+            // 有效优先级未变时链条上后续线程也无需更新，锁的循环等待也因此不会无限传递
+            if inner.priority == priority {
+                return;
+            }
+            inner.priority = priority;
+            let Some(owner) = inner.lock_owner.as_ref().and_then(Weak::upgrade) else {
+                return;
+            };
+            drop(inner);
+            task = owner;
+        }
+    }
+}
@@ -0,0 +1,26 @@
+//! os/src/timer.rs <br>
+//! 时钟与时钟中断
+
+use riscv::register::time;
+
+use crate::config::CLOCK_FREQ;
+use crate::sbi_call::set_timer;
+
+/// 每秒触发的时钟中断次数，即时间片长度为10ms
+const TICKS_PER_SEC: usize = 100;
+const USEC_PER_SEC: usize = 1_000_000;
+
+/// 读取mtime计数器
+pub fn get_time() -> usize {
+    time::read()
+}
+
+/// 获取以微秒为单位的当前时间
+pub fn get_time_us() -> usize {
+    time::read() * USEC_PER_SEC / CLOCK_FREQ
+}
+
+/// 设置下一次时钟中断
+pub fn set_next_trigger() {
+    set_timer(get_time() + CLOCK_FREQ / TICKS_PER_SEC);
+}
@@ -1,7 +1,8 @@
 use log::trace;
 use riscv::register::sstatus::{self, SPP, Sstatus};
 
-/// 结构体TrapContext，用于保存寄存器和CSR
+/// 结构体TrapContext，用于保存寄存器和CSR <br>
+/// 该结构体位于应用地址空间中各线程的TrapContext页中，字段顺序与trap.S中的偏移量一一对应
 #[repr(C)]
 pub struct TrapContext {
     /// 通用寄存器x0-x31
@@ -10,6 +11,12 @@ pub struct TrapContext {
     pub sstatus: Sstatus,
     /// CSR sepc
     pub sepc: usize,
+    /// 内核地址空间的satp
+    pub kernel_satp: usize,
+    /// 该应用内核栈的栈顶（内核地址空间中的虚拟地址）
+    pub kernel_sp: usize,
+    /// trap_handler的入口地址（内核地址空间中的虚拟地址）
+    pub trap_handler: usize,
 }
 
 impl TrapContext {
@@ -19,16 +26,25 @@ impl TrapContext {
     }
 
     /// 构造函数，初始化应用程序的TrapContext
-    pub fn app_init_context(entry: usize, sp: usize) -> Self {
+    pub fn app_init_context(
+        entry: usize,
+        sp: usize,
+        kernel_satp: usize,
+        kernel_sp: usize,
+        trap_handler: usize,
+    ) -> Self {
         trace!("app_init_context: entry = {:#x}, sp = {:#x}", entry, sp);
         let mut sstatus = sstatus::read(); // CSR sstatus
-        sstatus.set_spp(SPP::User); // 设置sstatus的SPP位为1，表示当前运行在用户态
+        sstatus.set_spp(SPP::User); // 设置sstatus的SPP位为0，表示trap返回后运行在用户态
         let mut cx = Self {
             x: [0; 32],
             sstatus,
             sepc: entry, // 应用程序的入口地址
+            kernel_satp,
+            kernel_sp,
+            trap_handler,
         };
         cx.set_sp(sp); // 设置应用程序的栈指针
         cx // 返回初始化的TrapContext
     }
-}
\ No newline at end of file
+}
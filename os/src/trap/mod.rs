@@ -1,16 +1,23 @@
-use core::arch::global_asm;
+use core::arch::{asm, global_asm};
 
 use log::{error, warn};
 use riscv::register::{
     mtvec::TrapMode,
-    scause::{self, Exception, Trap},
-    stval, stvec,
+    scause::{self, Exception, Interrupt, Trap},
+    sepc, sie, stval, stvec,
 };
 
 pub use context::TrapContext;
 
-use crate::batch::run_next_app;
+use crate::config::{kernel_stack_guard_owner, TRAMPOLINE};
+use crate::mm::{asid_enabled, MapPermission};
 use crate::syscall::syscall;
+use crate::task::{
+    current_pid, current_trap_cx, current_trap_cx_user_va, current_user_token,
+    exit_current_and_run_next, handle_current_page_fault, is_current_user_stack_guard,
+    suspend_current_and_run_next,
+};
+use crate::timer::set_next_trigger;
 
 mod context;
 
@@ -18,32 +25,97 @@ global_asm!(include_str!("trap.S"));
 
 /// 初始化中断处理
 pub fn init() {
-    extern "C" { fn __alltraps(); }
+    set_kernel_trap_entry();
+}
+
+/// 启用S模式时钟中断
+pub fn enable_timer_interrupt() {
+    unsafe {
+        sie::set_stimer();
+    }
+}
+
+/// 内核态下发生trap时进入__kerneltrap，由其切换至内核trap栈后调用trap_from_kernel
+fn set_kernel_trap_entry() {
+    extern "C" {
+        fn __kerneltrap();
+    }
     unsafe {
-        stvec::write(__alltraps as usize, TrapMode::Direct);
+        stvec::write(__kerneltrap as usize, TrapMode::Direct);
+    }
+}
+
+/// 用户态下发生trap时进入跳板页中的__alltraps
+fn set_user_trap_entry() {
+    unsafe {
+        stvec::write(TRAMPOLINE, TrapMode::Direct);
     }
 }
 
 /// 中断处理函数
 #[no_mangle]
-pub fn trap_handler(cx: &mut TrapContext) -> &mut TrapContext {
+pub fn trap_handler() -> ! {
+    set_kernel_trap_entry();
     let scause = scause::read();    // 获取中断原因
     let stval = stval::read();          // 获取stval寄存器的值(额外参数)
     match scause.cause() {
         Trap::Exception(Exception::UserEnvCall) => {
             // 来自用户程序的系统调用
+            let mut cx = current_trap_cx();
             cx.sepc += 4;
-            cx.x[10] = syscall(cx.x[17], [cx.x[10], cx.x[11], cx.x[12]]) as usize;
+            let result = syscall(
+                cx.x[17],
+                [cx.x[10], cx.x[11], cx.x[12], cx.x[13], cx.x[14], cx.x[15]],
+            );
+            // sys_exec会替换地址空间，需重新获取TrapContext
+            cx = current_trap_cx();
+            cx.x[10] = result as usize;
         }
-        Trap::Exception(Exception::StoreFault) | Trap::Exception(Exception::StorePageFault) => {
-            // 来自用户程序的内存访问异常
-            warn!("PageFault in application, kernel killed it.");
-            run_next_app();
+        Trap::Exception(Exception::StoreFault)
+        | Trap::Exception(Exception::StorePageFault)
+        | Trap::Exception(Exception::LoadFault)
+        | Trap::Exception(Exception::LoadPageFault)
+        | Trap::Exception(Exception::InstructionPageFault) => {
+            // 来自用户程序的内存访问异常，先尝试按需映射（如mmap的区域）
+            let access = match scause.cause() {
+                Trap::Exception(Exception::StorePageFault) => Some(MapPermission::W),
+                Trap::Exception(Exception::LoadPageFault) => Some(MapPermission::R),
+                Trap::Exception(Exception::InstructionPageFault) => Some(MapPermission::X),
+                _ => None,
+            };
+            if access.is_some_and(|access| handle_current_page_fault(stval, access)) {
+                trap_return();
+            }
+            let cx = current_trap_cx();
+            if is_current_user_stack_guard(stval) {
+                warn!(
+                    "Stack overflow in process {}, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+                    current_pid(),
+                    stval,
+                    cx.sepc
+                );
+            } else {
+                warn!(
+                    "PageFault in process {}, bad addr = {:#x}, bad instruction = {:#x}, kernel killed it.",
+                    current_pid(),
+                    stval,
+                    cx.sepc
+                );
+            }
+            exit_current_and_run_next(-2);
         }
         Trap::Exception(Exception::IllegalInstruction) => {
             // 来自用户程序的非法指令
-            warn!("IllegalInstruction in application, kernel killed it.");
-            run_next_app();
+            warn!(
+                "IllegalInstruction in process {}, kernel killed it.",
+                current_pid()
+            );
+            exit_current_and_run_next(-3);
+        }
+        Trap::Interrupt(Interrupt::SupervisorTimer) => {
+            // 时间片用尽，切换至下一个任务
+            set_next_trigger();
+            suspend_current_and_run_next();
         }
         _ => {
             // 无法处理的中断
@@ -52,9 +124,83 @@ pub fn trap_handler(cx: &mut TrapContext) -> &mut TrapContext {
                 scause.cause(),
                 stval
             );
-            run_next_app();
+            exit_current_and_run_next(-1);
         }
     }
-    cx
+    trap_return();
+}
+
+/// 从内核返回用户态：通过跳板页中的__restore切换至应用地址空间并恢复当前线程的TrapContext
+#[no_mangle]
+pub fn trap_return() -> ! {
+    set_user_trap_entry();
+    let trap_cx_ptr = current_trap_cx_user_va();
+    let user_satp = current_user_token();
+    let need_flush = !asid_enabled() as usize;
+    extern "C" {
+        fn __alltraps();
+        fn __restore();
+    }
+    // __restore在跳板页中的虚拟地址
+    let restore_va = __restore as usize - __alltraps as usize + TRAMPOLINE;
+    unsafe {
+        asm!(
+            "fence.i",
+            "jr {restore_va}",
+            restore_va = in(reg) restore_va,
+            in("a0") trap_cx_ptr,
+            in("a1") user_satp,
+            in("a2") need_flush,
+            options(noreturn)
+        );
+    }
 }
 
+/// 内核态trap处理函数，目前内核不应发生任何trap <br>
+/// 该函数运行在独立的内核trap栈上，参数为发生trap时的内核栈指针
+#[no_mangle]
+pub fn trap_from_kernel(kernel_sp: usize) -> ! {
+    extern "C" {
+        fn stext();
+        fn erodata();
+        fn boot_stack_guard();
+        fn boot_stack_lower_bound();
+    }
+    let scause = scause::read();
+    let stval = stval::read();
+    if let Trap::Exception(
+        Exception::StoreFault
+        | Exception::StorePageFault
+        | Exception::LoadFault
+        | Exception::LoadPageFault,
+    ) = scause.cause()
+    {
+        // 访问内核栈的保护页，说明发生了内核栈溢出
+        if let Some(id) = kernel_stack_guard_owner(stval) {
+            panic!(
+                "Kernel stack {} overflow! sp = {:#x}, stval = {:#x}",
+                id, kernel_sp, stval
+            );
+        }
+        if (boot_stack_guard as usize..boot_stack_lower_bound as usize).contains(&stval) {
+            panic!(
+                "Boot stack overflow! sp = {:#x}, stval = {:#x}",
+                kernel_sp, stval
+            );
+        }
+        // 内核代码段与只读数据段不可写
+        if (stext as usize..erodata as usize).contains(&stval) {
+            panic!(
+                "Write to read-only kernel memory! sepc = {:#x}, stval = {:#x}",
+                sepc::read(),
+                stval
+            );
+        }
+    }
+    panic!(
+        "A trap from kernel! scause = {:?}, stval = {:#x}, sp = {:#x}",
+        scause.cause(),
+        stval,
+        kernel_sp
+    );
+}
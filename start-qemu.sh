@@ -2,6 +2,7 @@
 
 # Check running param
 gdb_flag=false
+fat_drive=()
 
 KERNEL_SOURCE_PATH="./os"
 USER_LIB_SOURCE_PATH="./user"
@@ -11,9 +12,13 @@ for i in "$@"; do
   "--wait-gdb")
     gdb_flag=true
     ;;
+  --fat-img=*)
+    fat_drive=(-drive "file=${i#*=},if=none,format=raw,id=x1" -device virtio-blk-device,drive=x1,bus=virtio-mmio-bus.1)
+    ;;
   *)
     echo "args:"
-    echo "    --wait-gdb  Start QEMU and wait for GDB request."
+    echo "    --wait-gdb        Start QEMU and wait for GDB request."
+    echo "    --fat-img=IMAGE   Attach a FAT32 image, mounted read-only at /fat."
     exit 1
     ;;
   esac
@@ -28,11 +33,17 @@ if [ $gdb_flag == true ]; then
     -nographic \
     -bios ./bootloader/rustsbi-qemu.bin \
     -device loader,file='os/target/riscv64gc-unknown-none-elf/release/os.bin',addr=0x80200000 \
+    -drive file='os/target/fs.img',if=none,format=raw,id=x0 \
+    -device virtio-blk-device,drive=x0,bus=virtio-mmio-bus.0 \
+    "${fat_drive[@]}" \
     -s -S
 elif [ $gdb_flag == false ]; then
   qemu-system-riscv64 \
     -machine virt \
     -nographic \
     -bios ./bootloader/rustsbi-qemu.bin \
-    -device loader,file='os/target/riscv64gc-unknown-none-elf/release/os.bin',addr=0x80200000
+    -device loader,file='os/target/riscv64gc-unknown-none-elf/release/os.bin',addr=0x80200000 \
+    -drive file='os/target/fs.img',if=none,format=raw,id=x0 \
+    -device virtio-blk-device,drive=x0,bus=virtio-mmio-bus.0 \
+    "${fat_drive[@]}"
 fi
\ No newline at end of file
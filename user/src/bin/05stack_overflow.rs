@@ -0,0 +1,23 @@
+//! user/src/bin/05stack_overflow.rs
+//! 实验：用户栈溢出
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+// 无终止条件的递归，每层占用一段栈空间，最终越过用户栈进入保护页
+#[allow(unconditional_recursion)]
+fn recurse(depth: usize) -> usize {
+    let buf = [depth; 64];
+    core::hint::black_box(&buf);
+    recurse(depth + 1) + buf[depth % 64]
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test stack_overflow, we will recurse without end...");
+    println!("Kernel should report a stack overflow and kill this application!");
+    recurse(0) as i32
+}
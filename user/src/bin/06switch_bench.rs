@@ -0,0 +1,44 @@
+//! user/src/bin/06switch_bench.rs
+//! 实验：任务切换开销测试
+//! 启动07yield_partner作为子进程并与其交替让出CPU，统计yield往返（本任务→对方任务→本任务）的耗时。
+//! 往返中包含两次任务切换与两次地址空间切换；最小值反映不受其他任务干扰时的切换开销。
+//! 分别使用默认配置与`make run ASID=off`运行，即可对比启用ASID前后的切换开销。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{exec, fork, get_time_us, waitpid, yield_};
+
+const ROUNDS: usize = 10000;
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test switch_bench, yield {} times...", ROUNDS);
+    let partner = fork();
+    if partner == 0 {
+        exec("07yield_partner\0", &[core::ptr::null::<u8>()]);
+        panic!("Cannot exec 07yield_partner!");
+    }
+    let mut min_us = usize::MAX;
+    let begin = get_time_us();
+    for _ in 0..ROUNDS {
+        let start = get_time_us();
+        yield_();
+        let round_trip = get_time_us() - start;
+        min_us = min_us.min(round_trip);
+    }
+    let total_us = get_time_us() - begin;
+    println!(
+        "switch_bench: yield round trip min = {}us, avg = {}us over {} rounds",
+        min_us,
+        total_us / ROUNDS,
+        ROUNDS
+    );
+    let mut exit_code = 0;
+    waitpid(partner as usize, &mut exit_code);
+    println!("Test switch_bench OK!");
+    0
+}
@@ -0,0 +1,22 @@
+//! user/src/bin/07yield_partner.rs
+//! 实验：任务切换开销测试的陪跑任务，持续让出CPU
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::yield_;
+
+/// 略多于06switch_bench的轮数，保证其测量期间始终有对方任务可切换
+const ROUNDS: usize = 12000;
+
+#[no_mangle]
+fn main() -> i32 {
+    for _ in 0..ROUNDS {
+        yield_();
+    }
+    println!("Test yield_partner OK!");
+    0
+}
@@ -0,0 +1,75 @@
+//! user/src/bin/08file_test.rs
+//! 实验：文件读写测试
+//! 依次检验CREATE/TRUNC/APPEND标志、读写权限检查与关闭无效文件描述符。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, open, read, write, OpenFlags};
+
+const FILE: &str = "filea\0";
+
+/// 读出文件的全部内容至buf，返回读取的字节数
+fn read_file(buf: &mut [u8]) -> usize {
+    let fd = open(FILE, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let fd = fd as usize;
+    let mut total = 0;
+    loop {
+        let len = read(fd, &mut buf[total..]);
+        assert!(len >= 0);
+        if len == 0 {
+            break;
+        }
+        total += len as usize;
+    }
+    close(fd);
+    total
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test file_test...");
+    let mut buf = [0u8; 64];
+
+    // 创建并截断文件后写入
+    let fd = open(FILE, OpenFlags::CREATE | OpenFlags::TRUNC | OpenFlags::WRONLY);
+    assert!(fd >= 0);
+    let fd = fd as usize;
+    assert_eq!(write(fd, b"Hello, "), 7);
+    // 只写打开的文件不可读
+    assert_eq!(read(fd, &mut buf), -1);
+    close(fd);
+    let len = read_file(&mut buf);
+    assert_eq!(&buf[..len], b"Hello, ");
+
+    // 追加写入
+    let fd = open(FILE, OpenFlags::WRONLY | OpenFlags::APPEND);
+    assert!(fd >= 0);
+    let fd = fd as usize;
+    assert_eq!(write(fd, b"world!"), 6);
+    close(fd);
+    let len = read_file(&mut buf);
+    assert_eq!(&buf[..len], b"Hello, world!");
+
+    // 只读打开的文件不可写
+    let fd = open(FILE, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    assert_eq!(write(fd as usize, b"x"), -1);
+    close(fd as usize);
+
+    // 截断后文件为空
+    let fd = open(FILE, OpenFlags::WRONLY | OpenFlags::TRUNC);
+    assert!(fd >= 0);
+    close(fd as usize);
+    assert_eq!(read_file(&mut buf), 0);
+
+    // 不带CREATE打开不存在的文件失败，关闭无效的文件描述符失败
+    assert_eq!(open("no_such_file\0", OpenFlags::RDONLY), -1);
+    assert_eq!(close(fd as usize), -1);
+    println!("Test file_test OK!");
+    0
+}
@@ -0,0 +1,78 @@
+//! user/src/bin/09pipe_test.rs
+//! 实验：管道测试
+//! 父进程通过管道向子进程发送一段超过管道缓冲区大小的数据，子进程校验后经另一个管道回传校验和；
+//! 并检验所有写端关闭后读端读到EOF。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, fork, pipe, read, waitpid, write};
+
+/// 发送的数据长度，大于内核管道缓冲区以检验读写阻塞
+const LENGTH: usize = 3000;
+
+fn byte_at(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test pipe_test...");
+    // down_pipe: 父进程 -> 子进程；up_pipe: 子进程 -> 父进程
+    let mut down_pipe = [0usize; 2];
+    let mut up_pipe = [0usize; 2];
+    assert_eq!(pipe(&mut down_pipe), 0);
+    assert_eq!(pipe(&mut up_pipe), 0);
+    let pid = fork();
+    if pid == 0 {
+        // 子进程关闭不使用的一端
+        close(down_pipe[1]);
+        close(up_pipe[0]);
+        let mut buffer = [0u8; 256];
+        let mut received = 0usize;
+        let mut sum = 0usize;
+        loop {
+            let len = read(down_pipe[0], &mut buffer);
+            assert!(len >= 0);
+            if len == 0 {
+                // 父进程关闭写端后读到EOF
+                break;
+            }
+            for &byte in buffer[..len as usize].iter() {
+                assert_eq!(byte, byte_at(received));
+                sum += byte as usize;
+                received += 1;
+            }
+        }
+        assert_eq!(received, LENGTH);
+        close(down_pipe[0]);
+        assert_eq!(write(up_pipe[1], &sum.to_le_bytes()), 8);
+        close(up_pipe[1]);
+        0
+    } else {
+        close(down_pipe[0]);
+        close(up_pipe[1]);
+        let mut data = [0u8; LENGTH];
+        let mut expected_sum = 0usize;
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = byte_at(i);
+            expected_sum += *byte as usize;
+        }
+        assert_eq!(write(down_pipe[1], &data), LENGTH as isize);
+        close(down_pipe[1]);
+        let mut sum_bytes = [0u8; 8];
+        assert_eq!(read(up_pipe[0], &mut sum_bytes), 8);
+        // 子进程已关闭写端，再次读取得到EOF
+        assert_eq!(read(up_pipe[0], &mut sum_bytes), 0);
+        close(up_pipe[0]);
+        assert_eq!(usize::from_le_bytes(sum_bytes), expected_sum);
+        let mut exit_code = 0;
+        assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+        assert_eq!(exit_code, 0);
+        println!("Test pipe_test OK!");
+        0
+    }
+}
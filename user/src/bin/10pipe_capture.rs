@@ -0,0 +1,71 @@
+//! user/src/bin/10pipe_capture.rs
+//! 实验：捕获子进程的输出
+//! 子进程用dup2将标准输出重定向至管道写端后执行00hello_world，父进程从管道读端读出其全部输出并校验；
+//! 同时检验dup分配最小的空闲文件描述符、dup2拒绝过大的目标描述符，以及文件描述符耗尽时dup与pipe失败。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup, dup2, exec, fork, pipe, read, waitpid};
+
+const EXPECTED: &[u8] = b"Hello world from user program.\n";
+/// 文件描述符的上界，与内核的MAX_FD一致
+const MAX_FD: usize = 1024;
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test pipe_capture...");
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    // dup返回最小的空闲文件描述符
+    let fd = dup(pipe_fd[0]);
+    assert_eq!(fd as usize, pipe_fd[1] + 1);
+    assert_eq!(close(fd as usize), 0);
+    assert_eq!(dup(100), -1);
+    // dup2的目标描述符有上界
+    assert_eq!(dup2(pipe_fd[0], usize::MAX), -1);
+    assert_eq!(dup2(pipe_fd[0], 1 << 20), -1);
+    // 耗尽文件描述符后dup失败；只剩一个空闲描述符时pipe失败且不占用它
+    for fd in pipe_fd[1] + 1..MAX_FD {
+        assert_eq!(dup(0), fd as isize);
+    }
+    assert_eq!(dup(0), -1);
+    assert_eq!(close(MAX_FD - 1), 0);
+    let mut full_fd = [0usize; 2];
+    assert_eq!(pipe(&mut full_fd), -1);
+    assert_eq!(dup(0), (MAX_FD - 1) as isize);
+    for fd in pipe_fd[1] + 1..MAX_FD {
+        assert_eq!(close(fd), 0);
+    }
+
+    let pid = fork();
+    if pid == 0 {
+        close(pipe_fd[0]);
+        // 标准输出 -> 管道写端
+        assert_eq!(dup2(pipe_fd[1], 1), 1);
+        close(pipe_fd[1]);
+        exec("00hello_world\0", &[core::ptr::null::<u8>()]);
+        panic!("Cannot exec 00hello_world!");
+    }
+    close(pipe_fd[1]);
+    let mut output = [0u8; 128];
+    let mut len = 0usize;
+    loop {
+        let n = read(pipe_fd[0], &mut output[len..]);
+        assert!(n >= 0);
+        if n == 0 {
+            break;
+        }
+        len += n as usize;
+    }
+    close(pipe_fd[0]);
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(&output[..len], EXPECTED);
+    println!("Test pipe_capture OK!");
+    0
+}
@@ -0,0 +1,109 @@
+//! user/src/bin/11dir_test.rs
+//! 实验：目录操作测试
+//! 检验mkdir/chdir/getcwd、相对路径与"."/".."的解析、硬链接、getdents以及文件与目录的删除。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use user_lib::{
+    chdir, close, getcwd, getdents, link, mkdir, open, read, rmdir, unlink, write, Dirents,
+    OpenFlags, DT_DIR, DT_REG,
+};
+
+/// 当前工作目录
+fn cwd() -> String {
+    let mut buf = [0u8; 64];
+    let len = getcwd(&mut buf);
+    assert!(len > 0);
+    String::from(core::str::from_utf8(&buf[..len as usize]).unwrap())
+}
+
+/// 读取文件的全部内容
+fn read_file(path: &str) -> Vec<u8> {
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut buf = [0u8; 64];
+    let len = read(fd as usize, &mut buf);
+    assert!(len >= 0);
+    close(fd as usize);
+    Vec::from(&buf[..len as usize])
+}
+
+/// 列出目录中的(文件名, 类型)
+fn list_dir(path: &str) -> Vec<(String, u8)> {
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut buf = [0u8; 128];
+    let mut entries = Vec::new();
+    loop {
+        let len = getdents(fd as usize, &mut buf);
+        assert!(len >= 0);
+        if len == 0 {
+            break;
+        }
+        for dirent in Dirents::new(&buf[..len as usize]) {
+            entries.push((String::from(dirent.name), dirent.d_type));
+        }
+    }
+    close(fd as usize);
+    entries
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test dir_test...");
+    assert_eq!(chdir("/\0"), 0);
+    assert_eq!(mkdir("dtest\0"), 0);
+    assert_eq!(mkdir("dtest\0"), -1);
+    assert_eq!(mkdir("/dtest/sub\0"), 0);
+    assert_eq!(mkdir("no_such_dir/sub\0"), -1);
+
+    // 切换目录与相对路径
+    assert_eq!(chdir("dtest/./sub\0"), 0);
+    assert_eq!(cwd(), "/dtest/sub");
+    let fd = open("f\0", OpenFlags::CREATE | OpenFlags::WRONLY);
+    assert!(fd >= 0);
+    assert_eq!(write(fd as usize, b"data"), 4);
+    close(fd as usize);
+    assert_eq!(read_file("../sub/./f\0"), b"data");
+    // 目录不能以写方式打开，普通文件不能作为目录
+    assert_eq!(open(".\0", OpenFlags::WRONLY), -1);
+    assert_eq!(chdir("f\0"), -1);
+
+    // 硬链接：删除其中一个名字后另一个仍可访问
+    assert_eq!(link("f\0", "/dtest/g\0"), 0);
+    assert_eq!(link("f\0", "/dtest/g\0"), -1);
+    assert_eq!(link("/dtest\0", "dir_link\0"), -1);
+    assert_eq!(unlink("f\0"), 0);
+    assert_eq!(open("f\0", OpenFlags::RDONLY), -1);
+    assert_eq!(read_file("/dtest/g\0"), b"data");
+
+    // 删除目录：须使用rmdir，且目录须为空
+    assert_eq!(chdir("..\0"), 0);
+    assert_eq!(cwd(), "/dtest");
+    assert_eq!(unlink("sub\0"), -1);
+    assert_eq!(rmdir("g\0"), -1);
+    assert_eq!(rmdir("sub\0"), 0);
+    assert_eq!(rmdir("/dtest\0"), -1);
+    assert_eq!(rmdir(".\0"), -1);
+    let entries = list_dir(".\0");
+    assert_eq!(entries.len(), 3);
+    assert!(entries.contains(&(String::from("."), DT_DIR)));
+    assert!(entries.contains(&(String::from(".."), DT_DIR)));
+    assert!(entries.contains(&(String::from("g"), DT_REG)));
+
+    assert_eq!(unlink("g\0"), 0);
+    assert_eq!(chdir("/\0"), 0);
+    assert_eq!(rmdir("dtest\0"), 0);
+    assert_eq!(chdir("dtest\0"), -1);
+    assert_eq!(cwd(), "/");
+    println!("Test dir_test OK!");
+    0
+}
@@ -0,0 +1,78 @@
+//! user/src/bin/12fstat_test.rs
+//! 实验：文件元数据测试
+//! 检验fstat返回的文件类型、inode编号、硬链接数与文件大小。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{
+    close, fstat, link, mkdir, open, pipe, rmdir, unlink, write, OpenFlags, Stat, StatMode,
+};
+
+/// 打开path并获取其元数据
+fn stat_path(path: &str) -> Stat {
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut stat = Stat::new();
+    assert_eq!(fstat(fd as usize, &mut stat), 0);
+    close(fd as usize);
+    stat
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test fstat_test...");
+    // 普通文件：大小随写入增长
+    let fd = open(
+        "/fstat_f\0",
+        OpenFlags::CREATE | OpenFlags::TRUNC | OpenFlags::WRONLY,
+    );
+    assert!(fd >= 0);
+    let mut stat = Stat::new();
+    assert_eq!(fstat(fd as usize, &mut stat), 0);
+    assert!(stat.is_file());
+    assert_eq!(stat.size, 0);
+    assert_eq!(stat.nlink, 1);
+    assert_eq!(write(fd as usize, b"hello, fstat"), 12);
+    assert_eq!(fstat(fd as usize, &mut stat), 0);
+    assert_eq!(stat.size, 12);
+    close(fd as usize);
+
+    // 硬链接：两个名字对应同一个inode
+    assert_eq!(link("/fstat_f\0", "/fstat_g\0"), 0);
+    let f = stat_path("/fstat_f\0");
+    let g = stat_path("/fstat_g\0");
+    assert_eq!(f.ino, g.ino);
+    assert_eq!(f.nlink, 2);
+    assert_eq!(unlink("/fstat_g\0"), 0);
+    assert_eq!(stat_path("/fstat_f\0").nlink, 1);
+    assert_eq!(unlink("/fstat_f\0"), 0);
+
+    // 目录：空目录的硬链接数为2，创建子目录后加1
+    assert_eq!(mkdir("/fstat_d\0"), 0);
+    let d = stat_path("/fstat_d\0");
+    assert!(d.is_dir());
+    assert!(!d.is_file());
+    assert_eq!(d.nlink, 2);
+    assert_eq!(mkdir("/fstat_d/sub\0"), 0);
+    assert_eq!(stat_path("/fstat_d\0").nlink, 3);
+    assert_eq!(stat_path("/fstat_d/..\0").ino, stat_path("/\0").ino);
+    assert_eq!(rmdir("/fstat_d/sub\0"), 0);
+    assert_eq!(rmdir("/fstat_d\0"), 0);
+
+    // 标准输出与管道
+    assert_eq!(fstat(1, &mut stat), 0);
+    assert_eq!(stat.mode, StatMode::CHAR);
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    assert_eq!(fstat(pipe_fd[0], &mut stat), 0);
+    assert_eq!(stat.mode, StatMode::FIFO);
+    close(pipe_fd[0]);
+    close(pipe_fd[1]);
+    assert_eq!(fstat(100, &mut stat), -1);
+    println!("Test fstat_test OK!");
+    0
+}
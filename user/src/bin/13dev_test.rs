@@ -0,0 +1,64 @@
+//! user/src/bin/13dev_test.rs
+//! 实验：设备文件测试
+//! 通过路径打开/dev下的设备文件，检验null、zero、urandom与console的读写行为。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, dup2, fstat, mkdir, open, read, unlink, write, OpenFlags, Stat, StatMode};
+
+/// 以flags打开设备文件path
+fn open_dev(path: &str, flags: OpenFlags) -> usize {
+    let fd = open(path, flags);
+    assert!(fd >= 0);
+    fd as usize
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test dev_test...");
+    // null：写入的数据被丢弃，读取立即返回0
+    let fd = open_dev("/dev/null\0", OpenFlags::RDWR);
+    assert_eq!(write(fd, b"discarded"), 9);
+    let mut buf = [0xffu8; 64];
+    assert_eq!(read(fd, &mut buf), 0);
+    let mut stat = Stat::new();
+    assert_eq!(fstat(fd, &mut stat), 0);
+    assert_eq!(stat.mode, StatMode::CHAR);
+    close(fd);
+
+    // zero：读取得到全0
+    let fd = open_dev("/dev/zero\0", OpenFlags::RDONLY);
+    assert_eq!(read(fd, &mut buf), 64);
+    assert!(buf.iter().all(|&b| b == 0));
+    close(fd);
+
+    // urandom：两次读取的结果不同
+    let fd = open_dev("/dev/urandom\0", OpenFlags::RDONLY);
+    let mut a = [0u8; 32];
+    let mut b = [0u8; 32];
+    assert_eq!(read(fd, &mut a), 32);
+    assert_eq!(read(fd, &mut b), 32);
+    assert_ne!(a, b);
+    assert!(a.iter().any(|&x| x != 0));
+    close(fd);
+
+    // console：将标准输出重定向到/dev/console
+    let fd = open_dev("/dev/console\0", OpenFlags::WRONLY);
+    assert_eq!(write(fd, b"Hello from /dev/console!\n"), 25);
+    assert_eq!(dup2(fd, 1), 1);
+    close(fd);
+    println!("Hello from stdout via /dev/console!");
+
+    // 设备目录中不能创建或删除文件
+    assert_eq!(open("/dev/nonexist\0", OpenFlags::RDONLY), -1);
+    assert_eq!(open("/dev/new\0", OpenFlags::CREATE | OpenFlags::WRONLY), -1);
+    assert_eq!(mkdir("/dev/dir\0"), -1);
+    assert_eq!(unlink("/dev/null\0"), -1);
+    assert_eq!(unlink("/dev\0"), -1);
+    println!("Test dev_test OK!");
+    0
+}
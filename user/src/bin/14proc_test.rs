@@ -0,0 +1,131 @@
+//! user/src/bin/14proc_test.rs
+//! 实验：进程文件系统测试
+//! 读取/proc下的全局文件与本进程、子进程的目录，检验其内容随内核状态变化。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use user_lib::{
+    close, exit, fork, getdents, getpid, open, read, waitpid, write, yield_, Dirents, OpenFlags,
+};
+
+/// 读取path的全部内容，失败时返回None
+fn read_file(path: &str) -> Option<String> {
+    let fd = open(path, OpenFlags::RDONLY);
+    if fd == -1 {
+        return None;
+    }
+    let fd = fd as usize;
+    let mut content: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 64];
+    loop {
+        let len = read(fd, &mut buf);
+        if len <= 0 {
+            break;
+        }
+        content.extend_from_slice(&buf[..len as usize]);
+    }
+    close(fd);
+    String::from_utf8(content).ok()
+}
+
+/// 列出目录path中的文件名
+fn list_dir(path: &str) -> Vec<String> {
+    let fd = open(path, OpenFlags::RDONLY);
+    assert!(fd >= 0);
+    let mut names = Vec::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let len = getdents(fd as usize, &mut buf);
+        if len <= 0 {
+            break;
+        }
+        for dirent in Dirents::new(&buf[..len as usize]) {
+            names.push(String::from(dirent.name));
+        }
+    }
+    close(fd as usize);
+    names
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test proc_test...");
+    let pid = getpid() as usize;
+    // 全局文件
+    let meminfo = read_file("/proc/meminfo\0").unwrap();
+    assert!(meminfo.starts_with("MemTotal:"));
+    assert!(meminfo.contains("HeapUsed:"));
+    assert!(read_file("/proc/apps\0")
+        .unwrap()
+        .lines()
+        .any(|app| app == "14proc_test"));
+    let sched = read_file("/proc/sched\0").unwrap();
+    assert!(sched.contains(format!("Current:\t{}\n", pid).as_str()));
+    assert!(sched.starts_with("Policy:\tPriority"));
+    let uptime = read_file("/proc/uptime\0").unwrap();
+    assert!(uptime.trim_end().contains('.'));
+
+    // 本进程的目录
+    let names = list_dir("/proc\0");
+    assert!(names.iter().any(|name| *name == format!("{}", pid)));
+    assert!(names.iter().any(|name| name == "meminfo"));
+    let status = read_file(format!("/proc/{}/status\0", pid).as_str()).unwrap();
+    assert!(status.contains("Name:\t14proc_test\n"));
+    assert!(status.contains(format!("Pid:\t{}\n", pid).as_str()));
+    assert!(status.contains("State:\tR (running)\n"));
+    let maps = read_file(format!("/proc/{}/maps\0", pid).as_str()).unwrap();
+    assert!(maps.lines().any(|line| line.contains(" r-xu ")));
+    // 读取fds时本文件也在打开的文件之中
+    let fds = read_file(format!("/proc/{}/fds\0", pid).as_str()).unwrap();
+    assert!(fds.lines().any(|line| line.starts_with("0\t")));
+    assert!(fds.lines().any(|line| line.ends_with("/fds")));
+    // 写入次数随调用增加
+    let count = |syscalls: &str| -> usize {
+        syscalls
+            .lines()
+            .find(|line| line.starts_with("write "))
+            .and_then(|line| line.split_whitespace().last()?.parse().ok())
+            .unwrap_or(0)
+    };
+    let before = count(&read_file(format!("/proc/{}/syscalls\0", pid).as_str()).unwrap());
+    write(1, b"");
+    write(1, b"");
+    let after = count(&read_file(format!("/proc/{}/syscalls\0", pid).as_str()).unwrap());
+    assert_eq!(after, before + 2);
+
+    // 子进程退出后成为僵尸进程，回收后目录消失
+    let child = fork();
+    if child == 0 {
+        exit(7);
+    }
+    let child = child as usize;
+    let status_path = format!("/proc/{}/status\0", child);
+    loop {
+        let status = read_file(status_path.as_str()).unwrap();
+        if status.contains("State:\tZ (zombie)\n") {
+            assert!(status.contains(format!("PPid:\t{}\n", pid).as_str()));
+            assert!(status.contains("ExitCode:\t7\n"));
+            break;
+        }
+        yield_();
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(child, &mut exit_code), child as isize);
+    assert_eq!(exit_code, 7);
+    assert!(read_file(status_path.as_str()).is_none());
+    assert_eq!(
+        open("/proc/new\0", OpenFlags::CREATE | OpenFlags::WRONLY),
+        -1
+    );
+    println!("Test proc_test OK!");
+    0
+}
@@ -0,0 +1,230 @@
+//! user/src/bin/15mmap_test.rs
+//! 实验：内存映射测试
+//! 检验文件的私有映射与共享映射、匿名映射、按需映射、部分解除映射、fork后映射的行为，以及共享映射与read/write的一致性。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use core::slice;
+
+use user_lib::{
+    close, exit, fork, fsync, mmap, munmap, open, read, unlink, waitpid, write, MmapFlags,
+    MmapProt, OpenFlags,
+};
+
+const PAGE_SIZE: usize = 4096;
+/// 测试文件跨越三页，最后一页不满
+const FILE_SIZE: usize = PAGE_SIZE * 2 + 100;
+
+/// 测试文件第i个字节的内容
+fn pattern(i: usize) -> u8 {
+    (i % 251) as u8
+}
+
+/// 以flags打开path
+fn open_file(path: &str, flags: OpenFlags) -> usize {
+    let fd = open(path, flags);
+    assert!(fd >= 0);
+    fd as usize
+}
+
+/// 映射fd对应文件的前len字节，返回可访问映射内容的切片
+fn map_file(len: usize, prot: MmapProt, flags: MmapFlags, fd: usize) -> &'static mut [u8] {
+    let addr = mmap(0, len, prot, flags, fd, 0);
+    assert!(addr > 0);
+    assert_eq!(addr as usize % PAGE_SIZE, 0);
+    unsafe { slice::from_raw_parts_mut(addr as *mut u8, len) }
+}
+
+/// 读取文件path的前FILE_SIZE字节
+fn read_file(path: &str, buf: &mut [u8; FILE_SIZE]) {
+    let fd = open_file(path, OpenFlags::RDONLY);
+    let mut total = 0;
+    while total < FILE_SIZE {
+        let len = read(fd, &mut buf[total..]);
+        assert!(len > 0);
+        total += len as usize;
+    }
+    close(fd);
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test mmap_test...");
+    let mut data = [0u8; FILE_SIZE];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = pattern(i);
+    }
+    let fd = open_file(
+        "/mmap_f\0",
+        OpenFlags::CREATE | OpenFlags::WRONLY | OpenFlags::TRUNC,
+    );
+    assert_eq!(write(fd, &data), FILE_SIZE as isize);
+    close(fd);
+
+    // 私有映射：内容与文件一致，文件末尾之后为0，写入不影响文件
+    let fd = open_file("/mmap_f\0", OpenFlags::RDONLY);
+    let map = map_file(
+        PAGE_SIZE * 3,
+        MmapProt::READ | MmapProt::WRITE,
+        MmapFlags::PRIVATE,
+        fd,
+    );
+    assert!(map[..FILE_SIZE]
+        .iter()
+        .enumerate()
+        .all(|(i, b)| *b == pattern(i)));
+    assert!(map[FILE_SIZE..].iter().all(|b| *b == 0));
+    map[0] = 0xff;
+    map[PAGE_SIZE] = 0xff;
+    let mut buf = [0u8; FILE_SIZE];
+    read_file("/mmap_f\0", &mut buf);
+    assert_eq!(buf, data);
+    assert_eq!(munmap(map.as_ptr() as usize, PAGE_SIZE * 3), 0);
+
+    // 系统调用可直接读写尚未访问过的映射
+    let map = map_file(FILE_SIZE, MmapProt::READ, MmapFlags::PRIVATE, fd);
+    let out = open_file(
+        "/mmap_out\0",
+        OpenFlags::CREATE | OpenFlags::RDWR | OpenFlags::TRUNC,
+    );
+    assert_eq!(write(out, map), FILE_SIZE as isize);
+    close(out);
+    read_file("/mmap_out\0", &mut buf);
+    assert_eq!(buf, data);
+    assert_eq!(munmap(map.as_ptr() as usize, FILE_SIZE), 0);
+
+    // 只读打开的文件不能建立可写的共享映射
+    assert_eq!(
+        mmap(0, PAGE_SIZE, MmapProt::WRITE, MmapFlags::SHARED, fd, 0),
+        -1
+    );
+    close(fd);
+    // 参数检查
+    let fd = open_file("/mmap_f\0", OpenFlags::RDWR);
+    assert_eq!(
+        mmap(0, PAGE_SIZE, MmapProt::READ, MmapFlags::SHARED, fd, 1),
+        -1
+    );
+    assert_eq!(
+        mmap(
+            0,
+            PAGE_SIZE,
+            MmapProt::READ,
+            MmapFlags::SHARED | MmapFlags::PRIVATE,
+            fd,
+            0
+        ),
+        -1
+    );
+    assert_eq!(mmap(0, 0, MmapProt::READ, MmapFlags::SHARED, fd, 0), -1);
+    assert_eq!(
+        mmap(0, PAGE_SIZE, MmapProt::READ, MmapFlags::SHARED, 100, 0),
+        -1
+    );
+    let dir = open_file("/\0", OpenFlags::RDONLY);
+    assert_eq!(
+        mmap(0, PAGE_SIZE, MmapProt::READ, MmapFlags::PRIVATE, dir, 0),
+        -1
+    );
+    close(dir);
+    // 不能解除非mmap映射的内存
+    let text = main as fn() -> i32 as usize & !(PAGE_SIZE - 1);
+    assert_eq!(munmap(text, PAGE_SIZE), -1);
+
+    // 共享映射：子进程的写入对父进程可见，解除映射后写回文件
+    let shared = map_file(
+        FILE_SIZE,
+        MmapProt::READ | MmapProt::WRITE,
+        MmapFlags::SHARED,
+        fd,
+    );
+    let private = map_file(
+        FILE_SIZE,
+        MmapProt::READ | MmapProt::WRITE,
+        MmapFlags::PRIVATE,
+        fd,
+    );
+    assert_eq!(private[10], pattern(10));
+    close(fd);
+    let pid = fork();
+    if pid == 0 {
+        shared[1] = 0xaa;
+        shared[PAGE_SIZE * 2 + 1] = 0xbb;
+        private[10] = 0xcc;
+        exit(0);
+    }
+    let mut exit_code = 0;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    assert_eq!(shared[1], 0xaa);
+    assert_eq!(shared[PAGE_SIZE * 2 + 1], 0xbb);
+    assert_eq!(private[10], pattern(10));
+    assert_eq!(munmap(shared.as_ptr() as usize, FILE_SIZE), 0);
+    assert_eq!(munmap(private.as_ptr() as usize, FILE_SIZE), 0);
+    read_file("/mmap_f\0", &mut buf);
+    assert_eq!(buf[1], 0xaa);
+    assert_eq!(buf[PAGE_SIZE * 2 + 1], 0xbb);
+    assert_eq!(buf[2..PAGE_SIZE * 2 + 1], data[2..PAGE_SIZE * 2 + 1]);
+
+    // 共享映射与read/write一致：映射中的修改无需解除映射即可读到，写入文件的数据在映射中可见
+    let fd = open_file("/mmap_f\0", OpenFlags::RDWR);
+    let shared = map_file(
+        FILE_SIZE,
+        MmapProt::READ | MmapProt::WRITE,
+        MmapFlags::SHARED,
+        fd,
+    );
+    shared[3] = 0xdd;
+    read_file("/mmap_f\0", &mut buf);
+    assert_eq!(buf[3], 0xdd);
+    assert_eq!(write(fd, &[0xee; 2]), 2);
+    assert_eq!(shared[..2], [0xee; 2]);
+    assert_eq!(shared[3], 0xdd);
+    shared[PAGE_SIZE] = 0x11;
+    assert_eq!(fsync(fd), 0);
+    read_file("/mmap_f\0", &mut buf);
+    assert_eq!(buf[PAGE_SIZE], 0x11);
+    assert_eq!(munmap(shared.as_ptr() as usize, FILE_SIZE), 0);
+    close(fd);
+
+    // 匿名映射：初始为0，解除中间一页后两侧仍可访问，访问被解除的页将被内核杀死
+    let anon = mmap(
+        0,
+        PAGE_SIZE * 3,
+        MmapProt::READ | MmapProt::WRITE,
+        MmapFlags::PRIVATE | MmapFlags::ANONYMOUS,
+        0,
+        0,
+    );
+    assert!(anon > 0);
+    let anon = unsafe { slice::from_raw_parts_mut(anon as *mut u8, PAGE_SIZE * 3) };
+    assert!(anon[..PAGE_SIZE * 2].iter().all(|b| *b == 0));
+    let out = open_file("/mmap_out\0", OpenFlags::RDONLY);
+    assert_eq!(read(out, &mut anon[PAGE_SIZE * 2..]), PAGE_SIZE as isize);
+    close(out);
+    assert_eq!(anon[PAGE_SIZE * 2..], data[..PAGE_SIZE]);
+    anon.fill(0x5a);
+    let hole = anon.as_ptr() as usize + PAGE_SIZE;
+    assert_eq!(munmap(hole, PAGE_SIZE), 0);
+    assert_eq!(anon[PAGE_SIZE - 1], 0x5a);
+    assert_eq!(anon[PAGE_SIZE * 2], 0x5a);
+    let pid = fork();
+    if pid == 0 {
+        unsafe {
+            (hole as *mut u8).write_volatile(1);
+        }
+        exit(0);
+    }
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, -2);
+    assert_eq!(munmap(anon.as_ptr() as usize, PAGE_SIZE * 3), 0);
+
+    unlink("/mmap_f\0");
+    unlink("/mmap_out\0");
+    println!("Test mmap_test OK!");
+    0
+}
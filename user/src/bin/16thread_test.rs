@@ -0,0 +1,112 @@
+//! user/src/bin/16thread_test.rs
+//! 实验：线程测试
+//! 检验同一进程的线程共享地址空间与文件描述符表、各自拥有独立的用户栈，以及waittid回收线程的行为。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use user_lib::{
+    close, exit, fork, gettid, pipe, read, thread_create, waitpid, waittid, write, yield_,
+};
+
+const THREADS: usize = 4;
+const ROUNDS: usize = 1000;
+
+/// 所有线程共同累加的计数器
+static COUNTER: AtomicUsize = AtomicUsize::new(0);
+/// 各线程记录自己的tid
+static TIDS: [AtomicUsize; THREADS] = [const { AtomicUsize::new(0) }; THREADS];
+/// 各线程记录自己栈上局部变量的地址
+static STACKS: [AtomicUsize; THREADS] = [const { AtomicUsize::new(0) }; THREADS];
+/// 主线程创建的管道的写端
+static PIPE_WRITE: AtomicUsize = AtomicUsize::new(0);
+
+/// 工作线程：累加共享计数器，并经共享的管道写端写入一个字节
+fn worker(i: usize) {
+    let local = i;
+    TIDS[i].store(gettid() as usize, Ordering::Relaxed);
+    STACKS[i].store(&local as *const usize as usize, Ordering::Relaxed);
+    for round in 0..ROUNDS {
+        COUNTER.fetch_add(1, Ordering::Relaxed);
+        if round % 100 == 0 {
+            yield_();
+        }
+    }
+    write(PIPE_WRITE.load(Ordering::Relaxed), &[b'a' + i as u8]);
+    exit(100 + i as i32);
+}
+
+/// 立即退出的线程
+fn quick(code: usize) {
+    exit(code as i32);
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test thread_test...");
+    assert_eq!(gettid(), 0);
+    let mut pipe_fd = [0usize; 2];
+    assert_eq!(pipe(&mut pipe_fd), 0);
+    PIPE_WRITE.store(pipe_fd[1], Ordering::Relaxed);
+
+    let mut tids = [0usize; THREADS];
+    for (i, tid) in tids.iter_mut().enumerate() {
+        let ret = thread_create(worker as fn(usize) as usize, i);
+        assert!(ret > 0);
+        *tid = ret as usize;
+    }
+    // 进程中还有其他线程时不能fork；线程不能等待自身
+    assert_eq!(fork(), -1);
+    assert_eq!(waittid(0), -1);
+    for (i, tid) in tids.iter().enumerate() {
+        assert_eq!(waittid(*tid), 100 + i as isize);
+        // 已回收的线程不能再次等待
+        assert_eq!(waittid(*tid), -1);
+    }
+    assert_eq!(COUNTER.load(Ordering::Relaxed), THREADS * ROUNDS);
+    // 各线程的tid互不相同，用户栈互相独立
+    for i in 0..THREADS {
+        assert_eq!(TIDS[i].load(Ordering::Relaxed), tids[i]);
+        for j in 0..i {
+            assert_ne!(tids[i], tids[j]);
+            assert_ne!(
+                STACKS[i].load(Ordering::Relaxed),
+                STACKS[j].load(Ordering::Relaxed)
+            );
+        }
+    }
+
+    // 各线程经共享的文件描述符表写入了管道
+    close(pipe_fd[1]);
+    let mut buf = [0u8; THREADS];
+    let mut total = 0;
+    while total < THREADS {
+        let len = read(pipe_fd[0], &mut buf[total..]);
+        assert!(len > 0);
+        total += len as usize;
+    }
+    close(pipe_fd[0]);
+    buf.sort_unstable();
+    assert_eq!(&buf, b"abcd");
+
+    // 回收的tid可被新线程复用
+    let tid = thread_create(quick as fn(usize) as usize, 7);
+    assert!(tid > 0 && tid as usize <= THREADS);
+    assert_eq!(waittid(tid as usize), 7);
+
+    // 线程全部回收后可以fork
+    let pid = fork();
+    if pid == 0 {
+        exit(gettid() as i32);
+    }
+    let mut exit_code = -1;
+    assert_eq!(waitpid(pid as usize, &mut exit_code), pid);
+    assert_eq!(exit_code, 0);
+    println!("Test thread_test OK!");
+    0
+}
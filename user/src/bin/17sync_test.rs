@@ -0,0 +1,159 @@
+//! user/src/bin/17sync_test.rs
+//! 实验：同步原语测试
+//! 检验自旋锁与阻塞锁保护的临界区、锁只能由持有者释放、信号量实现的生产者-消费者，以及条件变量的等待与唤醒。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use user_lib::{
+    condvar_create, condvar_signal, condvar_wait, exit, mutex_blocking_create, mutex_create,
+    mutex_lock, mutex_unlock, semaphore_create, semaphore_down, semaphore_up, thread_create,
+    waittid, yield_,
+};
+
+const THREADS: usize = 4;
+const ROUNDS: usize = 50;
+/// 生产者-消费者的缓冲区大小与产品总数
+const BUF_SIZE: usize = 4;
+const ITEMS: usize = 32;
+
+/// 由互斥锁保护的计数器，读写之间让出CPU以制造竞争
+static mut COUNTER: usize = 0;
+static MUTEX_ID: AtomicUsize = AtomicUsize::new(0);
+
+static BUFFER: [AtomicUsize; BUF_SIZE] = [const { AtomicUsize::new(0) }; BUF_SIZE];
+static SEM_EMPTY: AtomicUsize = AtomicUsize::new(0);
+static SEM_FULL: AtomicUsize = AtomicUsize::new(0);
+
+static CONDVAR_ID: AtomicUsize = AtomicUsize::new(0);
+static FLAG: AtomicUsize = AtomicUsize::new(0);
+
+/// 被主线程持有的两把锁
+static HELD_SPIN: AtomicUsize = AtomicUsize::new(0);
+static HELD_BLOCKING: AtomicUsize = AtomicUsize::new(0);
+
+/// 反复获取MUTEX_ID对应的锁并递增计数器
+fn locker(_arg: usize) {
+    let mutex_id = MUTEX_ID.load(Ordering::Relaxed);
+    for _ in 0..ROUNDS {
+        assert_eq!(mutex_lock(mutex_id), 0);
+        unsafe {
+            let value = COUNTER;
+            yield_();
+            COUNTER = value + 1;
+        }
+        assert_eq!(mutex_unlock(mutex_id), 0);
+    }
+    exit(0);
+}
+
+/// 试图释放或借条件变量释放其他线程持有的锁，均应失败
+fn intruder(_arg: usize) {
+    let spin = HELD_SPIN.load(Ordering::Relaxed);
+    let blocking = HELD_BLOCKING.load(Ordering::Relaxed);
+    assert_eq!(mutex_unlock(spin), -1);
+    assert_eq!(mutex_unlock(blocking), -1);
+    let condvar = CONDVAR_ID.load(Ordering::Relaxed);
+    assert_eq!(condvar_wait(condvar, blocking), -1);
+    exit(0);
+}
+
+/// 多个线程在mutex_id对应的锁的保护下递增计数器，结果应不丢失任何一次递增
+fn run_lockers(mutex_id: usize) {
+    MUTEX_ID.store(mutex_id, Ordering::Relaxed);
+    unsafe {
+        COUNTER = 0;
+    }
+    let mut tids = [0usize; THREADS];
+    for tid in tids.iter_mut() {
+        *tid = thread_create(locker as fn(usize) as usize, 0) as usize;
+    }
+    for tid in tids {
+        assert_eq!(waittid(tid), 0);
+    }
+    assert_eq!(unsafe { COUNTER }, THREADS * ROUNDS);
+}
+
+/// 生产者：依次放入1..=ITEMS
+fn producer(_arg: usize) {
+    let empty = SEM_EMPTY.load(Ordering::Relaxed);
+    let full = SEM_FULL.load(Ordering::Relaxed);
+    for i in 0..ITEMS {
+        assert_eq!(semaphore_down(empty), 0);
+        BUFFER[i % BUF_SIZE].store(i + 1, Ordering::Relaxed);
+        assert_eq!(semaphore_up(full), 0);
+    }
+    exit(0);
+}
+
+/// 在锁的保护下设置标志并唤醒等待者
+fn signaler(_arg: usize) {
+    let mutex_id = MUTEX_ID.load(Ordering::Relaxed);
+    mutex_lock(mutex_id);
+    FLAG.store(1, Ordering::Relaxed);
+    condvar_signal(CONDVAR_ID.load(Ordering::Relaxed));
+    mutex_unlock(mutex_id);
+    exit(0);
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test sync_test...");
+    // 自旋锁与阻塞锁
+    let spin = mutex_create();
+    assert!(spin >= 0);
+    run_lockers(spin as usize);
+    let blocking = mutex_blocking_create();
+    assert!(blocking >= 0);
+    run_lockers(blocking as usize);
+    assert_eq!(mutex_unlock(blocking as usize), -1);
+    assert_eq!(mutex_lock(100), -1);
+
+    // 锁只能由持有者释放
+    let condvar = condvar_create();
+    assert!(condvar >= 0);
+    CONDVAR_ID.store(condvar as usize, Ordering::Relaxed);
+    HELD_SPIN.store(spin as usize, Ordering::Relaxed);
+    HELD_BLOCKING.store(blocking as usize, Ordering::Relaxed);
+    assert_eq!(mutex_lock(spin as usize), 0);
+    assert_eq!(mutex_lock(blocking as usize), 0);
+    let tid = thread_create(intruder as fn(usize) as usize, 0);
+    assert_eq!(waittid(tid as usize), 0);
+    assert_eq!(mutex_unlock(blocking as usize), 0);
+    assert_eq!(mutex_unlock(spin as usize), 0);
+
+    // 信号量：容量为BUF_SIZE的生产者-消费者
+    let empty = semaphore_create(BUF_SIZE);
+    let full = semaphore_create(0);
+    assert!(empty >= 0 && full >= 0 && empty != full);
+    SEM_EMPTY.store(empty as usize, Ordering::Relaxed);
+    SEM_FULL.store(full as usize, Ordering::Relaxed);
+    let tid = thread_create(producer as fn(usize) as usize, 0);
+    for i in 0..ITEMS {
+        assert_eq!(semaphore_down(full as usize), 0);
+        assert_eq!(BUFFER[i % BUF_SIZE].load(Ordering::Relaxed), i + 1);
+        assert_eq!(semaphore_up(empty as usize), 0);
+    }
+    assert_eq!(waittid(tid as usize), 0);
+    assert_eq!(semaphore_up(100), -1);
+
+    // 条件变量：持锁等待标志被另一个线程设置
+    MUTEX_ID.store(blocking as usize, Ordering::Relaxed);
+    assert_eq!(mutex_lock(blocking as usize), 0);
+    let tid = thread_create(signaler as fn(usize) as usize, 0);
+    while FLAG.load(Ordering::Relaxed) == 0 {
+        assert_eq!(condvar_wait(condvar as usize, blocking as usize), 0);
+    }
+    assert_eq!(mutex_unlock(blocking as usize), 0);
+    assert_eq!(waittid(tid as usize), 0);
+    // 等待时须持有互斥锁
+    assert_eq!(condvar_wait(condvar as usize, blocking as usize), -1);
+    assert_eq!(condvar_signal(100), -1);
+    println!("Test sync_test OK!");
+    0
+}
@@ -0,0 +1,105 @@
+//! user/src/bin/18deadlock_test.rs
+//! 实验：死锁检测测试
+//! 检验开启死锁检测后，重复获取自身持有的锁、两个线程交叉加锁等会导致死锁的请求被拒绝，而普通的竞争不受影响。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use user_lib::{
+    enable_deadlock_detect, exit, mutex_blocking_create, mutex_lock, mutex_unlock,
+    semaphore_create, semaphore_down, semaphore_up, thread_create, waittid, yield_, EDEADLOCK,
+};
+
+const THREADS: usize = 4;
+const ROUNDS: usize = 20;
+
+static MUTEX_A: AtomicUsize = AtomicUsize::new(0);
+static MUTEX_B: AtomicUsize = AtomicUsize::new(0);
+/// 子线程已持有MUTEX_B
+static HOLDING: AtomicUsize = AtomicUsize::new(0);
+
+/// 与其他线程竞争同一把锁，不应被判定为死锁
+fn contender(_arg: usize) {
+    let mutex_id = MUTEX_A.load(Ordering::Relaxed);
+    for _ in 0..ROUNDS {
+        assert_eq!(mutex_lock(mutex_id), 0);
+        yield_();
+        assert_eq!(mutex_unlock(mutex_id), 0);
+    }
+    exit(0);
+}
+
+/// 先持有MUTEX_B再请求MUTEX_A；请求被拒绝时释放MUTEX_B并以1退出
+fn crosser(_arg: usize) {
+    let mutex_a = MUTEX_A.load(Ordering::Relaxed);
+    let mutex_b = MUTEX_B.load(Ordering::Relaxed);
+    assert_eq!(mutex_lock(mutex_b), 0);
+    HOLDING.store(1, Ordering::Relaxed);
+    if mutex_lock(mutex_a) == EDEADLOCK {
+        mutex_unlock(mutex_b);
+        exit(1);
+    }
+    mutex_unlock(mutex_a);
+    mutex_unlock(mutex_b);
+    exit(0);
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test deadlock_test...");
+    assert_eq!(enable_deadlock_detect(true), 0);
+    let mutex_a = mutex_blocking_create() as usize;
+    let mutex_b = mutex_blocking_create() as usize;
+    MUTEX_A.store(mutex_a, Ordering::Relaxed);
+    MUTEX_B.store(mutex_b, Ordering::Relaxed);
+
+    // 重复获取自身持有的锁或信号量
+    assert_eq!(mutex_lock(mutex_a), 0);
+    assert_eq!(mutex_lock(mutex_a), EDEADLOCK);
+    assert_eq!(mutex_unlock(mutex_a), 0);
+    let sem = semaphore_create(1) as usize;
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(semaphore_down(sem), EDEADLOCK);
+    assert_eq!(semaphore_up(sem), 0);
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(semaphore_up(sem), 0);
+
+    // 多个线程竞争同一把锁
+    let mut tids = [0usize; THREADS];
+    for tid in tids.iter_mut() {
+        *tid = thread_create(contender as fn(usize) as usize, 0) as usize;
+    }
+    for tid in tids {
+        assert_eq!(waittid(tid), 0);
+    }
+
+    // 两个线程以相反的顺序获取两把锁，恰有一方的请求被拒绝
+    assert_eq!(mutex_lock(mutex_a), 0);
+    let tid = thread_create(crosser as fn(usize) as usize, 0) as usize;
+    while HOLDING.load(Ordering::Relaxed) == 0 {
+        yield_();
+    }
+    yield_();
+    let main_rejected = mutex_lock(mutex_b) == EDEADLOCK;
+    if main_rejected {
+        // 释放MUTEX_A使子线程得以继续
+        assert_eq!(mutex_unlock(mutex_a), 0);
+    } else {
+        assert_eq!(mutex_unlock(mutex_b), 0);
+        assert_eq!(mutex_unlock(mutex_a), 0);
+    }
+    let crosser_rejected = waittid(tid) == 1;
+    assert!(main_rejected != crosser_rejected);
+
+    // 关闭检测后请求不再被检查
+    assert_eq!(enable_deadlock_detect(false), 0);
+    assert_eq!(semaphore_down(sem), 0);
+    assert_eq!(semaphore_up(sem), 0);
+    println!("Test deadlock_test OK!");
+    0
+}
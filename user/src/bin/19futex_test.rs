@@ -0,0 +1,113 @@
+//! user/src/bin/19futex_test.rs
+//! 实验：futex测试
+//! 检验futex系统调用，以及基于futex的用户态互斥锁、条件变量与一次性初始化在多线程竞争下的正确性。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use core::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+
+use user_lib::sync::{Condvar, Mutex, Once};
+use user_lib::{exit, futex_wait, futex_wake, thread_create, waittid, yield_};
+
+const THREADS: usize = 4;
+const ROUNDS: usize = 50;
+/// 经条件变量传递的产品总数
+const ITEMS: usize = 32;
+
+/// 由互斥锁保护的计数器，持锁期间让出CPU以制造竞争
+static COUNTER: Mutex<usize> = Mutex::new(0);
+
+/// 单槽缓冲区，None表示为空
+static SLOT: Mutex<Option<usize>> = Mutex::new(None);
+static NOT_EMPTY: Condvar = Condvar::new();
+static NOT_FULL: Condvar = Condvar::new();
+
+static INIT: Once = Once::new();
+/// 初始化闭包的执行次数
+static INIT_RUNS: AtomicUsize = AtomicUsize::new(0);
+
+fn locker(_arg: usize) {
+    for _ in 0..ROUNDS {
+        let mut counter = COUNTER.lock();
+        let value = *counter;
+        yield_();
+        *counter = value + 1;
+    }
+    exit(0);
+}
+
+/// 生产者：依次放入1..=ITEMS，槽满时等待
+fn producer(_arg: usize) {
+    for i in 1..=ITEMS {
+        let mut slot = SLOT.lock();
+        while slot.is_some() {
+            slot = NOT_FULL.wait(slot);
+        }
+        *slot = Some(i);
+        NOT_EMPTY.notify_one();
+    }
+    exit(0);
+}
+
+fn initializer(_arg: usize) {
+    INIT.call_once(|| {
+        INIT_RUNS.fetch_add(1, Ordering::Relaxed);
+        // 初始化期间让出CPU，使其他线程进入等待
+        for _ in 0..10 {
+            yield_();
+        }
+    });
+    assert!(INIT.is_completed());
+    exit(0);
+}
+
+/// 创建THREADS个执行entry的线程并等待它们全部正常退出
+fn run_threads(entry: fn(usize)) {
+    let mut tids = [0usize; THREADS];
+    for tid in tids.iter_mut() {
+        *tid = thread_create(entry as usize, 0) as usize;
+    }
+    for tid in tids {
+        assert_eq!(waittid(tid), 0);
+    }
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test futex_test...");
+    // futex系统调用本身
+    let futex = AtomicU32::new(1);
+    assert_eq!(futex_wait(&futex, 0), -2);
+    assert_eq!(futex_wake(&futex, 1), 0);
+
+    // 互斥锁
+    run_threads(locker);
+    assert_eq!(*COUNTER.lock(), THREADS * ROUNDS);
+    let guard = COUNTER.lock();
+    assert!(COUNTER.try_lock().is_none());
+    drop(guard);
+    assert!(COUNTER.try_lock().is_some());
+
+    // 条件变量：主线程作为消费者
+    let tid = thread_create(producer as fn(usize) as usize, 0);
+    for i in 1..=ITEMS {
+        let mut slot = SLOT.lock();
+        while slot.is_none() {
+            slot = NOT_EMPTY.wait(slot);
+        }
+        assert_eq!(slot.take(), Some(i));
+        NOT_FULL.notify_one();
+    }
+    assert_eq!(waittid(tid as usize), 0);
+
+    // 一次性初始化
+    run_threads(initializer);
+    assert_eq!(INIT_RUNS.load(Ordering::Relaxed), 1);
+    INIT.call_once(|| unreachable!());
+    println!("Test futex_test OK!");
+    0
+}
@@ -0,0 +1,140 @@
+//! user/src/bin/20priority_test.rs
+//! 实验：优先级继承测试
+//! 低优先级线程持有高优先级线程等待的阻塞锁时，经锁链传递的优先级继承使其不被中等优先级线程饿死，释放锁后优先级随之恢复。
+//! 最高优先级的主线程以让出CPU的方式等待最低优先级的线程时，后者仍能运行，系统不会活锁。
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use core::hint::spin_loop;
+use core::sync::atomic::{AtomicIsize, AtomicUsize, Ordering};
+
+use user_lib::{
+    exit, get_priority, mutex_blocking_create, mutex_lock, mutex_unlock, set_priority,
+    thread_create, waittid, yield_,
+};
+
+const DEFAULT: isize = 16;
+const MAIN: isize = 31;
+const LOW: isize = 17;
+const MIDDLE: isize = 18;
+const HOG: isize = 20;
+const HIGH: isize = 25;
+const IDLE: isize = 0;
+
+/// LOW持有MUTEX_A；MIDDLE持有MUTEX_B并等待MUTEX_A；HIGH等待MUTEX_B
+static MUTEX_A: AtomicUsize = AtomicUsize::new(0);
+static MUTEX_B: AtomicUsize = AtomicUsize::new(0);
+static LOW_LOCKED: AtomicUsize = AtomicUsize::new(0);
+static MIDDLE_LOCKED: AtomicUsize = AtomicUsize::new(0);
+/// HIGH获得锁后置1，HOG随之退出
+static DONE: AtomicUsize = AtomicUsize::new(0);
+
+/// 各线程在释放锁前后观察到的有效优先级
+static LOW_BOOSTED: AtomicIsize = AtomicIsize::new(0);
+static LOW_RESTORED: AtomicIsize = AtomicIsize::new(0);
+static MIDDLE_BOOSTED: AtomicIsize = AtomicIsize::new(0);
+static MIDDLE_RESTORED: AtomicIsize = AtomicIsize::new(0);
+
+/// IDLE线程完成的工作量
+static IDLE_WORK: AtomicUsize = AtomicUsize::new(0);
+
+/// 新线程继承主线程的优先级MAIN，先置标志再降低自身优先级
+fn low(_arg: usize) {
+    let mutex_a = MUTEX_A.load(Ordering::Relaxed);
+    assert_eq!(mutex_lock(mutex_a), 0);
+    LOW_LOCKED.store(1, Ordering::Relaxed);
+    assert_eq!(set_priority(LOW), LOW);
+    // HOG运行期间，只有继承了高于HOG的优先级才会再次被调度
+    while get_priority() != HIGH {
+        yield_();
+    }
+    LOW_BOOSTED.store(get_priority(), Ordering::Relaxed);
+    assert_eq!(mutex_unlock(mutex_a), 0);
+    LOW_RESTORED.store(get_priority(), Ordering::Relaxed);
+    exit(0);
+}
+
+fn middle(_arg: usize) {
+    let mutex_a = MUTEX_A.load(Ordering::Relaxed);
+    let mutex_b = MUTEX_B.load(Ordering::Relaxed);
+    assert_eq!(mutex_lock(mutex_b), 0);
+    MIDDLE_LOCKED.store(1, Ordering::Relaxed);
+    assert_eq!(set_priority(MIDDLE), MIDDLE);
+    assert_eq!(mutex_lock(mutex_a), 0);
+    MIDDLE_BOOSTED.store(get_priority(), Ordering::Relaxed);
+    assert_eq!(mutex_unlock(mutex_a), 0);
+    assert_eq!(mutex_unlock(mutex_b), 0);
+    MIDDLE_RESTORED.store(get_priority(), Ordering::Relaxed);
+    exit(0);
+}
+
+/// 不需要任何锁、持续占用CPU而从不让出的线程，只会被时钟中断抢占
+fn hog(_arg: usize) {
+    set_priority(HOG);
+    while DONE.load(Ordering::Relaxed) == 0 {
+        spin_loop();
+    }
+    exit(0);
+}
+
+fn high(_arg: usize) {
+    set_priority(HIGH);
+    let mutex_b = MUTEX_B.load(Ordering::Relaxed);
+    assert_eq!(mutex_lock(mutex_b), 0);
+    DONE.store(1, Ordering::Relaxed);
+    assert_eq!(mutex_unlock(mutex_b), 0);
+    exit(0);
+}
+
+/// 最低优先级、从不让出CPU的线程
+fn idle(_arg: usize) {
+    assert_eq!(set_priority(IDLE), IDLE);
+    for _ in 0..100000 {
+        IDLE_WORK.fetch_add(1, Ordering::Relaxed);
+    }
+    exit(0);
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Into Test priority_test...");
+    assert_eq!(get_priority(), DEFAULT);
+    assert_eq!(set_priority(32), -1);
+    assert_eq!(set_priority(-1), -1);
+    assert_eq!(set_priority(IDLE), IDLE);
+    // 主线程以最高优先级布置场景，并以最高优先级等待各线程
+    assert_eq!(set_priority(MAIN), MAIN);
+    MUTEX_A.store(mutex_blocking_create() as usize, Ordering::Relaxed);
+    MUTEX_B.store(mutex_blocking_create() as usize, Ordering::Relaxed);
+
+    let low_tid = thread_create(low as fn(usize) as usize, 0);
+    while LOW_LOCKED.load(Ordering::Relaxed) == 0 {
+        yield_();
+    }
+    let middle_tid = thread_create(middle as fn(usize) as usize, 0);
+    while MIDDLE_LOCKED.load(Ordering::Relaxed) == 0 {
+        yield_();
+    }
+    let hog_tid = thread_create(hog as fn(usize) as usize, 0);
+    let high_tid = thread_create(high as fn(usize) as usize, 0);
+    for tid in [high_tid, middle_tid, low_tid, hog_tid] {
+        assert_eq!(waittid(tid as usize), 0);
+    }
+
+    // HIGH的优先级经MIDDLE传递给LOW，二者释放锁后恢复自身的优先级
+    assert_eq!(LOW_BOOSTED.load(Ordering::Relaxed), HIGH);
+    assert_eq!(LOW_RESTORED.load(Ordering::Relaxed), LOW);
+    assert_eq!(MIDDLE_BOOSTED.load(Ordering::Relaxed), HIGH);
+    assert_eq!(MIDDLE_RESTORED.load(Ordering::Relaxed), MIDDLE);
+
+    // 忙等最低优先级线程的最高优先级线程不会一直被选中
+    let idle_tid = thread_create(idle as fn(usize) as usize, 0);
+    assert_eq!(waittid(idle_tid as usize), 0);
+    assert_eq!(IDLE_WORK.load(Ordering::Relaxed), 100000);
+    println!("Test priority_test OK!");
+    0
+}
@@ -0,0 +1,68 @@
+//! user/src/bin/cat.rs
+//! 将各参数指定的文件依次输出到标准输出，没有参数时复制标准输入
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+use alloc::vec;
+
+use user_lib::{close, fstat, open, read, write, OpenFlags, Stat};
+
+const STDIN: usize = 0;
+const STDOUT: usize = 1;
+
+/// 缓冲区的最大长度
+const MAX_BUFFER_SIZE: usize = 4096;
+/// 无法得知文件大小（如管道、标准输入、/proc中的文件）时使用的缓冲区长度
+const DEFAULT_BUFFER_SIZE: usize = 256;
+
+/// 将fd中的内容全部复制到标准输出，普通文件按其大小分配缓冲区
+fn copy_to_stdout(fd: usize, stat: &Stat) {
+    let size = if stat.is_file() && stat.size > 0 {
+        (stat.size as usize).min(MAX_BUFFER_SIZE)
+    } else {
+        DEFAULT_BUFFER_SIZE
+    };
+    let mut buffer = vec![0u8; size];
+    loop {
+        let len = read(fd, &mut buffer);
+        if len <= 0 {
+            break;
+        }
+        write(STDOUT, &buffer[..len as usize]);
+    }
+}
+
+#[no_mangle]
+fn main(argc: usize, argv: &[&str]) -> i32 {
+    let mut stat = Stat::new();
+    if argc <= 1 {
+        fstat(STDIN, &mut stat);
+        copy_to_stdout(STDIN, &stat);
+        return 0;
+    }
+    for name in &argv[1..] {
+        let mut path = String::from(*name);
+        path.push('\0');
+        let fd = open(path.as_str(), OpenFlags::RDONLY);
+        if fd == -1 {
+            println!("cat: cannot open {}", name);
+            return -1;
+        }
+        let fd = fd as usize;
+        fstat(fd, &mut stat);
+        if stat.is_dir() {
+            println!("cat: {} is a directory", name);
+            close(fd);
+            return -1;
+        }
+        copy_to_stdout(fd, &stat);
+        close(fd);
+    }
+    0
+}
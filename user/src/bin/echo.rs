@@ -0,0 +1,20 @@
+//! user/src/bin/echo.rs
+//! 将各参数以空格分隔输出到标准输出
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+#[no_mangle]
+fn main(_argc: usize, argv: &[&str]) -> i32 {
+    for (i, arg) in argv.iter().enumerate().skip(1) {
+        if i > 1 {
+            print!(" ");
+        }
+        print!("{}", arg);
+    }
+    println!("");
+    0
+}
@@ -0,0 +1,59 @@
+//! user/src/bin/free.rs
+//! 以表格形式输出物理内存与内核堆的总量、已用量与空闲量（单位kB），信息取自/proc/meminfo
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::{close, open, read, OpenFlags};
+
+/// 取meminfo中key字段的值（kB）
+fn field(meminfo: &str, key: &str) -> usize {
+    meminfo
+        .lines()
+        .find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix(':')?;
+            value.trim().trim_end_matches("kB").trim().parse().ok()
+        })
+        .unwrap_or(0)
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    let fd = open("/proc/meminfo\0", OpenFlags::RDONLY);
+    if fd == -1 {
+        println!("free: cannot open /proc/meminfo");
+        return -1;
+    }
+    let mut buf = [0u8; 512];
+    let len = read(fd as usize, &mut buf);
+    close(fd as usize);
+    if len <= 0 {
+        return -1;
+    }
+    let Ok(meminfo) = core::str::from_utf8(&buf[..len as usize]) else {
+        return -1;
+    };
+    let mem_total = field(meminfo, "MemTotal");
+    let mem_free = field(meminfo, "MemFree");
+    let heap_total = field(meminfo, "HeapTotal");
+    let heap_used = field(meminfo, "HeapUsed");
+    println!("{:<6}{:>10}{:>10}{:>10}", "", "total", "used", "free");
+    println!(
+        "{:<6}{:>10}{:>10}{:>10}",
+        "Mem:",
+        mem_total,
+        mem_total - mem_free,
+        mem_free
+    );
+    println!(
+        "{:<6}{:>10}{:>10}{:>10}",
+        "Heap:",
+        heap_total,
+        heap_used,
+        heap_total - heap_used
+    );
+    0
+}
@@ -0,0 +1,114 @@
+//! user/src/bin/ls.rs
+//! 列出各参数指定的目录中的文件，没有参数时列出当前工作目录，目录名后附加'/' <br>
+//! 参数为普通文件时只列出该文件；`-l`选项同时列出硬链接数与文件大小
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use user_lib::{close, fstat, getdents, open, Dirents, OpenFlags, Stat};
+
+/// 输出一个文件，long为true时同时输出其元数据
+fn print_entry(name: &str, stat: &Stat, long: bool) {
+    let suffix = if stat.is_dir() { "/" } else { "" };
+    if long {
+        let kind = if stat.is_dir() { 'd' } else { '-' };
+        println!(
+            "{} {:>3} {:>8} {}{}",
+            kind, stat.nlink, stat.size, name, suffix
+        );
+    } else {
+        println!("{}{}", name, suffix);
+    }
+}
+
+/// 获取path的元数据，失败时返回None
+fn stat_path(path: &str) -> Option<Stat> {
+    let mut path_z = String::from(path);
+    path_z.push('\0');
+    let fd = open(path_z.as_str(), OpenFlags::RDONLY);
+    if fd == -1 {
+        return None;
+    }
+    let mut stat = Stat::new();
+    let ret = fstat(fd as usize, &mut stat);
+    close(fd as usize);
+    if ret == 0 {
+        Some(stat)
+    } else {
+        None
+    }
+}
+
+/// 列出目录fd中的文件
+fn list_dir(fd: usize, path: &str, long: bool) {
+    let mut buf = [0u8; 512];
+    loop {
+        let len = getdents(fd, &mut buf);
+        if len <= 0 {
+            break;
+        }
+        for dirent in Dirents::new(&buf[..len as usize]) {
+            if dirent.name == "." || dirent.name == ".." {
+                continue;
+            }
+            let mut child = String::from(path);
+            child.push('/');
+            child.push_str(dirent.name);
+            match stat_path(child.as_str()) {
+                Some(stat) => print_entry(dirent.name, &stat, long),
+                None => println!("ls: cannot access {}", child),
+            }
+        }
+    }
+}
+
+/// 列出path，失败时返回false
+fn list(path: &str, long: bool) -> bool {
+    let mut path_z = String::from(path);
+    path_z.push('\0');
+    let fd = open(path_z.as_str(), OpenFlags::RDONLY);
+    if fd == -1 {
+        println!("ls: cannot access {}", path);
+        return false;
+    }
+    let fd = fd as usize;
+    let mut stat = Stat::new();
+    fstat(fd, &mut stat);
+    if stat.is_dir() {
+        list_dir(fd, path, long);
+    } else {
+        print_entry(path, &stat, long);
+    }
+    close(fd);
+    true
+}
+
+#[no_mangle]
+fn main(_argc: usize, argv: &[&str]) -> i32 {
+    let long = argv[1..].contains(&"-l");
+    let paths: Vec<&str> = argv[1..]
+        .iter()
+        .copied()
+        .filter(|arg| *arg != "-l")
+        .collect();
+    if paths.is_empty() {
+        return if list(".", long) { 0 } else { -1 };
+    }
+    let mut exit_code = 0;
+    for path in paths.iter() {
+        if paths.len() > 1 {
+            println!("{}:", path);
+        }
+        if !list(path, long) {
+            exit_code = -1;
+        }
+    }
+    exit_code
+}
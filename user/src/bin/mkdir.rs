@@ -0,0 +1,29 @@
+//! user/src/bin/mkdir.rs
+//! 创建各参数指定的目录
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+
+#[no_mangle]
+fn main(argc: usize, argv: &[&str]) -> i32 {
+    if argc <= 1 {
+        println!("usage: mkdir <dir>...");
+        return -1;
+    }
+    let mut exit_code = 0;
+    for path in &argv[1..] {
+        let mut path_z = String::from(*path);
+        path_z.push('\0');
+        if user_lib::mkdir(path_z.as_str()) == -1 {
+            println!("mkdir: cannot create directory {}", path);
+            exit_code = -1;
+        }
+    }
+    exit_code
+}
@@ -0,0 +1,87 @@
+//! user/src/bin/ps.rs
+//! 列出所有进程的pid、父进程pid、状态、内存占用与名称，信息取自/proc/<pid>/status
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use user_lib::{close, getdents, open, read, Dirents, OpenFlags};
+
+/// 读取path的全部内容，失败时返回None
+fn read_file(path: &str) -> Option<String> {
+    let fd = open(path, OpenFlags::RDONLY);
+    if fd == -1 {
+        return None;
+    }
+    let fd = fd as usize;
+    let mut content: Vec<u8> = Vec::new();
+    let mut buf = [0u8; 256];
+    loop {
+        let len = read(fd, &mut buf);
+        if len <= 0 {
+            break;
+        }
+        content.extend_from_slice(&buf[..len as usize]);
+    }
+    close(fd);
+    String::from_utf8(content).ok()
+}
+
+/// 取status中key字段的值
+fn field<'a>(status: &'a str, key: &str) -> &'a str {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(key)?.strip_prefix(":\t"))
+        .unwrap_or("?")
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    let fd = open("/proc\0", OpenFlags::RDONLY);
+    if fd == -1 {
+        println!("ps: cannot open /proc");
+        return -1;
+    }
+    let fd = fd as usize;
+    let mut pids: Vec<usize> = Vec::new();
+    let mut buf = [0u8; 512];
+    loop {
+        let len = getdents(fd, &mut buf);
+        if len <= 0 {
+            break;
+        }
+        for dirent in Dirents::new(&buf[..len as usize]) {
+            if let Ok(pid) = dirent.name.parse() {
+                pids.push(pid);
+            }
+        }
+    }
+    close(fd);
+    pids.sort_unstable();
+    println!(
+        "{:>5} {:>5} {:<12} {:>8} NAME",
+        "PID", "PPID", "STATE", "RSS"
+    );
+    for pid in pids {
+        // 进程可能在列出目录后退出
+        let Some(status) = read_file(format!("/proc/{}/status\0", pid).as_str()) else {
+            continue;
+        };
+        println!(
+            "{:>5} {:>5} {:<12} {:>8} {}",
+            pid,
+            field(&status, "PPid"),
+            field(&status, "State"),
+            field(&status, "VmRSS"),
+            field(&status, "Name")
+        );
+    }
+    0
+}
@@ -0,0 +1,22 @@
+//! user/src/bin/pwd.rs
+//! 输出当前工作目录
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+#[macro_use]
+extern crate user_lib;
+
+use user_lib::getcwd;
+
+#[no_mangle]
+fn main() -> i32 {
+    let mut buf = [0u8; 256];
+    let len = getcwd(&mut buf);
+    if len == -1 {
+        println!("pwd: path too long");
+        return -1;
+    }
+    println!("{}", core::str::from_utf8(&buf[..len as usize]).unwrap());
+    0
+}
@@ -0,0 +1,35 @@
+//! user/src/bin/rm.rs
+//! 删除各参数指定的文件，带`-d`参数时也可删除空目录
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+
+use user_lib::{rmdir, unlink};
+
+#[no_mangle]
+fn main(_argc: usize, argv: &[&str]) -> i32 {
+    let remove_dir = argv.len() > 1 && argv[1] == "-d";
+    let paths = if remove_dir { &argv[2..] } else { &argv[1..] };
+    if paths.is_empty() {
+        println!("usage: rm [-d] <path>...");
+        return -1;
+    }
+    let mut exit_code = 0;
+    for path in paths {
+        let mut path_z = String::from(*path);
+        path_z.push('\0');
+        // 先尝试作为文件删除，-d时再尝试作为空目录删除
+        if unlink(path_z.as_str()) == 0 || (remove_dir && rmdir(path_z.as_str()) == 0) {
+            continue;
+        }
+        println!("rm: cannot remove {}", path);
+        exit_code = -1;
+    }
+    exit_code
+}
@@ -0,0 +1,256 @@
+//! user/src/bin/user_shell.rs
+//! 用户shell：读取一行命令，从文件系统中加载并运行，等待其退出 <br>
+//! 支持命令行参数、`<`/`>`/`>>`重定向与`|`流水线，流水线中最后一条命令的退出码作为整行命令的退出码 <br>
+//! 内建命令`cd`切换工作目录；不含'/'的程序名在当前目录中找不到时到根目录中查找
+
+#![no_std]  //Delete std-lib, use rust-core-lib
+#![no_main] //Remove main() func
+
+extern crate alloc;
+#[macro_use]
+extern crate user_lib;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use user_lib::console::getchar;
+use user_lib::{chdir, close, dup2, exec, fork, open, pipe, waitpid, OpenFlags};
+
+const LF: u8 = 0x0au8;
+const CR: u8 = 0x0du8;
+const DL: u8 = 0x7fu8;
+const BS: u8 = 0x08u8;
+
+const STDIN: usize = 0;
+const STDOUT: usize = 1;
+
+/// 流水线中的一条命令，其中的字符串均以'\0'结尾
+struct ProcessArguments {
+    /// 输入重定向的文件名，为空时不重定向
+    input: String,
+    /// 输出重定向的文件名，为空时不重定向
+    output: String,
+    /// 输出重定向时是否追加写入（`>>`）
+    append: bool,
+    args_copy: Vec<String>,
+    /// 各参数的起始地址，以空指针结尾，传递给exec
+    args_addr: Vec<*const u8>,
+}
+
+impl ProcessArguments {
+    /// 由一条命令的各个词法单元构造，缺少重定向文件名或程序名时返回错误信息
+    fn new(tokens: &[String]) -> Result<Self, &'static str> {
+        let mut input = String::new();
+        let mut output = String::new();
+        let mut append = false;
+        let mut args_copy: Vec<String> = Vec::new();
+        let mut iter = tokens.iter();
+        while let Some(token) = iter.next() {
+            match token.as_str() {
+                "<" => {
+                    input = iter.next().ok_or("missing file name after '<'")?.clone();
+                    input.push('\0');
+                }
+                ">" | ">>" => {
+                    append = token == ">>";
+                    output = iter.next().ok_or("missing file name after '>'")?.clone();
+                    output.push('\0');
+                }
+                _ => {
+                    let mut arg = token.clone();
+                    arg.push('\0');
+                    args_copy.push(arg);
+                }
+            }
+        }
+        if args_copy.is_empty() {
+            return Err("missing command");
+        }
+        let mut args_addr: Vec<*const u8> = args_copy.iter().map(|arg| arg.as_ptr()).collect();
+        args_addr.push(core::ptr::null::<u8>());
+        Ok(Self {
+            input,
+            output,
+            append,
+            args_copy,
+            args_addr,
+        })
+    }
+}
+
+/// 将一行命令切分为词法单元，`<`、`>`、`>>`与`|`无需以空白分隔
+fn tokenize(line: &str) -> Vec<String> {
+    let mut tokens: Vec<String> = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' | '\t' => {
+                if !current.is_empty() {
+                    tokens.push(core::mem::take(&mut current));
+                }
+            }
+            '<' | '>' | '|' => {
+                if !current.is_empty() {
+                    tokens.push(core::mem::take(&mut current));
+                }
+                let mut op = String::from(c);
+                if c == '>' && chars.peek() == Some(&'>') {
+                    chars.next();
+                    op.push('>');
+                }
+                tokens.push(op);
+            }
+            _ => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// 解析一行命令为流水线中的各条命令
+fn parse(line: &str) -> Result<Vec<ProcessArguments>, &'static str> {
+    let tokens = tokenize(line);
+    tokens
+        .split(|token| token == "|")
+        .map(ProcessArguments::new)
+        .collect()
+}
+
+/// 在子进程中按重定向打开文件并替换标准输入输出，失败时返回false
+fn redirect(process: &ProcessArguments) -> bool {
+    if !process.input.is_empty() {
+        let input_fd = open(process.input.as_str(), OpenFlags::RDONLY);
+        if input_fd == -1 {
+            println!("Error when opening file {}", process.input.trim_end_matches('\0'));
+            return false;
+        }
+        dup2(input_fd as usize, STDIN);
+        close(input_fd as usize);
+    }
+    if !process.output.is_empty() {
+        let mode = if process.append {
+            OpenFlags::APPEND
+        } else {
+            OpenFlags::TRUNC
+        };
+        let output_fd = open(
+            process.output.as_str(),
+            OpenFlags::CREATE | OpenFlags::WRONLY | mode,
+        );
+        if output_fd == -1 {
+            println!("Error when opening file {}", process.output.trim_end_matches('\0'));
+            return false;
+        }
+        dup2(output_fd as usize, STDOUT);
+        close(output_fd as usize);
+    }
+    true
+}
+
+/// 内建命令cd，不带参数时切换至根目录
+fn change_dir(process: &ProcessArguments) {
+    let path = process.args_copy.get(1).map_or("/\0", |path| path.as_str());
+    if chdir(path) == -1 {
+        println!("cd: {}: No such directory", path.trim_end_matches('\0'));
+    }
+}
+
+/// 运行一条流水线，等待其中所有进程退出，返回(最后一个进程的pid, 其退出码)
+fn run_pipeline(processes: &[ProcessArguments]) -> (isize, i32) {
+    // 相邻两条命令之间以管道相连
+    let mut pipes: Vec<[usize; 2]> = Vec::new();
+    for _ in 1..processes.len() {
+        let mut pipe_fd = [0usize; 2];
+        pipe(&mut pipe_fd);
+        pipes.push(pipe_fd);
+    }
+    let mut children: Vec<isize> = Vec::new();
+    for (i, process) in processes.iter().enumerate() {
+        let pid = fork();
+        if pid == 0 {
+            // 子进程：先接上管道，显式的重定向优先于管道
+            if i > 0 {
+                dup2(pipes[i - 1][0], STDIN);
+            }
+            if i < processes.len() - 1 {
+                dup2(pipes[i][1], STDOUT);
+            }
+            for pipe_fd in pipes.iter() {
+                close(pipe_fd[0]);
+                close(pipe_fd[1]);
+            }
+            if !redirect(process) {
+                user_lib::exit(-4);
+            }
+            let app = process.args_copy[0].as_str();
+            exec(app, process.args_addr.as_slice());
+            if !app.contains('/') {
+                // 应用均位于根目录中
+                let mut app_in_root = String::from("/");
+                app_in_root.push_str(app);
+                exec(app_in_root.as_str(), process.args_addr.as_slice());
+            }
+            println!("Error when executing!");
+            user_lib::exit(-4);
+            unreachable!();
+        }
+        children.push(pid);
+    }
+    // 父进程关闭所有管道，使读端能在写端进程退出后读到EOF
+    for pipe_fd in pipes.iter() {
+        close(pipe_fd[0]);
+        close(pipe_fd[1]);
+    }
+    let mut exit_code: i32 = 0;
+    for pid in children.iter() {
+        let exit_pid = waitpid(*pid as usize, &mut exit_code);
+        assert_eq!(*pid, exit_pid);
+    }
+    (*children.last().unwrap(), exit_code)
+}
+
+#[no_mangle]
+fn main() -> i32 {
+    println!("Rust user shell");
+    let mut line: String = String::new();
+    print!(">> ");
+    loop {
+        let c = getchar();
+        match c {
+            LF | CR => {
+                println!("");
+                if !line.trim().is_empty() {
+                    match parse(line.as_str()) {
+                        Ok(processes)
+                            if processes.len() == 1 && processes[0].args_copy[0] == "cd\0" =>
+                        {
+                            change_dir(&processes[0]);
+                        }
+                        Ok(processes) => {
+                            let (pid, exit_code) = run_pipeline(&processes);
+                            println!("Shell: Process {} exited with code {}", pid, exit_code);
+                        }
+                        Err(msg) => println!("Shell: syntax error: {}", msg),
+                    }
+                }
+                line.clear();
+                print!(">> ");
+            }
+            BS | DL => {
+                if !line.is_empty() {
+                    print!("{}", BS as char);
+                    print!(" ");
+                    print!("{}", BS as char);
+                    line.pop();
+                }
+            }
+            _ => {
+                print!("{}", c as char);
+                line.push(c as char);
+            }
+        }
+    }
+}
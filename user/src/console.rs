@@ -1,15 +1,17 @@
 //! user/src/console.rs <br>
-//! declare of console output
+//! declare of console input and output
 
-/* print()     Func    print sth <br>
+/* getchar()   Func    get a char from stdin <br>
+ * print()     Func    print sth <br>
  * print!      Macro   print <br>
  * println!    Macro   `println!` <br>
  */
 
 use core::fmt::{self, Write};
 
-use super::write;
+use super::{read, write};
 
+const STDIN: usize = 0;
 const STDOUT: usize = 1;
 
 struct Stdout; // Unit-like structs
@@ -22,6 +24,13 @@ impl Write for Stdout {
     }
 }
 
+/// 从标准输入读取一个字符
+pub fn getchar() -> u8 {
+    let mut c = [0u8; 1];
+    read(STDIN, &mut c);
+    c[0]
+}
+
 pub fn print(args: fmt::Arguments) {
     Stdout.write_fmt(args).unwrap();
 }
@@ -1,25 +1,210 @@
 #![no_std]
 #![feature(linkage)]            // 启用弱链接特性
 #![feature(panic_info_message)] // 启用panic_info_message特性
+#![feature(alloc_error_handler)] // 启用alloc_error_handler特性
 
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::sync::atomic::AtomicU32;
+
+use bitflags::bitflags;
+use buddy_system_allocator::LockedHeap;
 use sys_call::*;
 
 #[macro_use]
 pub mod console;
 mod lang_items;
+pub mod sync;
 mod sys_call;
 
+/// 时间值
+#[repr(C)]
+#[derive(Debug, Default)]
+pub struct TimeVal {
+    pub sec: usize,
+    pub usec: usize,
+}
+
+bitflags! {
+    /// 打开文件的标志，与内核中的定义保持一致
+    pub struct OpenFlags: u32 {
+        const RDONLY = 0;
+        const WRONLY = 1 << 0;
+        const RDWR = 1 << 1;
+        const CREATE = 0o100;
+        const TRUNC = 0o1000;
+        const APPEND = 0o2000;
+    }
+}
+
+/// 文件元数据，与内核中的定义保持一致
+#[repr(C)]
+#[derive(Debug)]
+pub struct Stat {
+    /// 文件所在磁盘驱动器号
+    pub dev: u64,
+    /// inode编号
+    pub ino: u64,
+    /// 文件类型
+    pub mode: StatMode,
+    /// 硬链接数
+    pub nlink: u32,
+    /// 文件大小（字节）
+    pub size: u64,
+    pad: [u64; 6],
+}
+
+impl Stat {
+    pub fn new() -> Self {
+        Self {
+            dev: 0,
+            ino: 0,
+            mode: StatMode::NULL,
+            nlink: 0,
+            size: 0,
+            pad: [0; 6],
+        }
+    }
+
+    pub fn is_dir(&self) -> bool {
+        self.mode.contains(StatMode::DIR)
+    }
+
+    pub fn is_file(&self) -> bool {
+        self.mode.contains(StatMode::FILE)
+    }
+}
+
+impl Default for Stat {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+bitflags! {
+    /// 文件类型，与内核中的定义保持一致
+    pub struct StatMode: u32 {
+        const NULL = 0;
+        /// 目录
+        const DIR = 0o040000;
+        /// 普通文件
+        const FILE = 0o100000;
+        /// 字符设备
+        const CHAR = 0o020000;
+        /// 管道
+        const FIFO = 0o010000;
+    }
+}
+
+bitflags! {
+    /// mmap的内存保护标志，与内核中的定义保持一致
+    pub struct MmapProt: u32 {
+        const READ = 1 << 0;
+        const WRITE = 1 << 1;
+        const EXEC = 1 << 2;
+    }
+}
+
+bitflags! {
+    /// mmap的映射标志，与内核中的定义保持一致
+    pub struct MmapFlags: u32 {
+        /// 修改对映射同一文件的其他进程可见，并写回文件
+        const SHARED = 0x01;
+        /// 写入时复制，修改仅对本进程可见
+        const PRIVATE = 0x02;
+        /// 不映射文件，各页初始为0
+        const ANONYMOUS = 0x20;
+    }
+}
+
+/// 目录项类型：目录
+pub const DT_DIR: u8 = 4;
+/// 目录项类型：普通文件
+pub const DT_REG: u8 = 8;
+
+/// getdents读出的一个目录项
+pub struct Dirent<'a> {
+    pub ino: u64,
+    pub d_type: u8,
+    pub name: &'a str,
+}
+
+/// 遍历getdents写入缓冲区中的目录项
+pub struct Dirents<'a> {
+    buf: &'a [u8],
+}
+
+impl<'a> Dirents<'a> {
+    /// buf为getdents写入的有效部分
+    pub fn new(buf: &'a [u8]) -> Self {
+        Self { buf }
+    }
+}
+
+impl<'a> Iterator for Dirents<'a> {
+    type Item = Dirent<'a>;
+    fn next(&mut self) -> Option<Self::Item> {
+        // d_ino(8) d_off(8) d_reclen(2) d_type(1) d_name(以'\0'结尾)
+        if self.buf.len() < 19 {
+            return None;
+        }
+        let ino = u64::from_le_bytes(self.buf[0..8].try_into().unwrap());
+        let reclen = u16::from_le_bytes(self.buf[16..18].try_into().unwrap()) as usize;
+        let d_type = self.buf[18];
+        let name_bytes = &self.buf[19..reclen];
+        let name_len = name_bytes.iter().position(|c| *c == 0).unwrap_or(name_bytes.len());
+        let name = core::str::from_utf8(&name_bytes[..name_len]).unwrap();
+        self.buf = &self.buf[reclen..];
+        Some(Dirent { ino, d_type, name })
+    }
+}
+
+/// 用户堆大小
+const USER_HEAP_SIZE: usize = 16384;
+
+/// 用户堆空间，位于.bss段中
+static mut HEAP_SPACE: [u8; USER_HEAP_SIZE] = [0; USER_HEAP_SIZE];
+
+#[global_allocator]
+static HEAP: LockedHeap = LockedHeap::empty();
+
+/// 堆内存分配失败时的处理函数
+#[alloc_error_handler]
+pub fn handle_alloc_error(layout: core::alloc::Layout) -> ! {
+    panic!("Heap allocation error, layout = {:?}", layout);
+}
+
 #[no_mangle]
 #[link_section = ".text.entry"] // 定义该段为entry段，方便调整内存布局
-pub extern "C" fn _start() -> ! {
+pub extern "C" fn _start(argc: usize, argv: usize) -> ! {
     clear_bss();  //当使用半系统模拟时，注释掉
-    exit(main());
+    unsafe {
+        HEAP.lock()
+            .init(core::ptr::addr_of!(HEAP_SPACE) as usize, USER_HEAP_SIZE);
+    }
+    // 内核将命令行参数压在用户栈上，argv为以'\0'结尾的字符串的指针数组
+    let mut v: Vec<&'static str> = Vec::new();
+    for i in 0..argc {
+        let str_start =
+            unsafe { ((argv + i * core::mem::size_of::<usize>()) as *const usize).read_volatile() };
+        let len = (0usize..)
+            .find(|i| unsafe { ((str_start + *i) as *const u8).read_volatile() == 0 })
+            .unwrap();
+        v.push(
+            core::str::from_utf8(unsafe {
+                core::slice::from_raw_parts(str_start as *const u8, len)
+            })
+            .unwrap(),
+        );
+    }
+    exit(main(argc, v.as_slice()));
     panic!("unreachable after sys_exit!");
 }
 
 #[linkage = "weak"] // 弱链接，使得当用户程序没有main函数时自动链接至此main函数
 #[no_mangle]
-fn main() -> i32 {
+fn main(_argc: usize, _argv: &[&str]) -> i32 {
     panic!("Cannot find main!");
 }
 
@@ -34,10 +219,257 @@ fn clear_bss() {
     });
 }
 
+/// 表示相对于当前工作目录解析路径的dirfd
+pub const AT_FDCWD: isize = -100;
+/// unlinkat的标志：删除目录
+pub const AT_REMOVEDIR: u32 = 0x200;
+
+/// 打开文件，path须以'\0'结尾，相对路径从当前工作目录出发
+pub fn open(path: &str, flags: OpenFlags) -> isize {
+    sys_openat(AT_FDCWD, path, flags.bits)
+}
+
+/// 创建目录，path须以'\0'结尾
+pub fn mkdir(path: &str) -> isize {
+    sys_mkdirat(AT_FDCWD, path, 0)
+}
+
+/// 删除文件，path须以'\0'结尾
+pub fn unlink(path: &str) -> isize {
+    sys_unlinkat(AT_FDCWD, path, 0)
+}
+
+/// 删除空目录，path须以'\0'结尾
+pub fn rmdir(path: &str) -> isize {
+    sys_unlinkat(AT_FDCWD, path, AT_REMOVEDIR)
+}
+
+/// 为old_path创建硬链接new_path，路径须以'\0'结尾
+pub fn link(old_path: &str, new_path: &str) -> isize {
+    sys_linkat(AT_FDCWD, old_path, AT_FDCWD, new_path, 0)
+}
+
+/// 切换当前工作目录，path须以'\0'结尾
+pub fn chdir(path: &str) -> isize {
+    sys_chdir(path)
+}
+
+/// 将当前工作目录的绝对路径写入buf，返回路径长度（不含'\0'），buf过小时返回-1
+pub fn getcwd(buf: &mut [u8]) -> isize {
+    match sys_getcwd(buf) {
+        -1 => -1,
+        size => size - 1,
+    }
+}
+
+/// 读取目录fd中的目录项至buf，可用`Dirents`遍历
+pub fn getdents(fd: usize, buf: &mut [u8]) -> isize {
+    sys_getdents64(fd, buf)
+}
+
+pub fn close(fd: usize) -> isize {
+    sys_close(fd)
+}
+
+/// 创建管道，pipe_fd[0]为读端，pipe_fd[1]为写端
+pub fn pipe(pipe_fd: &mut [usize]) -> isize {
+    sys_pipe(pipe_fd)
+}
+
+pub fn dup(fd: usize) -> isize {
+    sys_dup(fd)
+}
+
+pub fn dup2(old_fd: usize, new_fd: usize) -> isize {
+    sys_dup2(old_fd, new_fd)
+}
+
+pub fn read(fd: usize, buf: &mut [u8]) -> isize {
+    sys_read(fd, buf)
+}
+
 pub fn write(fd: usize, buf: &[u8]) -> isize {
     sys_write(fd, buf)
 }
 
+/// 获取fd对应文件的元数据
+pub fn fstat(fd: usize, st: &mut Stat) -> isize {
+    sys_fstat(fd, st)
+}
+
+pub fn sync() -> isize {
+    sys_sync()
+}
+
+pub fn fsync(fd: usize) -> isize {
+    sys_fsync(fd)
+}
+
 pub fn exit(exit_code: i32) -> isize {
     sys_exit(exit_code)
-}
\ No newline at end of file
+}
+
+pub fn yield_() -> isize {
+    sys_yield()
+}
+
+/// 设置当前线程的优先级，数值越大优先级越高，成功时返回prio
+pub fn set_priority(prio: isize) -> isize {
+    sys_set_priority(prio)
+}
+
+/// 获取当前线程的有效优先级
+pub fn get_priority() -> isize {
+    sys_get_priority()
+}
+
+/// 获取以微秒为单位的当前时间
+pub fn get_time_us() -> usize {
+    let mut time = TimeVal::default();
+    sys_get_time(&mut time, 0);
+    time.sec * 1_000_000 + time.usec
+}
+
+pub fn getpid() -> isize {
+    sys_getpid()
+}
+
+pub fn fork() -> isize {
+    sys_fork()
+}
+
+/// 创建从entry开始执行的线程，arg作为其第一个参数，返回新线程的tid <br>
+/// 线程函数须以exit结束，不能直接返回
+pub fn thread_create(entry: usize, arg: usize) -> isize {
+    sys_thread_create(entry, arg)
+}
+
+pub fn gettid() -> isize {
+    sys_gettid()
+}
+
+/// 等待当前进程中tid为tid的线程退出，返回其退出码；线程不存在时返回-1
+pub fn waittid(tid: usize) -> isize {
+    loop {
+        match sys_waittid(tid) {
+            -2 => {
+                yield_();
+            }
+            exit_code => return exit_code,
+        }
+    }
+}
+
+/// 创建自旋锁，返回其编号
+pub fn mutex_create() -> isize {
+    sys_mutex_create(false)
+}
+
+/// 创建阻塞锁，返回其编号
+pub fn mutex_blocking_create() -> isize {
+    sys_mutex_create(true)
+}
+
+pub fn mutex_lock(mutex_id: usize) -> isize {
+    sys_mutex_lock(mutex_id)
+}
+
+pub fn mutex_unlock(mutex_id: usize) -> isize {
+    sys_mutex_unlock(mutex_id)
+}
+
+/// 创建初始计数为res_count的信号量，返回其编号
+pub fn semaphore_create(res_count: usize) -> isize {
+    sys_semaphore_create(res_count)
+}
+
+pub fn semaphore_up(sem_id: usize) -> isize {
+    sys_semaphore_up(sem_id)
+}
+
+pub fn semaphore_down(sem_id: usize) -> isize {
+    sys_semaphore_down(sem_id)
+}
+
+/// 创建条件变量，返回其编号
+pub fn condvar_create() -> isize {
+    sys_condvar_create()
+}
+
+pub fn condvar_signal(condvar_id: usize) -> isize {
+    sys_condvar_signal(condvar_id)
+}
+
+/// 释放mutex_id对应的互斥锁并等待条件变量，返回前重新获取该互斥锁
+pub fn condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
+    sys_condvar_wait(condvar_id, mutex_id)
+}
+
+/// 开启死锁检测后，可能导致死锁的mutex_lock与semaphore_down返回的错误码
+pub const EDEADLOCK: isize = -0xDEAD;
+
+/// 开启或关闭当前进程的死锁检测
+pub fn enable_deadlock_detect(enabled: bool) -> isize {
+    sys_enable_deadlock_detect(enabled as usize)
+}
+
+const FUTEX_WAIT: usize = 0;
+const FUTEX_WAKE: usize = 1;
+
+/// futex的值仍为val时阻塞直至被futex_wake唤醒，值已改变时立即返回-2
+pub fn futex_wait(futex: &AtomicU32, val: u32) -> isize {
+    sys_futex(futex.as_ptr() as usize, FUTEX_WAIT, val as usize)
+}
+
+/// 唤醒至多count个在futex上等待的线程，返回唤醒的线程数
+pub fn futex_wake(futex: &AtomicU32, count: usize) -> isize {
+    sys_futex(futex.as_ptr() as usize, FUTEX_WAKE, count)
+}
+
+/// 将fd对应文件自offset起的len字节映射到地址空间，返回映射的起始地址，失败时返回-1 <br>
+/// 匿名映射（flags含ANONYMOUS）时忽略fd
+pub fn mmap(
+    addr: usize,
+    len: usize,
+    prot: MmapProt,
+    flags: MmapFlags,
+    fd: usize,
+    offset: usize,
+) -> isize {
+    sys_mmap(addr, len, prot.bits, flags.bits, fd, offset)
+}
+
+/// 解除[addr, addr + len)中的内存映射
+pub fn munmap(addr: usize, len: usize) -> isize {
+    sys_munmap(addr, len)
+}
+
+/// 加载名为path的应用替换当前进程，path与各参数须以'\0'结尾，args须以空指针结尾
+pub fn exec(path: &str, args: &[*const u8]) -> isize {
+    sys_exec(path, args)
+}
+
+/// 等待任意一个子进程退出，返回其pid，退出码写入exit_code
+pub fn wait(exit_code: &mut i32) -> isize {
+    loop {
+        match sys_waitpid(-1, exit_code as *mut _) {
+            -2 => {
+                yield_();
+            }
+            // -1或被回收的子进程pid
+            exit_pid => return exit_pid,
+        }
+    }
+}
+
+/// 等待pid为pid的子进程退出，返回其pid，退出码写入exit_code
+pub fn waitpid(pid: usize, exit_code: &mut i32) -> isize {
+    loop {
+        match sys_waitpid(pid as isize, exit_code as *mut _) {
+            -2 => {
+                yield_();
+            }
+            exit_pid => return exit_pid,
+        }
+    }
+}
@@ -0,0 +1,221 @@
+//! user/src/sync.rs <br>
+//! 基于futex的用户态同步原语：无竞争时仅在用户态操作原子变量，只有需要等待或唤醒其他线程时才陷入内核
+
+use core::cell::UnsafeCell;
+use core::ops::{Deref, DerefMut};
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use crate::{futex_wait, futex_wake};
+
+/// 陷入内核等待前在用户态自旋的次数
+const SPIN_LIMIT: usize = 100;
+
+/// 互斥锁未被占用
+const UNLOCKED: u32 = 0;
+/// 互斥锁被占用，且没有线程在内核中等待
+const LOCKED: u32 = 1;
+/// 互斥锁被占用，可能有线程在内核中等待
+const CONTENDED: u32 = 2;
+
+/// 保护数据T的互斥锁
+pub struct Mutex<T> {
+    state: AtomicU32,
+    data: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for Mutex<T> {}
+unsafe impl<T: Send> Sync for Mutex<T> {}
+
+impl<T> Mutex<T> {
+    pub const fn new(data: T) -> Self {
+        Self {
+            state: AtomicU32::new(UNLOCKED),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// 获取锁，锁被占用时先自旋，仍未获得时在futex上等待
+    pub fn lock(&self) -> MutexGuard<'_, T> {
+        if self
+            .state
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            self.lock_contended();
+        }
+        MutexGuard { mutex: self }
+    }
+
+    /// 尝试获取锁，锁被占用时返回None
+    pub fn try_lock(&self) -> Option<MutexGuard<'_, T>> {
+        self.state
+            .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+            .ok()
+            .map(|_| MutexGuard { mutex: self })
+    }
+
+    fn lock_contended(&self) {
+        for _ in 0..SPIN_LIMIT {
+            if self
+                .state
+                .compare_exchange(UNLOCKED, LOCKED, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+            {
+                return;
+            }
+            core::hint::spin_loop();
+        }
+        // 将状态置为CONTENDED，使持有者释放锁时唤醒等待者；交换出UNLOCKED即获得了锁
+        while self.state.swap(CONTENDED, Ordering::Acquire) != UNLOCKED {
+            futex_wait(&self.state, CONTENDED);
+        }
+    }
+
+    fn unlock(&self) {
+        if self.state.swap(UNLOCKED, Ordering::Release) == CONTENDED {
+            futex_wake(&self.state, 1);
+        }
+    }
+}
+
+/// 互斥锁的守卫，析构时释放锁
+pub struct MutexGuard<'a, T> {
+    mutex: &'a Mutex<T>,
+}
+
+impl<T> Deref for MutexGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.mutex.data.get() }
+    }
+}
+
+impl<T> DerefMut for MutexGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.mutex.data.get() }
+    }
+}
+
+impl<T> Drop for MutexGuard<'_, T> {
+    fn drop(&mut self) {
+        self.mutex.unlock();
+    }
+}
+
+/// 条件变量：每次通知递增序号，等待者在futex上等待序号改变
+pub struct Condvar {
+    seq: AtomicU32,
+    /// 正在等待的线程数，为0时通知无需陷入内核
+    waiters: AtomicU32,
+}
+
+impl Condvar {
+    pub const fn new() -> Self {
+        Self {
+            seq: AtomicU32::new(0),
+            waiters: AtomicU32::new(0),
+        }
+    }
+
+    /// 释放guard对应的互斥锁并等待通知，返回前重新获取该互斥锁 <br>
+    /// 可能被虚假唤醒，调用者应在循环中检查等待的条件
+    pub fn wait<'a, T>(&self, guard: MutexGuard<'a, T>) -> MutexGuard<'a, T> {
+        let mutex = guard.mutex;
+        self.waiters.fetch_add(1, Ordering::SeqCst);
+        // 在释放锁之前读取序号，释放锁之后发出的通知必然改变序号
+        let seq = self.seq.load(Ordering::SeqCst);
+        drop(guard);
+        futex_wait(&self.seq, seq);
+        self.waiters.fetch_sub(1, Ordering::SeqCst);
+        mutex.lock()
+    }
+
+    /// 唤醒一个等待的线程
+    pub fn notify_one(&self) {
+        self.seq.fetch_add(1, Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            futex_wake(&self.seq, 1);
+        }
+    }
+
+    /// 唤醒所有等待的线程
+    pub fn notify_all(&self) {
+        self.seq.fetch_add(1, Ordering::SeqCst);
+        if self.waiters.load(Ordering::SeqCst) > 0 {
+            futex_wake(&self.seq, usize::MAX);
+        }
+    }
+}
+
+impl Default for Condvar {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 初始化尚未开始
+const INCOMPLETE: u32 = 0;
+/// 初始化正在进行，且没有线程在内核中等待
+const RUNNING: u32 = 1;
+/// 初始化正在进行，可能有线程在内核中等待
+const RUNNING_CONTENDED: u32 = 2;
+/// 初始化已完成
+const COMPLETE: u32 = 3;
+
+/// 一次性初始化：无论多少线程调用call_once，闭包只执行一次，且所有调用都在其执行完后返回
+pub struct Once {
+    state: AtomicU32,
+}
+
+impl Once {
+    pub const fn new() -> Self {
+        Self {
+            state: AtomicU32::new(INCOMPLETE),
+        }
+    }
+
+    pub fn is_completed(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    pub fn call_once<F: FnOnce()>(&self, f: F) {
+        if self.is_completed() {
+            return;
+        }
+        if self
+            .state
+            .compare_exchange(INCOMPLETE, RUNNING, Ordering::Acquire, Ordering::Acquire)
+            .is_ok()
+        {
+            f();
+            if self.state.swap(COMPLETE, Ordering::Release) == RUNNING_CONTENDED {
+                futex_wake(&self.state, usize::MAX);
+            }
+            return;
+        }
+        // 其他线程正在初始化，等待其完成
+        loop {
+            match self.state.load(Ordering::Acquire) {
+                COMPLETE => return,
+                RUNNING => {
+                    let _ = self.state.compare_exchange(
+                        RUNNING,
+                        RUNNING_CONTENDED,
+                        Ordering::Relaxed,
+                        Ordering::Relaxed,
+                    );
+                }
+                _ => {
+                    futex_wait(&self.state, RUNNING_CONTENDED);
+                }
+            }
+        }
+    }
+}
+
+impl Default for Once {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -8,8 +8,49 @@
 
 use core::arch::asm;
 
+use super::{Stat, TimeVal};
+
+const SYSCALL_GETCWD: usize = 17;
+const SYSCALL_DUP: usize = 23;
+const SYSCALL_DUP2: usize = 24;
+const SYSCALL_MKDIRAT: usize = 34;
+const SYSCALL_UNLINKAT: usize = 35;
+const SYSCALL_LINKAT: usize = 37;
+const SYSCALL_CHDIR: usize = 49;
+const SYSCALL_OPENAT: usize = 56;
+const SYSCALL_CLOSE: usize = 57;
+const SYSCALL_PIPE: usize = 59;
+const SYSCALL_GETDENTS64: usize = 61;
+const SYSCALL_READ: usize = 63;
 const SYSCALL_WRITE: usize = 64;
+const SYSCALL_FSTAT: usize = 80;
+const SYSCALL_SYNC: usize = 81;
+const SYSCALL_FSYNC: usize = 82;
 const SYSCALL_EXIT: usize = 93;
+const SYSCALL_FUTEX: usize = 98;
+const SYSCALL_YIELD: usize = 124;
+const SYSCALL_SET_PRIORITY: usize = 140;
+const SYSCALL_GET_PRIORITY: usize = 141;
+const SYSCALL_GET_TIME: usize = 169;
+const SYSCALL_GETPID: usize = 172;
+const SYSCALL_MUNMAP: usize = 215;
+const SYSCALL_FORK: usize = 220;
+const SYSCALL_EXEC: usize = 221;
+const SYSCALL_MMAP: usize = 222;
+const SYSCALL_WAITPID: usize = 260;
+const SYSCALL_ENABLE_DEADLOCK_DETECT: usize = 469;
+const SYSCALL_THREAD_CREATE: usize = 1000;
+const SYSCALL_GETTID: usize = 1001;
+const SYSCALL_WAITTID: usize = 1002;
+const SYSCALL_MUTEX_CREATE: usize = 1010;
+const SYSCALL_MUTEX_LOCK: usize = 1011;
+const SYSCALL_MUTEX_UNLOCK: usize = 1012;
+const SYSCALL_SEMAPHORE_CREATE: usize = 1020;
+const SYSCALL_SEMAPHORE_UP: usize = 1021;
+const SYSCALL_SEMAPHORE_DOWN: usize = 1022;
+const SYSCALL_CONDVAR_CREATE: usize = 1030;
+const SYSCALL_CONDVAR_SIGNAL: usize = 1031;
+const SYSCALL_CONDVAR_WAIT: usize = 1032;
 
 fn syscall(id: usize, args: [usize; 3]) -> isize {
     let mut ret: isize;
@@ -25,22 +66,422 @@ fn syscall(id: usize, args: [usize; 3]) -> isize {
     ret
 }
 
+/// 参数多于3个的系统调用
+fn syscall6(id: usize, args: [usize; 6]) -> isize {
+    let mut ret: isize;
+    unsafe {
+        asm!(
+        "ecall",
+        inlateout("x10") args[0] => ret,
+        in("x11") args[1],
+        in("x12") args[2],
+        in("x13") args[3],
+        in("x14") args[4],
+        in("x15") args[5],
+        in("x17") id
+        );
+    }
+    ret
+}
+
+/// **功能：** 打开一个文件，并返回可以访问它的文件描述符。 <br>
+/// **参数：**  <br>
+///         - `dirfd` 表示解析相对路径的起点目录的文件描述符，为`AT_FDCWD`时从当前工作目录出发；<br>
+///         - `path` 描述要打开的文件的路径，须以'\0'结尾；<br>
+///         - `flags` 描述打开文件的标志，取值见`OpenFlags`。<br>
+/// **返回值：** 成功时返回文件描述符，失败时返回 -1。<br>
+/// **syscall ID：** 56
+pub fn sys_openat(dirfd: isize, path: &str, flags: u32) -> isize {
+    syscall(SYSCALL_OPENAT, [dirfd as usize, path.as_ptr() as usize, flags as usize])
+}
+
+/// **功能：** 创建一个目录。 <br>
+/// **参数：**  <br>
+///         - `dirfd` 表示解析相对路径的起点目录的文件描述符；<br>
+///         - `path` 表示要创建的目录的路径，须以'\0'结尾；<br>
+///         - `mode` 表示目录的权限，目前被忽略。<br>
+/// **返回值：** 成功返回 0，父目录不存在或同名文件已存在时返回 -1。<br>
+/// **syscall ID：** 34
+pub fn sys_mkdirat(dirfd: isize, path: &str, mode: u32) -> isize {
+    syscall(SYSCALL_MKDIRAT, [dirfd as usize, path.as_ptr() as usize, mode as usize])
+}
+
+/// **功能：** 删除一个目录项。 <br>
+/// **参数：**  <br>
+///         - `dirfd` 表示解析相对路径的起点目录的文件描述符；<br>
+///         - `path` 表示要删除的目录项的路径，须以'\0'结尾；<br>
+///         - `flags` 为`AT_REMOVEDIR`时删除空目录，为 0 时删除文件。<br>
+/// **返回值：** 成功返回 0，失败返回 -1。<br>
+/// **syscall ID：** 35
+pub fn sys_unlinkat(dirfd: isize, path: &str, flags: u32) -> isize {
+    syscall(SYSCALL_UNLINKAT, [dirfd as usize, path.as_ptr() as usize, flags as usize])
+}
+
+/// **功能：** 为文件创建一个硬链接。 <br>
+/// **参数：**  <br>
+///         - `old_dirfd` `old_path` 表示已有的文件，不能是目录；<br>
+///         - `new_dirfd` `new_path` 表示新建的硬链接，路径须以'\0'结尾；<br>
+///         - `flags` 目前被忽略。<br>
+/// **返回值：** 成功返回 0，失败返回 -1。<br>
+/// **syscall ID：** 37
+pub fn sys_linkat(
+    old_dirfd: isize,
+    old_path: &str,
+    new_dirfd: isize,
+    new_path: &str,
+    flags: u32,
+) -> isize {
+    syscall6(
+        SYSCALL_LINKAT,
+        [
+            old_dirfd as usize,
+            old_path.as_ptr() as usize,
+            new_dirfd as usize,
+            new_path.as_ptr() as usize,
+            flags as usize,
+            0,
+        ],
+    )
+}
+
+/// **功能：** 切换当前工作目录。 <br>
+/// **参数：**  <br>
+///         - `path` 表示目标目录的路径，须以'\0'结尾。<br>
+/// **返回值：** 成功返回 0，目录不存在时返回 -1。<br>
+/// **syscall ID：** 49
+pub fn sys_chdir(path: &str) -> isize {
+    syscall(SYSCALL_CHDIR, [path.as_ptr() as usize, 0, 0])
+}
+
+/// **功能：** 获取当前工作目录的绝对路径。 <br>
+/// **参数：**  <br>
+///         - `buf` 表示存放路径的缓冲区，路径以'\0'结尾。<br>
+/// **返回值：** 成功时返回写入的字节数（含'\0'），缓冲区过小时返回 -1。<br>
+/// **syscall ID：** 17
+pub fn sys_getcwd(buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_GETCWD, [buf.as_mut_ptr() as usize, buf.len(), 0])
+}
+
+/// **功能：** 读取目录中的目录项。 <br>
+/// **参数：**  <br>
+///         - `fd` 表示已打开的目录的文件描述符；<br>
+///         - `buf` 表示存放目录项的缓冲区，目录项的格式与 Linux 的`linux_dirent64`相同。<br>
+/// **返回值：** 成功时返回写入的字节数，已读完时返回 0，出错时返回 -1。<br>
+/// **syscall ID：** 61
+pub fn sys_getdents64(fd: usize, buf: &mut [u8]) -> isize {
+    syscall(SYSCALL_GETDENTS64, [fd, buf.as_mut_ptr() as usize, buf.len()])
+}
+
+/// **功能：** 关闭一个文件。 <br>
+/// **参数：**  <br>
+///         - `fd` 表示待关闭文件的文件描述符。<br>
+/// **返回值：** 成功返回 0，fd 无效时返回 -1。<br>
+/// **syscall ID：** 57
+pub fn sys_close(fd: usize) -> isize {
+    syscall(SYSCALL_CLOSE, [fd, 0, 0])
+}
+
+/// **功能：** 为当前进程打开一个管道。 <br>
+/// **参数：**  <br>
+///         - `pipe` 表示应用地址空间中的一个长度为 2 的 usize 数组，内核依次写入管道读端和写端的文件描述符。<br>
+/// **返回值：** 成功返回 0。<br>
+/// **syscall ID：** 59
+pub fn sys_pipe(pipe: &mut [usize]) -> isize {
+    syscall(SYSCALL_PIPE, [pipe.as_mut_ptr() as usize, 0, 0])
+}
+
+/// **功能：** 复制一个文件描述符。 <br>
+/// **参数：**  <br>
+///         - `fd` 表示待复制的文件描述符。<br>
+/// **返回值：** 成功时返回新分配的（最小的空闲）文件描述符，fd 无效时返回 -1。<br>
+/// **syscall ID：** 23
+pub fn sys_dup(fd: usize) -> isize {
+    syscall(SYSCALL_DUP, [fd, 0, 0])
+}
+
+/// **功能：** 将文件描述符复制到指定位置，该位置原先打开的文件将被关闭。 <br>
+/// **参数：**  <br>
+///         - `old_fd` 表示待复制的文件描述符；<br>
+///         - `new_fd` 表示复制的目标文件描述符。<br>
+/// **返回值：** 成功时返回 new_fd，old_fd 无效时返回 -1。<br>
+/// **syscall ID：** 24
+pub fn sys_dup2(old_fd: usize, new_fd: usize) -> isize {
+    syscall(SYSCALL_DUP2, [old_fd, new_fd, 0])
+}
+
+/// **功能：** 从文件中读取一段内容到缓冲区。 <br>
+/// **参数：**  <br>
+///         - `fd` 表示待读取文件的文件描述符；<br>
+///         - `buffer` 表示内存中缓冲区。<br>
+/// **返回值：** 返回成功读取的长度，出错时返回 -1。<br>
+/// **syscall ID：** 63
+pub fn sys_read(fd: usize, buffer: &mut [u8]) -> isize {
+    syscall(SYSCALL_READ, [fd, buffer.as_mut_ptr() as usize, buffer.len()])
+}
+
 /// **功能：** 将内存中缓冲区中的数据写入文件。 <br>
 /// **参数：**  <br>
 ///         - `fd` 表示待写入文件的文件描述符；<br>
 ///         - `buf` 表示内存中缓冲区的起始地址；<br>
 ///         - `len` 表示内存中缓冲区的长度。<br>
-/// **返回值：** 返回成功写入的长度。<br>
+/// **返回值：** 返回成功写入的长度，出错时返回 -1。<br>
 /// **syscall ID：** 64
 pub fn sys_write(fd: usize, buffer: &[u8]) -> isize {
     syscall(SYSCALL_WRITE, [fd, buffer.as_ptr() as usize, buffer.len()])
 }
 
-/// **功能：** 退出应用程序并将返回值告知批处理系统。 <br>
+/// **功能：** 获取文件的元数据。 <br>
+/// **参数：**  <br>
+///         - `fd` 表示文件描述符；<br>
+///         - `st` 表示用于保存元数据的 Stat 结构体。<br>
+/// **返回值：** 成功返回 0，fd 无效时返回 -1。<br>
+/// **syscall ID：** 80
+pub fn sys_fstat(fd: usize, st: &mut Stat) -> isize {
+    syscall(SYSCALL_FSTAT, [fd, st as *mut _ as usize, 0])
+}
+
+/// **功能：** 将文件系统块缓存中的所有脏块写回磁盘。 <br>
+/// **参数：** 无。<br>
+/// **返回值：** 总是返回 0。<br>
+/// **syscall ID：** 81
+pub fn sys_sync() -> isize {
+    syscall(SYSCALL_SYNC, [0, 0, 0])
+}
+
+/// **功能：** 将文件的脏数据写回磁盘。 <br>
 /// **参数：**  <br>
-///         - `exit_code` 表示应用程序的返回值。<br>
+///         - `fd` 表示待写回文件的文件描述符。<br>
+/// **返回值：** 成功返回 0，fd 无效时返回 -1。<br>
+/// **syscall ID：** 82
+pub fn sys_fsync(fd: usize) -> isize {
+    syscall(SYSCALL_FSYNC, [fd, 0, 0])
+}
+
+/// **功能：** 退出当前线程，主线程退出时整个进程随之退出。 <br>
+/// **参数：**  <br>
+///         - `exit_code` 表示线程的退出码，主线程的退出码即进程的退出码。<br>
 /// **返回值：** 该系统调用不应该返回。<br>
 /// **syscall ID：** 93
 pub fn sys_exit(exit_code: i32) -> isize {
     syscall(SYSCALL_EXIT, [exit_code as usize, 0, 0])
-}
\ No newline at end of file
+}
+
+/// **功能：** 应用主动交出CPU所有权并切换到其他应用。 <br>
+/// **返回值：** 总是返回0。<br>
+/// **syscall ID：** 124
+pub fn sys_yield() -> isize {
+    syscall(SYSCALL_YIELD, [0, 0, 0])
+}
+
+/// **功能：** 设置当前线程自身的优先级。内核总是调度有效优先级最高的就绪线程，优先级相同时先进先出。 <br>
+/// **参数：**  <br>
+///         - `prio` 表示优先级，取值范围为[0, 31]，数值越大优先级越高，默认为16。<br>
+/// **返回值：** 成功时返回`prio`，参数无效时返回-1。<br>
+/// **syscall ID：** 140
+pub fn sys_set_priority(prio: isize) -> isize {
+    syscall(SYSCALL_SET_PRIORITY, [prio as usize, 0, 0])
+}
+
+/// **功能：** 获取当前线程的有效优先级，持有阻塞锁时可能因优先级继承而高于其自身的优先级。 <br>
+/// **返回值：** 当前线程的有效优先级。<br>
+/// **syscall ID：** 141
+pub fn sys_get_priority() -> isize {
+    syscall(SYSCALL_GET_PRIORITY, [0, 0, 0])
+}
+
+/// **功能：** 获取当前时间。 <br>
+/// **参数：**  <br>
+///         - `ts` 表示用于保存时间的TimeVal结构体；<br>
+///         - `tz` 表示时区，目前不使用。<br>
+/// **返回值：** 成功时返回0。<br>
+/// **syscall ID：** 169
+pub fn sys_get_time(ts: &mut TimeVal, tz: usize) -> isize {
+    syscall(SYSCALL_GET_TIME, [ts as *mut TimeVal as usize, tz, 0])
+}
+
+/// **功能：** 获取当前进程的进程标识符。 <br>
+/// **返回值：** 当前进程的pid。<br>
+/// **syscall ID：** 172
+pub fn sys_getpid() -> isize {
+    syscall(SYSCALL_GETPID, [0, 0, 0])
+}
+
+/// **功能：** 复制当前进程，只能由单线程进程的主线程调用。 <br>
+/// **返回值：** 父进程返回子进程的pid，子进程返回0，进程中还有其他线程时返回-1。<br>
+/// **syscall ID：** 220
+pub fn sys_fork() -> isize {
+    syscall(SYSCALL_FORK, [0, 0, 0])
+}
+
+/// **功能：** 清空当前进程的地址空间并加载一个新的可执行文件，只能由单线程进程的主线程调用。 <br>
+/// **参数：**  <br>
+///         - `path` 表示要加载的可执行文件名，须以'\0'结尾；<br>
+///         - `args` 表示命令行参数字符串的起始地址数组，各字符串须以'\0'结尾，数组须以空指针结尾。<br>
+/// **返回值：** 出错时返回-1，成功时不返回。<br>
+/// **syscall ID：** 221
+pub fn sys_exec(path: &str, args: &[*const u8]) -> isize {
+    syscall(SYSCALL_EXEC, [path.as_ptr() as usize, args.as_ptr() as usize, 0])
+}
+
+/// **功能：** 等待子进程退出并回收其资源。 <br>
+/// **参数：**  <br>
+///         - `pid` 表示要等待的子进程的pid，为-1时表示任意子进程；<br>
+///         - `exit_code` 表示保存子进程退出码的地址。<br>
+/// **返回值：** 不存在符合条件的子进程时返回-1，子进程均未退出时返回-2，否则返回回收的子进程的pid。<br>
+/// **syscall ID：** 260
+pub fn sys_waitpid(pid: isize, exit_code: *mut i32) -> isize {
+    syscall(SYSCALL_WAITPID, [pid as usize, exit_code as usize, 0])
+}
+
+/// **功能：** 将文件或匿名内存映射到当前进程的地址空间，各页在首次访问时才载入。 <br>
+/// **参数：**  <br>
+///         - `addr` 表示期望的起始地址，仅作为提示，为0时由内核选择；<br>
+///         - `len` 表示映射的字节数，向上取整至页大小；<br>
+///         - `prot` 表示内存保护标志，取值见`MmapProt`；<br>
+///         - `flags` 表示映射标志，取值见`MmapFlags`，`SHARED`与`PRIVATE`须指定且仅指定其一；<br>
+///         - `fd` 表示要映射的文件的文件描述符，匿名映射时被忽略；<br>
+///         - `offset` 表示映射起始处在文件中的偏移，须按页对齐。<br>
+/// **返回值：** 成功时返回映射的起始地址，失败时返回-1。<br>
+/// **syscall ID：** 222
+pub fn sys_mmap(addr: usize, len: usize, prot: u32, flags: u32, fd: usize, offset: usize) -> isize {
+    syscall6(
+        SYSCALL_MMAP,
+        [addr, len, prot as usize, flags as usize, fd, offset],
+    )
+}
+
+/// **功能：** 解除一段内存映射，共享映射中修改过的页写回文件。 <br>
+/// **参数：**  <br>
+///         - `addr` 表示要解除映射的起始地址，须按页对齐；<br>
+///         - `len` 表示要解除映射的字节数。<br>
+/// **返回值：** 成功时返回0，区间中有非mmap映射的内存时返回-1。<br>
+/// **syscall ID：** 215
+pub fn sys_munmap(addr: usize, len: usize) -> isize {
+    syscall(SYSCALL_MUNMAP, [addr, len, 0])
+}
+
+/// **功能：** 在当前进程中创建一个线程，新线程与其他线程共享地址空间与文件描述符表，并继承当前线程自身的优先级。 <br>
+/// **参数：**  <br>
+///         - `entry` 表示新线程的入口地址；<br>
+///         - `arg` 表示传给新线程的参数，位于其a0寄存器中。<br>
+/// **返回值：** 成功时返回新线程的tid，线程数达到上限时返回-1。<br>
+/// **syscall ID：** 1000
+pub fn sys_thread_create(entry: usize, arg: usize) -> isize {
+    syscall(SYSCALL_THREAD_CREATE, [entry, arg, 0])
+}
+
+/// **功能：** 获取当前线程在进程中的线程标识符。 <br>
+/// **返回值：** 当前线程的tid，主线程的tid为0。<br>
+/// **syscall ID：** 1001
+pub fn sys_gettid() -> isize {
+    syscall(SYSCALL_GETTID, [0, 0, 0])
+}
+
+/// **功能：** 等待当前进程中的一个线程退出并回收其资源。 <br>
+/// **参数：**  <br>
+///         - `tid` 表示要等待的线程的tid。<br>
+/// **返回值：** 线程不存在或等待自身时返回-1，线程尚未退出时返回-2，否则返回线程的退出码。<br>
+/// **syscall ID：** 1002
+pub fn sys_waittid(tid: usize) -> isize {
+    syscall(SYSCALL_WAITTID, [tid, 0, 0])
+}
+
+/// **功能：** 为当前进程创建一个互斥锁。 <br>
+/// **参数：**  <br>
+///         - `blocking` 为true时创建阻塞锁，等待的线程被挂起直至锁被释放；为false时创建自旋锁，等待的线程反复让出CPU后重试。<br>
+/// **返回值：** 互斥锁的编号。阻塞锁支持优先级继承：持有者的有效优先级不低于任何等待者。<br>
+/// **syscall ID：** 1010
+pub fn sys_mutex_create(blocking: bool) -> isize {
+    syscall(SYSCALL_MUTEX_CREATE, [blocking as usize, 0, 0])
+}
+
+/// **功能：** 获取互斥锁，锁被占用时等待。 <br>
+/// **参数：**  <br>
+///         - `mutex_id` 表示互斥锁的编号。<br>
+/// **返回值：** 成功时返回0，编号无效时返回-1；开启死锁检测且获取锁可能导致死锁时不等待，返回-0xDEAD。<br>
+/// **syscall ID：** 1011
+pub fn sys_mutex_lock(mutex_id: usize) -> isize {
+    syscall(SYSCALL_MUTEX_LOCK, [mutex_id, 0, 0])
+}
+
+/// **功能：** 释放互斥锁。 <br>
+/// **参数：**  <br>
+///         - `mutex_id` 表示互斥锁的编号。<br>
+/// **返回值：** 成功时返回0，编号无效或锁未被占用时返回-1。<br>
+/// **syscall ID：** 1012
+pub fn sys_mutex_unlock(mutex_id: usize) -> isize {
+    syscall(SYSCALL_MUTEX_UNLOCK, [mutex_id, 0, 0])
+}
+
+/// **功能：** 为当前进程创建一个信号量。 <br>
+/// **参数：**  <br>
+///         - `res_count` 表示信号量的初始计数。<br>
+/// **返回值：** 信号量的编号。<br>
+/// **syscall ID：** 1020
+pub fn sys_semaphore_create(res_count: usize) -> isize {
+    syscall(SYSCALL_SEMAPHORE_CREATE, [res_count, 0, 0])
+}
+
+/// **功能：** 对信号量执行V操作，计数加一并唤醒一个等待的线程。 <br>
+/// **参数：**  <br>
+///         - `sem_id` 表示信号量的编号。<br>
+/// **返回值：** 成功时返回0，编号无效时返回-1。<br>
+/// **syscall ID：** 1021
+pub fn sys_semaphore_up(sem_id: usize) -> isize {
+    syscall(SYSCALL_SEMAPHORE_UP, [sem_id, 0, 0])
+}
+
+/// **功能：** 对信号量执行P操作，计数减一，计数不足时等待。 <br>
+/// **参数：**  <br>
+///         - `sem_id` 表示信号量的编号。<br>
+/// **返回值：** 成功时返回0，编号无效时返回-1；开启死锁检测且等待可能导致死锁时不等待，返回-0xDEAD。<br>
+/// **syscall ID：** 1022
+pub fn sys_semaphore_down(sem_id: usize) -> isize {
+    syscall(SYSCALL_SEMAPHORE_DOWN, [sem_id, 0, 0])
+}
+
+/// **功能：** 为当前进程创建一个条件变量。 <br>
+/// **返回值：** 条件变量的编号。<br>
+/// **syscall ID：** 1030
+pub fn sys_condvar_create() -> isize {
+    syscall(SYSCALL_CONDVAR_CREATE, [0, 0, 0])
+}
+
+/// **功能：** 唤醒一个等待条件变量的线程，没有线程等待时不产生任何效果。 <br>
+/// **参数：**  <br>
+///         - `condvar_id` 表示条件变量的编号。<br>
+/// **返回值：** 成功时返回0，编号无效时返回-1。<br>
+/// **syscall ID：** 1031
+pub fn sys_condvar_signal(condvar_id: usize) -> isize {
+    syscall(SYSCALL_CONDVAR_SIGNAL, [condvar_id, 0, 0])
+}
+
+/// **功能：** 释放互斥锁并等待条件变量，被唤醒后重新获取互斥锁。 <br>
+/// **参数：**  <br>
+///         - `condvar_id` 表示条件变量的编号；<br>
+///         - `mutex_id` 表示调用者持有的互斥锁的编号。<br>
+/// **返回值：** 成功时返回0，编号无效或互斥锁未被占用时返回-1。<br>
+/// **syscall ID：** 1032
+pub fn sys_condvar_wait(condvar_id: usize, mutex_id: usize) -> isize {
+    syscall(SYSCALL_CONDVAR_WAIT, [condvar_id, mutex_id, 0])
+}
+
+/// **功能：** 开启或关闭当前进程的死锁检测。开启后，获取互斥锁与对信号量执行P操作前，
+/// 内核以银行家算法检查满足请求后各线程能否全部运行结束，不能时拒绝请求。 <br>
+/// **参数：**  <br>
+///         - `enabled` 为1时开启，为0时关闭。<br>
+/// **返回值：** 成功时返回0，参数无效时返回-1。<br>
+/// **syscall ID：** 469
+pub fn sys_enable_deadlock_detect(enabled: usize) -> isize {
+    syscall(SYSCALL_ENABLE_DEADLOCK_DETECT, [enabled, 0, 0])
+}
+
+/// **功能：** 对位于用户地址空间中的32位futex执行等待或唤醒操作。 <br>
+/// **参数：**  <br>
+///         - `uaddr` 表示futex的地址，须按4字节对齐；<br>
+///         - `op` 为0（FUTEX_WAIT）时，若futex的值仍为`val`则阻塞直至被唤醒；为1（FUTEX_WAKE）时唤醒至多`val`个在该futex上等待的线程。<br>
+/// **返回值：** FUTEX_WAIT被唤醒时返回0，futex的值已改变时返回-2；FUTEX_WAKE返回唤醒的线程数；操作无效或地址非法时返回-1。<br>
+/// **syscall ID：** 98
+pub fn sys_futex(uaddr: usize, op: usize, val: usize) -> isize {
+    syscall(SYSCALL_FUTEX, [uaddr, op, val])
+}